@@ -0,0 +1,66 @@
+//! Derive macros for `diesel-turso`
+//!
+//! This crate is re-exported by `diesel-turso` with the `derive` feature,
+//! don't depend on it directly.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod rename;
+mod turso_enum;
+
+/// Maps a unit enum to a `TEXT` or `INTEGER` column
+///
+/// Implements `AsExpression`, `ToSql`, `FromSql` and `Queryable` for the
+/// enum and the given SQL type, which is `Text` unless specified otherwise.
+/// `Text` stores the names of the variants, the integer types store their
+/// discriminants. Loading a value which doesn't belong to any variant returns
+/// a deserialization error listing the accepted values, loading a value of
+/// the other storage class fails as well.
+///
+/// # Attributes
+///
+/// On the enum:
+///
+/// - `#[turso(sql_type = Integer)]`: the SQL type of the column, one of
+///   `Text`, `TinyInt`, `SmallInt`, `Integer` and `BigInt`, other types fail
+///   to compile. Defaults to `diesel::sql_types::Text`.
+/// - `#[turso(rename_all = "snake_case")]`: renames all variants, one of
+///   `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`, `snake_case`,
+///   `SCREAMING_SNAKE_CASE`, `kebab-case` and `SCREAMING-KEBAB-CASE`.
+///
+/// On a variant:
+///
+/// - `#[turso(rename = "name")]`: the name stored for this variant, takes
+///   precedence over `rename_all`.
+///
+/// # Example
+///
+/// ```ignore
+/// use diesel::sql_types::Integer;
+/// use diesel_turso::TursoEnum;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, TursoEnum)]
+/// #[turso(rename_all = "snake_case")]
+/// enum Status {
+///     Active,
+///     OnHold,
+///     #[turso(rename = "done")]
+///     Finished,
+/// }
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, TursoEnum)]
+/// #[turso(sql_type = Integer)]
+/// enum Priority {
+///     Low = 1,
+///     Normal = 5,
+///     High = 10,
+/// }
+/// ```
+#[proc_macro_derive(TursoEnum, attributes(turso))]
+pub fn derive_turso_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    turso_enum::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
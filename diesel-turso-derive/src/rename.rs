@@ -0,0 +1,99 @@
+//! Rename rules for `#[turso(rename_all = "...")]`, the same as the ones of serde
+
+use syn::LitStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RenameRule {
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+const RULES: &[(&str, RenameRule)] = &[
+    ("lowercase", RenameRule::LowerCase),
+    ("UPPERCASE", RenameRule::UpperCase),
+    ("PascalCase", RenameRule::PascalCase),
+    ("camelCase", RenameRule::CamelCase),
+    ("snake_case", RenameRule::SnakeCase),
+    ("SCREAMING_SNAKE_CASE", RenameRule::ScreamingSnakeCase),
+    ("kebab-case", RenameRule::KebabCase),
+    ("SCREAMING-KEBAB-CASE", RenameRule::ScreamingKebabCase),
+];
+
+impl RenameRule {
+    pub(crate) fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        let rule = lit.value();
+        RULES
+            .iter()
+            .find(|(name, _)| *name == rule)
+            .map(|(_, rule)| *rule)
+            .ok_or_else(|| {
+                let names = RULES
+                    .iter()
+                    .map(|(name, _)| format!("`{name}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                syn::Error::new(
+                    lit.span(),
+                    format!("unknown rename rule `{rule}`, expected one of {names}"),
+                )
+            })
+    }
+
+    /// Applies the rule to a variant name, which is expected to be PascalCase
+    pub(crate) fn apply(self, variant: &str) -> String {
+        match self {
+            Self::LowerCase => variant.to_ascii_lowercase(),
+            Self::UpperCase => variant.to_ascii_uppercase(),
+            Self::PascalCase => variant.to_owned(),
+            Self::CamelCase => {
+                let mut chars = variant.chars();
+                match chars.next() {
+                    Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+                    None => String::new(),
+                }
+            }
+            Self::SnakeCase => {
+                let mut snake = String::new();
+                for (i, c) in variant.char_indices() {
+                    if i > 0 && c.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(c.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnakeCase => Self::SnakeCase.apply(variant).to_ascii_uppercase(),
+            Self::KebabCase => Self::SnakeCase.apply(variant).replace('_', "-"),
+            Self::ScreamingKebabCase => Self::ScreamingSnakeCase.apply(variant).replace('_', "-"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenameRule;
+
+    #[test]
+    fn rename_rules() {
+        let cases = [
+            (RenameRule::LowerCase, "onhold"),
+            (RenameRule::UpperCase, "ONHOLD"),
+            (RenameRule::PascalCase, "OnHold"),
+            (RenameRule::CamelCase, "onHold"),
+            (RenameRule::SnakeCase, "on_hold"),
+            (RenameRule::ScreamingSnakeCase, "ON_HOLD"),
+            (RenameRule::KebabCase, "on-hold"),
+            (RenameRule::ScreamingKebabCase, "ON-HOLD"),
+        ];
+        for (rule, expected) in cases {
+            assert_eq!(rule.apply("OnHold"), expected, "{rule:?}");
+        }
+        assert_eq!(RenameRule::SnakeCase.apply("A"), "a");
+    }
+}
@@ -0,0 +1,216 @@
+//! `#[derive(TursoEnum)]`
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Attribute, Data, DeriveInput, Fields, Ident, LitStr, Type};
+
+use crate::rename::RenameRule;
+
+/// Options given with `#[turso(...)]` on the enum
+struct EnumOptions {
+    sql_type: Type,
+    rename_all: Option<RenameRule>,
+}
+
+impl EnumOptions {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut sql_type = None;
+        let mut rename_all = None;
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("turso")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("sql_type") {
+                    sql_type = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("rename_all") {
+                    let rule: LitStr = meta.value()?.parse()?;
+                    rename_all = Some(RenameRule::from_lit(&rule)?);
+                } else {
+                    return Err(
+                        meta.error("unknown attribute, expected `sql_type` or `rename_all`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        Ok(Self {
+            sql_type: sql_type.unwrap_or_else(|| parse_quote!(diesel::sql_types::Text)),
+            rename_all,
+        })
+    }
+}
+
+/// The name given with `#[turso(rename = "...")]` on a variant
+fn variant_rename(attrs: &[Attribute]) -> syn::Result<Option<String>> {
+    let mut rename = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("turso")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let name: LitStr = meta.value()?.parse()?;
+                rename = Some(name.value());
+                Ok(())
+            } else {
+                Err(meta.error("unknown attribute, expected `rename`"))
+            }
+        })?;
+    }
+    Ok(rename)
+}
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`TursoEnum` can only be derived for enums",
+        ));
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`TursoEnum` can't be derived for generic enums",
+        ));
+    }
+    if data.variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`TursoEnum` can't be derived for enums without variants",
+        ));
+    }
+
+    let options = EnumOptions::parse(&input.attrs)?;
+    let mut variants = Vec::<&Ident>::new();
+    let mut names = Vec::<String>::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "`TursoEnum` only supports variants without fields",
+            ));
+        }
+        let ident = variant.ident.to_string();
+        let name = match (variant_rename(&variant.attrs)?, options.rename_all) {
+            (Some(name), _) => name,
+            (None, Some(rule)) => rule.apply(&ident),
+            (None, None) => ident,
+        };
+        if names.contains(&name) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                format!("more than one variant is stored as `{name}`"),
+            ));
+        }
+        variants.push(&variant.ident);
+        names.push(name);
+    }
+
+    let enum_ident = &input.ident;
+    let enum_name = enum_ident.to_string();
+    let sql_type = &options.sql_type;
+    let nullable = quote!(diesel::sql_types::Nullable<#sql_type>);
+
+    Ok(quote! {
+        const _: () = {
+            use diesel::deserialize::{self, FromSql, Queryable};
+            use diesel::expression::AsExpression;
+            use diesel::internal::derives::as_expression::Bound;
+            use diesel::serialize::{self, IsNull, Output, ToSql};
+            use diesel_turso::backend::TursoBackend;
+            use diesel_turso::derive::{EnumSqlType, EnumStorage};
+            use diesel_turso::TursoValue;
+
+            // Fails to compile for SQL types which can't store an enum
+            const STORAGE: EnumStorage = <#sql_type as EnumSqlType>::STORAGE;
+
+            impl AsExpression<#sql_type> for #enum_ident {
+                type Expression = Bound<#sql_type, Self>;
+
+                fn as_expression(self) -> <Self as AsExpression<#sql_type>>::Expression {
+                    Bound::new(self)
+                }
+            }
+
+            impl AsExpression<#nullable> for #enum_ident {
+                type Expression = Bound<#nullable, Self>;
+
+                fn as_expression(self) -> <Self as AsExpression<#nullable>>::Expression {
+                    Bound::new(self)
+                }
+            }
+
+            impl<'__expr> AsExpression<#sql_type> for &'__expr #enum_ident {
+                type Expression = Bound<#sql_type, Self>;
+
+                fn as_expression(self) -> <Self as AsExpression<#sql_type>>::Expression {
+                    Bound::new(self)
+                }
+            }
+
+            impl<'__expr> AsExpression<#nullable> for &'__expr #enum_ident {
+                type Expression = Bound<#nullable, Self>;
+
+                fn as_expression(self) -> <Self as AsExpression<#nullable>>::Expression {
+                    Bound::new(self)
+                }
+            }
+
+            impl ToSql<#sql_type, TursoBackend> for #enum_ident {
+                fn to_sql<'__b>(
+                    &'__b self,
+                    out: &mut Output<'__b, '_, TursoBackend>,
+                ) -> serialize::Result {
+                    match STORAGE {
+                        EnumStorage::Integer => out.set_value(match self {
+                            #(Self::#variants => Self::#variants as i64,)*
+                        }),
+                        EnumStorage::Text => out.set_value(match self {
+                            #(Self::#variants => #names,)*
+                        }),
+                    }
+                    Ok(IsNull::No)
+                }
+            }
+
+            impl ToSql<#nullable, TursoBackend> for #enum_ident {
+                fn to_sql<'__b>(
+                    &'__b self,
+                    out: &mut Output<'__b, '_, TursoBackend>,
+                ) -> serialize::Result {
+                    ToSql::<#sql_type, TursoBackend>::to_sql(self, out)
+                }
+            }
+
+            impl FromSql<#sql_type, TursoBackend> for #enum_ident {
+                fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+                    match STORAGE {
+                        EnumStorage::Text => match value.as_text() {
+                            #(Some(#names) => return Ok(Self::#variants),)*
+                            _ => {}
+                        },
+                        EnumStorage::Integer => {
+                            if let Some(integer) = value.as_integer() {
+                                #(
+                                    if integer == Self::#variants as i64 {
+                                        return Ok(Self::#variants);
+                                    }
+                                )*
+                            }
+                        }
+                    }
+                    Err(diesel_turso::derive::unknown_enum_value(
+                        #enum_name,
+                        STORAGE,
+                        &value,
+                        &[#(#names),*],
+                        &[#(Self::#variants as i64),*],
+                    ))
+                }
+            }
+
+            impl Queryable<#sql_type, TursoBackend> for #enum_ident {
+                type Row = Self;
+
+                fn build(row: Self::Row) -> deserialize::Result<Self> {
+                    Ok(row)
+                }
+            }
+        };
+    })
+}
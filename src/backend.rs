@@ -1,8 +1,5 @@
 use diesel::{
-    backend::{
-        sql_dialect::{self, returning_clause::DoesNotSupportReturningClause},
-        Backend, DieselReserveSpecialization, SqlDialect, TrustedBackend,
-    },
+    backend::{sql_dialect, Backend, DieselReserveSpecialization, SqlDialect, TrustedBackend},
     sql_types::TypeMetadata,
 };
 
@@ -44,8 +41,9 @@ impl TypeMetadata for TursoBackend {
 }
 
 impl SqlDialect for TursoBackend {
-    // this is actually not true, but i would need to properly implement the ast for this, since the sqlite one is not exported
-    type ReturningClause = DoesNotSupportReturningClause;
+    // `AsyncTursoConnection` emulates single row `INSERT ... RETURNING` for
+    // databases without support for it
+    type ReturningClause = SqliteReturningClause;
 
     type OnConflictClause = SqliteOnConflictClause;
 
@@ -59,7 +57,7 @@ impl SqlDialect for TursoBackend {
     type SelectStatementSyntax = sql_dialect::select_statement_syntax::AnsiSqlSelectStatement;
 
     type ExistsSyntax = sql_dialect::exists_syntax::AnsiSqlExistsSyntax;
-    type ArrayComparison = sql_dialect::array_comparison::AnsiSqlArrayComparison;
+    type ArrayComparison = TursoArrayComparison;
     type AliasSyntax = sql_dialect::alias_syntax::AsAliasSyntax;
 
     type WindowFrameClauseGroupSupport =
@@ -84,6 +82,10 @@ impl sql_dialect::on_conflict_clause::PgLikeOnConflictClause for SqliteOnConflic
 #[derive(Debug, Copy, Clone)]
 pub struct SqliteBatchInsert;
 
+/// Binds long `IN` lists as a single JSON array, see `query_builder::array_comparison`
+#[derive(Debug, Copy, Clone)]
+pub struct TursoArrayComparison;
+
 #[derive(Debug, Copy, Clone)]
 pub struct SqliteReturningClause;
 
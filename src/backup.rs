@@ -0,0 +1,438 @@
+//! Online backups
+//!
+//! A backup copies the schema, the rows of every table, the `AUTOINCREMENT`
+//! counters and the `user_version` and `application_id` into a new
+//! database, in batches read in one transaction. Writers on other
+//! connections aren't blocked, the backup contains the data of the moment
+//! its transaction started.
+//!
+//! Rowids are copied as well, so they stay valid for tables without an
+//! `INTEGER PRIMARY KEY`.
+
+use std::fmt::Display;
+use std::path::Path;
+
+use diesel::result::{DatabaseErrorKind, Error};
+use diesel::QueryResult;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AnsiTransactionManager, AsyncConnection, TransactionManager};
+use turso::Value;
+
+use crate::binding::{TursoConnection, TursoDatabase};
+use crate::utils::{quote_identifier, TursoError};
+use crate::AsyncTursoConnection;
+
+/// The number of rows read from the source database at once
+const BATCH_SIZE: i64 = 1000;
+
+/// How far a backup or a restore is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupProgress {
+    copied_rows: u64,
+    total_rows: u64,
+}
+
+impl BackupProgress {
+    /// The number of rows copied so far
+    pub fn copied_rows(&self) -> u64 {
+        self.copied_rows
+    }
+
+    /// The number of rows of all tables
+    pub fn total_rows(&self) -> u64 {
+        self.total_rows
+    }
+
+    /// Whether all rows are copied
+    pub fn is_done(&self) -> bool {
+        self.copied_rows == self.total_rows
+    }
+}
+
+fn database_error(error: impl Display) -> Error {
+    Error::DatabaseError(
+        DatabaseErrorKind::Unknown,
+        Box::new(TursoError {
+            message: error.to_string(),
+        }),
+    )
+}
+
+async fn query(
+    conn: &TursoConnection,
+    sql: &str,
+    binds: Vec<Value>,
+) -> QueryResult<Vec<Vec<(String, Value)>>> {
+    let mut stmt = conn.prepare(sql);
+    stmt.bind(binds);
+    let result = conn.query(&stmt).await.map_err(database_error)?;
+    match result.error() {
+        Some(error) => Err(database_error(error)),
+        None => Ok(result.results().unwrap_or_default()),
+    }
+}
+
+async fn execute(conn: &TursoConnection, sql: &str, binds: Vec<Value>) -> QueryResult<()> {
+    let mut stmt = conn.prepare(sql);
+    stmt.bind(binds);
+    let result = conn.execute(&stmt).await.map_err(database_error)?;
+    match result.error() {
+        Some(error) => Err(database_error(error)),
+        None => Ok(()),
+    }
+}
+
+/// An entry of `sqlite_schema`
+struct SchemaObject {
+    kind: String,
+    name: String,
+    sql: String,
+}
+
+async fn schema(conn: &TursoConnection) -> QueryResult<Vec<SchemaObject>> {
+    // Internal tables like `sqlite_sequence` are created by the database,
+    // `_` is a wildcard of `LIKE`
+    let rows = query(
+        conn,
+        "SELECT type, name, sql FROM sqlite_schema \
+         WHERE sql IS NOT NULL AND substr(name, 1, 7) <> 'sqlite_' ORDER BY rowid",
+        Vec::new(),
+    )
+    .await?;
+    rows.into_iter()
+        .map(|row| match <[_; 3]>::try_from(row) {
+            Ok([(_, Value::Text(kind)), (_, Value::Text(name)), (_, Value::Text(sql))]) => {
+                Ok(SchemaObject { kind, name, sql })
+            }
+            _ => Err(database_error("Unexpected row in `sqlite_schema`")),
+        })
+        .collect()
+}
+
+/// The single integer `sql` returns
+async fn query_integer(conn: &TursoConnection, sql: &str) -> QueryResult<i64> {
+    match query(conn, sql, Vec::new()).await?.as_slice() {
+        [row] => match row.as_slice() {
+            [(_, Value::Integer(value))] => Ok(*value),
+            _ => Err(database_error(format!("Unexpected result of `{sql}`"))),
+        },
+        _ => Err(database_error(format!("Unexpected result of `{sql}`"))),
+    }
+}
+
+/// Whether the table is declared `WITHOUT ROWID`
+fn is_without_rowid(sql: &str) -> bool {
+    // The table options follow the closing parenthesis of the columns
+    let options = sql[sql.rfind(')').map_or(0, |end| end + 1)..].to_ascii_uppercase();
+    let words = options
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>();
+    words.windows(2).any(|pair| pair == ["WITHOUT", "ROWID"])
+}
+
+/// How the rows of a table are read and written
+struct TableCopy {
+    name: String,
+    /// The columns which identify a row, in the order rows are read
+    key: Vec<String>,
+    /// Whether the key is the rowid, which isn't one of the columns
+    rowid: bool,
+    /// The columns whose values are copied, generated columns are computed
+    /// by the target
+    columns: Vec<String>,
+}
+
+impl TableCopy {
+    async fn new(conn: &TursoConnection, table: &SchemaObject) -> QueryResult<Self> {
+        let name = quote_identifier(&table.name);
+        // The columns are `cid`, `name`, `type`, `notnull`, `dflt_value`,
+        // `pk` and `hidden`, which is `2` or `3` for generated columns
+        let info = query(conn, &format!("PRAGMA table_xinfo({name})"), Vec::new()).await?;
+        let mut columns = Vec::new();
+        let mut primary_key = Vec::new();
+        for row in info {
+            let value = |index: usize| row.get(index).map(|(_, value)| value);
+            match (value(1), value(5), value(6)) {
+                (
+                    Some(Value::Text(column)),
+                    Some(Value::Integer(pk)),
+                    Some(Value::Integer(hidden)),
+                ) => {
+                    if *hidden == 0 {
+                        columns.push(quote_identifier(column));
+                    }
+                    if *pk > 0 {
+                        primary_key.push((*pk, quote_identifier(column)));
+                    }
+                }
+                _ => return Err(database_error("Unexpected row of `PRAGMA table_xinfo`")),
+            }
+        }
+        primary_key.sort();
+        let rowid = !is_without_rowid(&table.sql);
+        let key = if rowid {
+            vec!["rowid".to_owned()]
+        } else {
+            primary_key.into_iter().map(|(_, column)| column).collect()
+        };
+        Ok(Self {
+            name,
+            key,
+            rowid,
+            columns,
+        })
+    }
+
+    /// Reads the next batch of rows after the row with the key `after`, each
+    /// row starts with its key
+    async fn read_batch(
+        &self,
+        conn: &TursoConnection,
+        after: Option<Vec<Value>>,
+    ) -> QueryResult<Vec<Vec<(String, Value)>>> {
+        let key = self.key.join(", ");
+        let columns = self.columns.join(", ");
+        let filter = match after {
+            Some(_) => {
+                let placeholders = vec!["?"; self.key.len()].join(", ");
+                format!(" WHERE ({key}) > ({placeholders})")
+            }
+            None => String::new(),
+        };
+        let sql = format!(
+            "SELECT {key}, {columns} FROM {}{filter} ORDER BY {key} LIMIT {BATCH_SIZE}",
+            self.name
+        );
+        query(conn, &sql, after.unwrap_or_default()).await
+    }
+
+    async fn write_row(&self, conn: &TursoConnection, row: &[(String, Value)]) -> QueryResult<()> {
+        // The primary key of a `WITHOUT ROWID` table is part of the columns,
+        // the rowid is written in front of them
+        let (columns, row) = if self.rowid {
+            (format!("rowid, {}", self.columns.join(", ")), row)
+        } else {
+            (self.columns.join(", "), &row[self.key.len()..])
+        };
+        let values = row
+            .iter()
+            .map(|(_, value)| value.clone())
+            .collect::<Vec<_>>();
+        let placeholders = vec!["?"; values.len()].join(", ");
+        let sql = format!(
+            "INSERT INTO {} ({columns}) VALUES ({placeholders})",
+            self.name
+        );
+        execute(conn, &sql, values).await
+    }
+}
+
+/// Copies the schema and the rows of `source` into the new database of
+/// `target`, in one transaction of `target`
+async fn copy_database(
+    source: &TursoConnection,
+    target: &TursoConnection,
+    progress: &mut (dyn FnMut(BackupProgress) + Send),
+) -> QueryResult<()> {
+    target
+        .execute_batch(&target.prepare("BEGIN"))
+        .await
+        .map_err(database_error)?;
+    let statement = match copy_objects(source, target, progress).await {
+        Ok(()) => "COMMIT",
+        Err(e) => {
+            let _ = target.execute_batch(&target.prepare("ROLLBACK")).await;
+            return Err(e);
+        }
+    };
+    target
+        .execute_batch(&target.prepare(statement))
+        .await
+        .map(|_| ())
+        .map_err(database_error)
+}
+
+/// Tables are created first, indexes, views and triggers once the rows are
+/// copied
+async fn copy_objects(
+    source: &TursoConnection,
+    target: &TursoConnection,
+    progress: &mut (dyn FnMut(BackupProgress) + Send),
+) -> QueryResult<()> {
+    let schema = schema(source).await?;
+    let mut tables = Vec::new();
+    let mut state = BackupProgress {
+        copied_rows: 0,
+        total_rows: 0,
+    };
+    for object in schema.iter().filter(|object| object.kind == "table") {
+        let table = TableCopy::new(source, object).await?;
+        let count = query_integer(source, &format!("SELECT count(*) FROM {}", table.name)).await?;
+        state.total_rows += count as u64;
+        tables.push((object, table));
+    }
+    progress(state);
+
+    for (object, _) in &tables {
+        execute(target, &object.sql, Vec::new()).await?;
+    }
+    for (_, table) in &tables {
+        let mut after = None;
+        loop {
+            let rows = table.read_batch(source, after).await?;
+            for row in &rows {
+                table.write_row(target, row).await?;
+            }
+            state.copied_rows += rows.len() as u64;
+            if !rows.is_empty() {
+                progress(state);
+            }
+            if (rows.len() as i64) < BATCH_SIZE {
+                break;
+            }
+            let last_row = &rows[rows.len() - 1];
+            after = Some(
+                last_row[..table.key.len()]
+                    .iter()
+                    .map(|(_, value)| value.clone())
+                    .collect(),
+            );
+        }
+    }
+    for object in schema.iter().filter(|object| object.kind != "table") {
+        execute(target, &object.sql, Vec::new()).await?;
+    }
+
+    // The counters of `AUTOINCREMENT` columns, the target created the table
+    // along with the first such column
+    let sequences = "SELECT count(*) FROM sqlite_schema WHERE name = 'sqlite_sequence'";
+    if query_integer(source, sequences).await? > 0 {
+        execute(target, "DELETE FROM sqlite_sequence", Vec::new()).await?;
+        for row in query(source, "SELECT name, seq FROM sqlite_sequence", Vec::new()).await? {
+            let values = row.into_iter().map(|(_, value)| value).collect();
+            let sql = "INSERT INTO sqlite_sequence (name, seq) VALUES (?, ?)";
+            execute(target, sql, values).await?;
+        }
+    }
+    for pragma in ["user_version", "application_id"] {
+        let value = query_integer(source, &format!("PRAGMA {pragma}")).await?;
+        execute(target, &format!("PRAGMA {pragma} = {value}"), Vec::new()).await?;
+    }
+    Ok(())
+}
+
+impl AsyncTursoConnection {
+    /// Writes a consistent copy of the database to a new database file at
+    /// `path`, without blocking writers on other connections
+    ///
+    /// Fails if `path` already exists, or if a transaction is open on this
+    /// connection, whose uncommitted rows would end up in the backup.
+    pub async fn backup_to(&mut self, path: &str) -> QueryResult<()> {
+        self.backup_to_with_progress(path, |_| {}).await
+    }
+
+    /// Like [`backup_to`](Self::backup_to), calls `progress` once before any
+    /// row is copied and after every batch of rows
+    pub async fn backup_to_with_progress(
+        &mut self,
+        path: &str,
+        mut progress: impl FnMut(BackupProgress) + Send,
+    ) -> QueryResult<()> {
+        if Path::new(path).exists() {
+            return Err(database_error(format!(
+                "The backup target `{path}` already exists"
+            )));
+        }
+        self.ensure_connection().await?;
+        if self.has_open_transaction()? {
+            return Err(database_error(
+                "A transaction is open, a backup can only be written outside of transactions",
+            ));
+        }
+        let source = self.connection.clone().unwrap();
+        let database = TursoDatabase::new(path).await.map_err(database_error)?;
+        let target = database.connect().await.map_err(database_error)?;
+
+        // The transaction gives all reads the same snapshot
+        let result = self
+            .transaction(|_| {
+                async move { copy_database(&source, &target, &mut progress).await }.scope_boxed()
+            })
+            .await;
+        if result.is_err() {
+            // Don't leave a partial backup behind
+            for file in [path.to_owned(), format!("{path}-wal")] {
+                let _ = std::fs::remove_file(file);
+            }
+        }
+        result
+    }
+
+    /// Copies the backup at `path` into the database of this connection,
+    /// which has to be empty, e.g. a new `:memory:` database
+    ///
+    /// The rows are written in a transaction of this connection, a savepoint
+    /// if a transaction is open.
+    pub async fn restore_from(&mut self, path: &str) -> QueryResult<()> {
+        self.restore_from_with_progress(path, |_| {}).await
+    }
+
+    /// Like [`restore_from`](Self::restore_from), calls `progress` once
+    /// before any row is copied and after every batch of rows
+    pub async fn restore_from_with_progress(
+        &mut self,
+        path: &str,
+        mut progress: impl FnMut(BackupProgress) + Send,
+    ) -> QueryResult<()> {
+        if !Path::new(path).exists() {
+            return Err(database_error(format!("The backup `{path}` doesn't exist")));
+        }
+        self.ensure_connection().await?;
+        let target = self.connection.clone().unwrap();
+        if !schema(&target).await?.is_empty() {
+            return Err(database_error(
+                "The database isn't empty, a backup can only be restored into an empty database",
+            ));
+        }
+        let database = TursoDatabase::new(path).await.map_err(database_error)?;
+        let source = database.connect().await.map_err(database_error)?;
+        self.transaction(|_| {
+            async move { copy_objects(&source, &target, &mut progress).await }.scope_boxed()
+        })
+        .await
+    }
+
+    /// Whether a transaction is open, including a test transaction and one
+    /// the transaction manager doesn't know about
+    fn has_open_transaction(&mut self) -> QueryResult<bool> {
+        let tracked = AnsiTransactionManager::transaction_manager_status_mut(self)
+            .transaction_state()?
+            .in_transaction
+            .is_some();
+        let autocommit = match &self.connection {
+            Some(connection) => connection.is_autocommit().map_err(database_error)?,
+            None => true,
+        };
+        Ok(tracked || !autocommit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_without_rowid;
+
+    #[test]
+    fn without_rowid() {
+        assert!(is_without_rowid(
+            "CREATE TABLE t (a TEXT, b TEXT, PRIMARY KEY (a, b)) WITHOUT ROWID"
+        ));
+        assert!(is_without_rowid(
+            "CREATE TABLE t (a TEXT PRIMARY KEY) STRICT,\n without  rowid"
+        ));
+        assert!(!is_without_rowid("CREATE TABLE t (a TEXT PRIMARY KEY)"));
+        assert!(!is_without_rowid(
+            "CREATE TABLE t (without_rowid INTEGER, `WITHOUT ROWID` TEXT)"
+        ));
+    }
+}
@@ -1,20 +1,30 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use turso::{Builder, Connection, Database, Value};
 
 #[derive(Debug, Clone)]
 pub struct TursoDatabase {
     pub db: Database,
+    /// The path the database was opened with, `:memory:` for in-memory
+    /// databases
+    pub path: String,
 }
 
 #[derive(Debug, Clone)]
 pub struct TursoConnection {
     pub conn: Arc<Connection>,
+    /// Set once a statement failed with an error which leaves the connection
+    /// unusable
+    fatal_error: Arc<AtomicBool>,
 }
 
 #[derive(Debug, Clone)]
 pub struct TursoPreparedStatement {
     pub sql: String,
     pub binds: Vec<Value>,
+    /// When to stop fetching the rows of a query
+    pub deadline: Option<Instant>,
 }
 
 #[derive(Debug, Clone)]
@@ -22,17 +32,25 @@ pub struct TursoResult {
     pub results: Vec<Vec<(String, Value)>>,
     pub error: Option<String>,
     pub changes: usize,
+    /// Whether the query was stopped at its deadline
+    pub interrupted: bool,
 }
 
 impl TursoDatabase {
     pub async fn new(path: &str) -> Result<Self, turso::Error> {
         let db = Builder::new_local(path).build().await?;
-        Ok(TursoDatabase { db })
+        Ok(TursoDatabase {
+            db,
+            path: path.to_owned(),
+        })
     }
 
     pub async fn connect(&self) -> Result<TursoConnection, turso::Error> {
         let conn = Arc::new(self.db.connect()?);
-        Ok(TursoConnection { conn })
+        Ok(TursoConnection {
+            conn,
+            fatal_error: Arc::new(AtomicBool::new(false)),
+        })
     }
 }
 
@@ -41,6 +59,7 @@ impl TursoConnection {
         TursoPreparedStatement {
             sql: query.to_string(),
             binds: Vec::new(),
+            deadline: None,
         }
     }
 
@@ -50,7 +69,7 @@ impl TursoConnection {
     ) -> Result<TursoResult, turso::Error> {
         // Execute the statement
         let params: Vec<Value> = stmt.binds.clone();
-        let result = self.conn.execute(&stmt.sql, params).await;
+        let result = self.record_fatal_error(self.conn.execute(&stmt.sql, params).await);
 
         // TODO: Workaround: some statements (like PRAGMA) return rows but are called via execute()
         let rows_affected = match result {
@@ -65,16 +84,57 @@ impl TursoConnection {
             results: Vec::new(),
             error: None,
             changes: rows_affected as usize,
+            interrupted: false,
         })
     }
 
-    pub async fn execute_batch(&self, stmt: &TursoPreparedStatement) -> Result<(), turso::Error> {
+    /// The rowid of the most recent successful `INSERT` on this connection
+    pub fn last_insert_rowid(&self) -> i64 {
+        self.conn.last_insert_rowid()
+    }
+
+    /// Whether no transaction is open on this connection
+    pub fn is_autocommit(&self) -> Result<bool, turso::Error> {
+        self.conn.is_autocommit()
+    }
+
+    pub async fn execute_batch(
+        &self,
+        stmt: &TursoPreparedStatement,
+    ) -> Result<TursoResult, turso::Error> {
         // Execute the statement
-        self.conn.execute_batch(&stmt.sql).await?;
-        Ok(())
+        self.record_fatal_error(self.conn.execute_batch(&stmt.sql).await)?;
+        Ok(TursoResult {
+            results: Vec::new(),
+            error: None,
+            changes: 0,
+            interrupted: false,
+        })
     }
 
     pub async fn query(&self, stmt: &TursoPreparedStatement) -> Result<TursoResult, turso::Error> {
+        let result = self.query_rows(stmt).await;
+        self.record_fatal_error(result)
+    }
+
+    /// Whether a statement failed with an error which leaves the connection
+    /// unusable, e.g. an I/O error on the database file
+    pub fn has_fatal_error(&self) -> bool {
+        self.fatal_error.load(Ordering::Relaxed)
+    }
+
+    fn record_fatal_error<T>(&self, result: Result<T, turso::Error>) -> Result<T, turso::Error> {
+        if result.as_ref().is_err_and(is_fatal_error) {
+            self.fatal_error.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    async fn query_rows(&self, stmt: &TursoPreparedStatement) -> Result<TursoResult, turso::Error> {
+        if stmt.is_past_deadline() {
+            return Ok(TursoResult::interrupted());
+        }
+
         // Prepare and execute query
         let mut prepared = self.conn.prepare(&stmt.sql).await?;
         let params: Vec<Value> = stmt.binds.clone();
@@ -95,24 +155,59 @@ impl TursoConnection {
                 row_data.push((col_name, value));
             }
             results.push(row_data);
+
+            // Stops between two rows, dropping the statement resets it in
+            // the engine. A cancelled future stops at the same points.
+            if stmt.is_past_deadline() {
+                return Ok(TursoResult::interrupted());
+            }
         }
 
         Ok(TursoResult {
             results,
             error: None,
             changes: 0,
+            interrupted: false,
         })
     }
 }
 
+/// Whether an error leaves the connection unusable, errors of the statement
+/// itself, e.g. a malformed JSON value, don't
+fn is_fatal_error(error: &turso::Error) -> bool {
+    matches!(
+        error,
+        turso::Error::IoError(_) | turso::Error::Corrupt(_) | turso::Error::NotAdb(_)
+    )
+}
+
 impl TursoPreparedStatement {
     pub fn bind(&mut self, values: Vec<Value>) -> &mut Self {
         self.binds = values;
         self
     }
+
+    pub fn deadline(&mut self, deadline: Option<Instant>) -> &mut Self {
+        self.deadline = deadline;
+        self
+    }
+
+    fn is_past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
 }
 
 impl TursoResult {
+    fn interrupted() -> Self {
+        TursoResult {
+            results: Vec::new(),
+            error: None,
+            changes: 0,
+            interrupted: true,
+        }
+    }
+
     pub fn results(&self) -> Option<Vec<Vec<(String, Value)>>> {
         if self.results.is_empty() {
             None
@@ -125,6 +220,10 @@ impl TursoResult {
         self.error.clone()
     }
 
+    pub fn is_interrupted(&self) -> bool {
+        self.interrupted
+    }
+
     pub fn meta(&self) -> TursoMeta {
         TursoMeta {
             changes: self.changes,
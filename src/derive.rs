@@ -0,0 +1,86 @@
+//! Support code for the generated impls of `#[derive(TursoEnum)]`
+
+use std::error::Error;
+
+use diesel::sql_types::{BigInt, Integer, SmallInt, Text, TinyInt};
+
+use crate::value::{TursoStorageClass, TursoValue};
+
+/// How the values of an enum are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumStorage {
+    /// The names of the variants
+    Text,
+    /// The discriminants of the variants
+    Integer,
+}
+
+/// The SQL types an enum can be stored as
+///
+/// Implement this for a custom SQL type stored as `TEXT` or `INTEGER` to
+/// use it with `#[turso(sql_type = ...)]`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't store a `TursoEnum`",
+    note = "enums can be stored as SQL types stored as `TEXT` or `INTEGER`, e.g. `diesel::sql_types::Text` or `diesel::sql_types::Integer`"
+)]
+pub trait EnumSqlType {
+    /// How the values of the enum are stored
+    const STORAGE: EnumStorage;
+}
+
+impl EnumSqlType for Text {
+    const STORAGE: EnumStorage = EnumStorage::Text;
+}
+
+impl EnumSqlType for TinyInt {
+    const STORAGE: EnumStorage = EnumStorage::Integer;
+}
+
+impl EnumSqlType for SmallInt {
+    const STORAGE: EnumStorage = EnumStorage::Integer;
+}
+
+impl EnumSqlType for Integer {
+    const STORAGE: EnumStorage = EnumStorage::Integer;
+}
+
+impl EnumSqlType for BigInt {
+    const STORAGE: EnumStorage = EnumStorage::Integer;
+}
+
+/// The error for a value which doesn't belong to any variant, or isn't
+/// stored as `storage`
+pub fn unknown_enum_value(
+    enum_name: &str,
+    storage: EnumStorage,
+    value: &TursoValue,
+    names: &[&str],
+    discriminants: &[i64],
+) -> Box<dyn Error + Send + Sync> {
+    match (storage, value.storage_class()) {
+        (EnumStorage::Text, TursoStorageClass::Text) => {
+            let expected = names
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let text = value.as_text().unwrap_or_default();
+            format!("Unknown value `{text}` for `{enum_name}`, expected one of {expected}").into()
+        }
+        (EnumStorage::Integer, TursoStorageClass::Integer) => {
+            let expected = discriminants
+                .iter()
+                .map(i64::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let integer = value.as_integer().unwrap_or_default();
+            format!("Unknown value {integer} for `{enum_name}`, expected one of {expected}").into()
+        }
+        (EnumStorage::Text, other) => {
+            format!("Expected a TEXT value for `{enum_name}`, but got {other}").into()
+        }
+        (EnumStorage::Integer, other) => {
+            format!("Expected an INTEGER value for `{enum_name}`, but got {other}").into()
+        }
+    }
+}
@@ -0,0 +1,387 @@
+//! `EXPLAIN` and `EXPLAIN QUERY PLAN`
+//!
+//! ```rust,ignore
+//! let query = users::table.filter(users::name.eq("Jane"));
+//!
+//! let plan = connection.explain_query_plan(&query).await?;
+//! println!("{plan}");
+//! // QUERY PLAN
+//! // `--SEARCH users USING INDEX idx_users_name (name=?)
+//!
+//! // Fails the test if the planner stops using the index
+//! connection.assert_uses_index(&query, "idx_users_name").await;
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use diesel::deserialize;
+use diesel::query_builder::QueryFragment;
+use diesel::row::{Field, Row};
+use diesel::sql_types::{BigInt, Text};
+use diesel::QueryResult;
+
+use crate::backend::TursoBackend;
+use crate::query_builder::TursoQueryBuilder;
+use crate::row::TursoRow;
+use crate::{construct_bind_data, AsyncTursoConnection, TursoValue};
+
+/// A step of the plan the query planner chose for a statement, one row of
+/// `EXPLAIN QUERY PLAN`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlanNode {
+    id: i64,
+    parent: i64,
+    detail: String,
+}
+
+impl QueryPlanNode {
+    /// The id of the step
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// The id of the parent step, `0` for the top level steps
+    pub fn parent(&self) -> i64 {
+        self.parent
+    }
+
+    /// The description of the step, e.g. `SCAN users` or
+    /// `SEARCH users USING INDEX idx_users_name (name=?)`
+    pub fn detail(&self) -> &str {
+        &self.detail
+    }
+
+    /// Whether the step looks up rows with the index `index`
+    pub fn uses_index(&self, index: &str) -> bool {
+        let mut words = self.detail.split_whitespace();
+        // Both `USING INDEX name` and `USING COVERING INDEX name`
+        while words.by_ref().any(|word| word == "INDEX") {
+            if words.next() == Some(index) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether the step reads every row of a table
+    pub fn is_full_scan(&self) -> bool {
+        self.detail.starts_with("SCAN ") && !self.detail.contains(" INDEX ")
+    }
+
+    fn from_row(row: &TursoRow) -> QueryResult<Self> {
+        // The columns are `id`, `parent`, `notused` and `detail`
+        let node = || -> deserialize::Result<Self> {
+            Ok(Self {
+                id: row.get_value::<BigInt, i64, _>(0)?,
+                parent: row.get_value::<BigInt, i64, _>(1)?,
+                detail: row.get_value::<Text, String, _>(3)?,
+            })
+        };
+        node().map_err(diesel::result::Error::DeserializationError)
+    }
+}
+
+/// The plan the query planner chose for a statement
+///
+/// The [`Display`](fmt::Display) impl renders the plan as a tree, like the
+/// `sqlite3` shell does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPlan {
+    nodes: Vec<QueryPlanNode>,
+}
+
+impl QueryPlan {
+    /// The steps of the plan, in the order the database returned them
+    pub fn nodes(&self) -> &[QueryPlanNode] {
+        &self.nodes
+    }
+
+    /// The steps whose parent is the step `id`, use `0` for the top level
+    /// steps
+    pub fn children(&self, id: i64) -> impl Iterator<Item = &QueryPlanNode> + '_ {
+        self.nodes.iter().filter(move |node| node.parent == id)
+    }
+
+    /// Whether any step looks up rows with the index `index`
+    pub fn uses_index(&self, index: &str) -> bool {
+        self.nodes.iter().any(|node| node.uses_index(index))
+    }
+
+    /// The steps which read every row of a table
+    pub fn full_scans(&self) -> impl Iterator<Item = &QueryPlanNode> + '_ {
+        self.nodes.iter().filter(|node| node.is_full_scan())
+    }
+
+    /// `visited` holds the steps printed with their children so far, a step
+    /// can't be its own ancestor, the set avoids endless recursion on
+    /// malformed plans with cycles
+    fn fmt_children(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        id: i64,
+        prefix: &str,
+        visited: &mut HashSet<i64>,
+    ) -> fmt::Result {
+        let mut children = self.children(id).peekable();
+        while let Some(node) = children.next() {
+            let last = children.peek().is_none();
+            let branch = if last { "`--" } else { "|--" };
+            writeln!(f, "{prefix}{branch}{}", node.detail)?;
+            if visited.insert(node.id) {
+                let indent = if last { "   " } else { "|  " };
+                self.fmt_children(f, node.id, &format!("{prefix}{indent}"), visited)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "QUERY PLAN")?;
+        self.fmt_children(f, 0, "", &mut HashSet::from([0]))
+    }
+}
+
+/// An instruction of the bytecode program of a statement, one row of
+/// `EXPLAIN`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplainInstruction {
+    addr: i64,
+    opcode: String,
+    p1: i64,
+    p2: i64,
+    p3: i64,
+    p4: Option<String>,
+    p5: i64,
+    comment: Option<String>,
+}
+
+impl ExplainInstruction {
+    /// The address of the instruction in the program
+    pub fn addr(&self) -> i64 {
+        self.addr
+    }
+
+    /// The name of the instruction, e.g. `OpenRead` or `SeekGE`
+    pub fn opcode(&self) -> &str {
+        &self.opcode
+    }
+
+    /// The first operand
+    pub fn p1(&self) -> i64 {
+        self.p1
+    }
+
+    /// The second operand
+    pub fn p2(&self) -> i64 {
+        self.p2
+    }
+
+    /// The third operand
+    pub fn p3(&self) -> i64 {
+        self.p3
+    }
+
+    /// The fourth operand, rendered as text
+    pub fn p4(&self) -> Option<&str> {
+        self.p4.as_deref()
+    }
+
+    /// The fifth operand, usually flags
+    pub fn p5(&self) -> i64 {
+        self.p5
+    }
+
+    /// A description of the instruction, if the database provides one
+    pub fn comment(&self) -> Option<&str> {
+        self.comment
+            .as_deref()
+            .filter(|comment| !comment.is_empty())
+    }
+
+    fn from_row(row: &TursoRow) -> QueryResult<Self> {
+        // The columns are `addr`, `opcode`, `p1`, `p2`, `p3`, `p4`, `p5` and
+        // `comment`, `p4` and `comment` can be of any type
+        let text = |index: usize| {
+            let value = row.get(index).and_then(|field| field.value())?;
+            Some(display_value(&value))
+        };
+        let instruction = || -> deserialize::Result<Self> {
+            Ok(Self {
+                addr: row.get_value::<BigInt, i64, _>(0)?,
+                opcode: row.get_value::<Text, String, _>(1)?,
+                p1: row.get_value::<BigInt, i64, _>(2)?,
+                p2: row.get_value::<BigInt, i64, _>(3)?,
+                p3: row.get_value::<BigInt, i64, _>(4)?,
+                p4: text(5),
+                p5: row.get_value::<BigInt, i64, _>(6)?,
+                comment: text(7),
+            })
+        };
+        instruction().map_err(diesel::result::Error::DeserializationError)
+    }
+}
+
+fn display_value(value: &TursoValue) -> String {
+    if let Some(text) = value.as_text() {
+        text.to_owned()
+    } else if let Some(integer) = value.as_integer() {
+        integer.to_string()
+    } else if let Some(real) = value.as_real() {
+        real.to_string()
+    } else {
+        let blob = value.as_blob().unwrap_or_default();
+        blob.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+}
+
+/// The SQL and the bind values of a query
+fn build_query<T>(query: &T) -> QueryResult<(String, Vec<turso::Value>)>
+where
+    T: QueryFragment<TursoBackend>,
+{
+    let mut query_builder = TursoQueryBuilder::default();
+    query.to_sql(&mut query_builder, &TursoBackend)?;
+    Ok((query_builder.sql, construct_bind_data(query)?))
+}
+
+impl AsyncTursoConnection {
+    /// Returns the plan the query planner chooses for `query`, without running
+    /// it
+    ///
+    /// `query` has to be a complete statement, e.g. `users::table.select(..)`
+    /// or `users::table.filter(..)` rather than `users::table`.
+    pub async fn explain_query_plan<T>(&mut self, query: &T) -> QueryResult<QueryPlan>
+    where
+        T: QueryFragment<TursoBackend>,
+    {
+        let (sql, binds) = build_query(query)?;
+        self.ensure_connection().await?;
+        self.query_plan(&sql, binds).await
+    }
+
+    /// Returns the bytecode program `query` compiles to, without running it
+    pub async fn explain<T>(&mut self, query: &T) -> QueryResult<Vec<ExplainInstruction>>
+    where
+        T: QueryFragment<TursoBackend>,
+    {
+        let (sql, binds) = build_query(query)?;
+        self.ensure_connection().await?;
+        self.query_rows(&format!("EXPLAIN {sql}"), binds)
+            .await?
+            .iter()
+            .map(ExplainInstruction::from_row)
+            .collect()
+    }
+
+    /// Panics unless the query planner uses the index `index` for `query`
+    ///
+    /// Meant for tests which ensure that critical queries keep using their
+    /// index when the schema or the queries change.
+    pub async fn assert_uses_index<T>(&mut self, query: &T, index: &str)
+    where
+        T: QueryFragment<TursoBackend>,
+    {
+        let sql = build_query(query)
+            .map(|(sql, _)| sql)
+            .unwrap_or_else(|e| panic!("Failed to build the query: {e}"));
+        let plan = self
+            .explain_query_plan(query)
+            .await
+            .unwrap_or_else(|e| panic!("Failed to explain `{sql}`: {e}"));
+        assert!(
+            plan.uses_index(index),
+            "Expected `{sql}` to use the index `{index}`, but the plan is\n{plan}"
+        );
+    }
+
+    /// Runs `EXPLAIN QUERY PLAN` for a statement on this connection
+    ///
+    /// The statement is prepared again but not run. It isn't reported to the
+    /// instrumentation.
+    pub(crate) async fn query_plan(
+        &mut self,
+        sql: &str,
+        binds: Vec<turso::Value>,
+    ) -> QueryResult<QueryPlan> {
+        let nodes = self
+            .query_rows(&format!("EXPLAIN QUERY PLAN {sql}"), binds)
+            .await?
+            .iter()
+            .map(QueryPlanNode::from_row)
+            .collect::<QueryResult<_>>()?;
+        Ok(QueryPlan { nodes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QueryPlan, QueryPlanNode};
+
+    fn node(id: i64, parent: i64, detail: &str) -> QueryPlanNode {
+        QueryPlanNode {
+            id,
+            parent,
+            detail: detail.to_owned(),
+        }
+    }
+
+    #[test]
+    fn plan_tree() {
+        let plan = QueryPlan {
+            nodes: vec![
+                node(2, 0, "SCAN users"),
+                node(
+                    5,
+                    0,
+                    "SEARCH posts USING INDEX idx_posts_user_id (user_id=?)",
+                ),
+                node(8, 0, "CORRELATED SCALAR SUBQUERY 1"),
+                node(
+                    11,
+                    8,
+                    "SEARCH comments USING COVERING INDEX idx_comments (post_id=?)",
+                ),
+            ],
+        };
+        assert_eq!(
+            plan.to_string(),
+            "QUERY PLAN\n\
+             |--SCAN users\n\
+             |--SEARCH posts USING INDEX idx_posts_user_id (user_id=?)\n\
+             `--CORRELATED SCALAR SUBQUERY 1\n   \
+             `--SEARCH comments USING COVERING INDEX idx_comments (post_id=?)\n"
+        );
+        assert!(plan.uses_index("idx_posts_user_id"));
+        assert!(plan.uses_index("idx_comments"));
+        assert!(!plan.uses_index("idx_posts"));
+        assert_eq!(
+            plan.full_scans()
+                .map(QueryPlanNode::detail)
+                .collect::<Vec<_>>(),
+            vec!["SCAN users"]
+        );
+    }
+
+    #[test]
+    fn plan_with_cycle() {
+        let plan = QueryPlan {
+            nodes: vec![
+                node(2, 0, "SCAN users"),
+                node(3, 4, "SCAN posts"),
+                node(4, 3, "SCAN comments"),
+                node(4, 2, "SCAN comments"),
+            ],
+        };
+        assert_eq!(
+            plan.to_string(),
+            "QUERY PLAN\n\
+             `--SCAN users\n   \
+             `--SCAN comments\n      \
+             `--SCAN posts\n         \
+             `--SCAN comments\n"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Query dsl node for the `COLLATE` operator
+
+use diesel::expression::{Expression, ValidGrouping};
+use diesel::query_builder::{AstPass, QueryFragment, QueryId};
+use diesel::QueryResult;
+
+use crate::backend::TursoBackend;
+
+/// The built-in collating functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collation {
+    /// Compares text byte by byte, the default
+    Binary,
+    /// Like `Binary`, but folds the 26 upper case ASCII characters to lower case
+    NoCase,
+    /// Like `Binary`, but ignores trailing spaces
+    RTrim,
+}
+
+impl Collation {
+    fn as_sql(self) -> &'static str {
+        match self {
+            Collation::Binary => "BINARY",
+            Collation::NoCase => "NOCASE",
+            Collation::RTrim => "RTRIM",
+        }
+    }
+}
+
+/// The return type of
+/// [`TursoExpressionMethods::collate`](crate::dsl::TursoExpressionMethods::collate)
+#[derive(Debug, Clone, Copy, ValidGrouping)]
+pub struct Collate<T> {
+    expr: T,
+    collation: Collation,
+}
+
+impl<T> Collate<T> {
+    pub(crate) fn new(expr: T, collation: Collation) -> Self {
+        Self { expr, collation }
+    }
+}
+
+impl<T> Expression for Collate<T>
+where
+    T: Expression,
+{
+    type SqlType = T::SqlType;
+}
+
+impl<T> QueryFragment<TursoBackend> for Collate<T>
+where
+    T: QueryFragment<TursoBackend>,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        self.expr.walk_ast(out.reborrow())?;
+        out.push_sql(" COLLATE ");
+        out.push_sql(self.collation.as_sql());
+        Ok(())
+    }
+}
+
+// The collation is part of the SQL, but not of the type
+impl<T> QueryId for Collate<T> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+diesel::impl_selectable_expression!(Collate<T>);
@@ -0,0 +1,272 @@
+//! Query dsl nodes for the date and time functions
+
+use std::marker::PhantomData;
+
+use diesel::expression::{AppearsOnTable, Expression, SelectableExpression, ValidGrouping};
+use diesel::query_builder::{AstPass, QueryFragment, QueryId};
+use diesel::sql_types::{
+    BigInt, Date, Double, MaybeNullableType, Nullable, SingleValue, SqlType, Text, Time, Timestamp,
+};
+use diesel::QueryResult;
+
+use crate::backend::TursoBackend;
+use crate::sql_types::{TimestampJulian, TimestampUnix, Timestamptz};
+
+/// SQL types which are accepted as time value by the date and time functions
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a time value",
+    note = "the date and time functions accept `Text`, `Date`, `Time`, `Timestamp`, \
+            `Timestamptz`, `TimestampUnix` and `TimestampJulian` expressions"
+)]
+pub trait TimeValue: SqlType {
+    /// Modifier required to interpret values of this type
+    const MODIFIER: Option<&'static str> = None;
+}
+
+impl TimeValue for Text {}
+impl TimeValue for Date {}
+impl TimeValue for Time {}
+impl TimeValue for Timestamp {}
+impl TimeValue for Timestamptz {}
+impl TimeValue for TimestampJulian {}
+
+impl TimeValue for TimestampUnix {
+    // Numbers are interpreted as julian day numbers otherwise
+    const MODIFIER: Option<&'static str> = Some("unixepoch");
+}
+
+impl<ST> TimeValue for Nullable<ST>
+where
+    ST: TimeValue<IsNull = diesel::sql_types::is_nullable::NotNull>,
+{
+    const MODIFIER: Option<&'static str> = ST::MODIFIER;
+}
+
+/// A date and time function taking a time value and modifiers
+pub trait DateTimeFunction {
+    /// The name of the SQL function
+    const NAME: &'static str;
+    /// The SQL type returned for a not nullable time value
+    type SqlType: SqlType + SingleValue;
+}
+
+macro_rules! date_time_function {
+    ($(#[$meta:meta])* $name:ident => $function:literal, $sql_type:ty) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name;
+
+        impl DateTimeFunction for $name {
+            const NAME: &'static str = $function;
+            type SqlType = $sql_type;
+        }
+    };
+}
+
+date_time_function!(
+    /// `date(time, modifiers...)`
+    DateFunction => "date", Date
+);
+date_time_function!(
+    /// `time(time, modifiers...)`
+    TimeFunction => "time", Time
+);
+date_time_function!(
+    /// `datetime(time, modifiers...)`
+    DatetimeFunction => "datetime", Timestamp
+);
+date_time_function!(
+    /// `julianday(time, modifiers...)`
+    JuliandayFunction => "julianday", Double
+);
+date_time_function!(
+    /// `unixepoch(time, modifiers...)`
+    UnixepochFunction => "unixepoch", BigInt
+);
+
+/// Renders the time value and the modifiers of a date and time function call
+fn walk_time_value<'b, T>(
+    time: &'b T,
+    modifiers: &'b [String],
+    mut out: AstPass<'_, 'b, TursoBackend>,
+) -> QueryResult<()>
+where
+    T: Expression + QueryFragment<TursoBackend>,
+    T::SqlType: TimeValue,
+{
+    time.walk_ast(out.reborrow())?;
+    if let Some(modifier) = <T::SqlType as TimeValue>::MODIFIER {
+        out.push_sql(", '");
+        out.push_sql(modifier);
+        out.push_sql("'");
+    }
+    for modifier in modifiers {
+        out.push_sql(", ");
+        out.push_bind_param::<Text, _>(modifier)?;
+    }
+    Ok(())
+}
+
+/// A call of one of the date and time functions, see
+/// [`crate::dsl::datetime()`] for an example
+#[derive(Debug, Clone)]
+pub struct DateTimeCall<F, T> {
+    time: T,
+    modifiers: Vec<String>,
+    _function: PhantomData<F>,
+}
+
+impl<F, T> DateTimeCall<F, T> {
+    pub(crate) fn new(time: T) -> Self {
+        Self {
+            time,
+            modifiers: Vec::new(),
+            _function: PhantomData,
+        }
+    }
+
+    /// Appends a modifier like `+1 day` or `start of month`
+    ///
+    /// Modifiers are applied from left to right. An invalid modifier makes
+    /// the function return `NULL`.
+    pub fn modifier(mut self, modifier: impl Into<String>) -> Self {
+        self.modifiers.push(modifier.into());
+        self
+    }
+}
+
+impl<F, T> Expression for DateTimeCall<F, T>
+where
+    F: DateTimeFunction,
+    T: Expression,
+    T::SqlType: TimeValue,
+    <T::SqlType as SqlType>::IsNull: MaybeNullableType<F::SqlType>,
+{
+    type SqlType = <<T::SqlType as SqlType>::IsNull as MaybeNullableType<F::SqlType>>::Out;
+}
+
+impl<F, T> QueryFragment<TursoBackend> for DateTimeCall<F, T>
+where
+    F: DateTimeFunction,
+    T: Expression + QueryFragment<TursoBackend>,
+    T::SqlType: TimeValue,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        out.push_sql(F::NAME);
+        out.push_sql("(");
+        walk_time_value(&self.time, &self.modifiers, out.reborrow())?;
+        out.push_sql(")");
+        Ok(())
+    }
+}
+
+// The number of modifiers changes the SQL, so the query can't be cached by type
+impl<F, T> QueryId for DateTimeCall<F, T> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<F, T, GB> ValidGrouping<GB> for DateTimeCall<F, T>
+where
+    T: ValidGrouping<GB>,
+{
+    type IsAggregate = T::IsAggregate;
+}
+
+impl<F, T, QS> SelectableExpression<QS> for DateTimeCall<F, T>
+where
+    Self: AppearsOnTable<QS>,
+    T: SelectableExpression<QS>,
+{
+}
+
+impl<F, T, QS> AppearsOnTable<QS> for DateTimeCall<F, T>
+where
+    Self: Expression,
+    T: AppearsOnTable<QS>,
+{
+}
+
+/// The return type of [`strftime(format, time)`](crate::dsl::strftime())
+#[derive(Debug, Clone)]
+pub struct Strftime<Fmt, T> {
+    format: Fmt,
+    time: T,
+    modifiers: Vec<String>,
+}
+
+impl<Fmt, T> Strftime<Fmt, T> {
+    pub(crate) fn new(format: Fmt, time: T) -> Self {
+        Self {
+            format,
+            time,
+            modifiers: Vec::new(),
+        }
+    }
+
+    /// Appends a modifier like `+1 day` or `start of month`
+    ///
+    /// Modifiers are applied from left to right. An invalid modifier makes
+    /// the function return `NULL`.
+    pub fn modifier(mut self, modifier: impl Into<String>) -> Self {
+        self.modifiers.push(modifier.into());
+        self
+    }
+}
+
+impl<Fmt, T> Expression for Strftime<Fmt, T>
+where
+    Fmt: Expression,
+    T: Expression,
+    T::SqlType: TimeValue,
+    <T::SqlType as SqlType>::IsNull: MaybeNullableType<Text>,
+{
+    type SqlType = <<T::SqlType as SqlType>::IsNull as MaybeNullableType<Text>>::Out;
+}
+
+impl<Fmt, T> QueryFragment<TursoBackend> for Strftime<Fmt, T>
+where
+    Fmt: QueryFragment<TursoBackend>,
+    T: Expression + QueryFragment<TursoBackend>,
+    T::SqlType: TimeValue,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        out.push_sql("strftime(");
+        self.format.walk_ast(out.reborrow())?;
+        out.push_sql(", ");
+        walk_time_value(&self.time, &self.modifiers, out.reborrow())?;
+        out.push_sql(")");
+        Ok(())
+    }
+}
+
+// The number of modifiers changes the SQL, so the query can't be cached by type
+impl<Fmt, T> QueryId for Strftime<Fmt, T> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<Fmt, T, GB> ValidGrouping<GB> for Strftime<Fmt, T>
+where
+    (Fmt, T): ValidGrouping<GB>,
+{
+    type IsAggregate = <(Fmt, T) as ValidGrouping<GB>>::IsAggregate;
+}
+
+impl<Fmt, T, QS> SelectableExpression<QS> for Strftime<Fmt, T>
+where
+    Self: AppearsOnTable<QS>,
+    Fmt: SelectableExpression<QS>,
+    T: SelectableExpression<QS>,
+{
+}
+
+impl<Fmt, T, QS> AppearsOnTable<QS> for Strftime<Fmt, T>
+where
+    Self: Expression,
+    Fmt: AppearsOnTable<QS>,
+    T: AppearsOnTable<QS>,
+{
+}
@@ -0,0 +1,342 @@
+//! Turso specific expression methods.
+
+#[cfg(feature = "serde_json")]
+use self::private::{JsonOrNullableJson, JsonOrNullableJsonOrJsonbOrNullableJsonb};
+
+use diesel::expression::{AsExpression, Expression};
+use diesel::sql_types::SqlType;
+#[cfg(feature = "serde_json")]
+use diesel::sql_types::{is_nullable, SingleValue, Text};
+
+use super::helper_types;
+#[cfg(feature = "serde_json")]
+use super::json::{JsonEach, JsonExtract, JsonSet};
+#[cfg(feature = "serde_json")]
+use super::operators::{RetrieveAsObject, RetrieveAsText};
+#[cfg(feature = "serde_json")]
+use crate::sql_types::Json;
+
+use self::private::{TextOrNullableText, VectorOrNullableVector};
+use super::collate::{Collate, Collation};
+use super::operators::{Glob, Is, IsNot, Regexp};
+use super::vector::{VectorDistanceCos, VectorDistanceL2, VectorExtract};
+
+/// Turso specific methods which are present on all expressions.
+///
+/// `LIKE ... ESCAPE` is provided by diesel's
+/// [`EscapeExpressionMethods`](diesel::expression_methods::EscapeExpressionMethods),
+/// e.g. `users::name.like("100\\%%").escape('\\')`. There is no default
+/// escape character.
+pub trait TursoExpressionMethods: Expression + Sized {
+    /// Creates an `IS` expression.
+    ///
+    /// `IS` works like `=`, except that it compares `NULL` as equal to `NULL`
+    /// and unequal to every other value. It never evaluates to `NULL`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let without_hair_color = users::table
+    ///     .filter(users::hair_color.is(None::<String>))
+    ///     .load::<User>(conn)
+    ///     .await?;
+    /// ```
+    fn is<T>(self, other: T) -> helper_types::Is<Self, T>
+    where
+        Self::SqlType: SqlType,
+        T: AsExpression<Self::SqlType>,
+    {
+        Is::new(self, other.as_expression())
+    }
+
+    /// Creates an `IS NOT` expression.
+    ///
+    /// `IS NOT` works like `!=`, except that it compares `NULL` as equal to
+    /// `NULL` and unequal to every other value. It never evaluates to `NULL`.
+    #[allow(clippy::wrong_self_convention)] // This is named after the sql operator
+    fn is_not<T>(self, other: T) -> helper_types::IsNot<Self, T>
+    where
+        Self::SqlType: SqlType,
+        T: AsExpression<Self::SqlType>,
+    {
+        IsNot::new(self, other.as_expression())
+    }
+
+    /// Creates a `GLOB` expression.
+    ///
+    /// Unlike `LIKE`, `GLOB` is case sensitive and uses the unix wildcards
+    /// `*`, `?` and `[...]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let logs = files::table
+    ///     .filter(files::name.glob("*.log"))
+    ///     .load::<File>(conn)
+    ///     .await?;
+    /// ```
+    fn glob<T>(self, pattern: T) -> helper_types::Glob<Self, T>
+    where
+        Self::SqlType: TextOrNullableText + SqlType,
+        T: AsExpression<Self::SqlType>,
+    {
+        Glob::new(self, pattern.as_expression())
+    }
+
+    /// Creates a `REGEXP` expression.
+    ///
+    /// The operator calls the `regexp(pattern, value)` function, the query
+    /// fails if the database was built without it.
+    fn regexp<T>(self, pattern: T) -> helper_types::Regexp<Self, T>
+    where
+        Self::SqlType: TextOrNullableText + SqlType,
+        T: AsExpression<Self::SqlType>,
+    {
+        Regexp::new(self, pattern.as_expression())
+    }
+
+    /// Creates a `COLLATE` expression, which compares or orders the value
+    /// using the given collating function.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let sean = users::table
+    ///     .filter(users::name.collate(Collation::NoCase).eq("SEAN"))
+    ///     .first::<User>(conn)
+    ///     .await?;
+    /// ```
+    fn collate(self, collation: Collation) -> helper_types::Collate<Self> {
+        Collate::new(self, collation)
+    }
+}
+
+impl<T: Expression> TursoExpressionMethods for T {}
+
+/// Turso specific methods present on JSON and JSONB expressions.
+///
+/// JSON paths use the syntax of the database engine, e.g. `$.address.city`
+/// or `$.tags[0]`.
+#[cfg(feature = "serde_json")]
+pub trait TursoJsonExpressionMethods: Expression + Sized {
+    /// Creates a `->` expression which extracts the sub-component at `path`
+    /// as JSON.
+    ///
+    /// The result is `NULL` if the path does not exist.
+    fn retrieve_as_object<T>(self, path: T) -> helper_types::RetrieveAsObject<Self, T>
+    where
+        T: AsExpression<Text>,
+    {
+        RetrieveAsObject::new(self, path.as_expression())
+    }
+
+    /// Creates a `->>` expression which extracts the sub-component at `path`
+    /// as text.
+    ///
+    /// The result is `NULL` if the path does not exist.
+    fn retrieve_as_text<T>(self, path: T) -> helper_types::RetrieveAsText<Self, T>
+    where
+        T: AsExpression<Text>,
+    {
+        RetrieveAsText::new(self, path.as_expression())
+    }
+
+    /// Creates a `json_extract(self, path)` expression which returns the
+    /// sub-component at `path` as the SQL type `ST`.
+    ///
+    /// The result is `NULL` if the path does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let city = users::table
+    ///     .select(users::profile.json_extract::<Text, _>("$.address.city"))
+    ///     .first::<Option<String>>(conn)
+    ///     .await?;
+    /// ```
+    fn json_extract<ST, T>(self, path: T) -> helper_types::JsonExtract<Self, T, ST>
+    where
+        ST: SqlType<IsNull = is_nullable::NotNull> + SingleValue,
+        T: AsExpression<Text>,
+    {
+        JsonExtract::new(self, path.as_expression())
+    }
+
+    /// Creates a `json_set(self, path, value)` expression which inserts or
+    /// replaces the value at `path`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// diesel::update(users::table)
+    ///     .set(users::profile.eq(users::profile.json_set("$.verified", json!(true))))
+    ///     .execute(conn)
+    ///     .await?;
+    /// ```
+    fn json_set<P, V>(self, path: P, value: V) -> helper_types::JsonSet<Self, P, V>
+    where
+        Self::SqlType: JsonOrNullableJson,
+        P: AsExpression<Text>,
+        V: AsExpression<Json>,
+    {
+        JsonSet::new(self, path.as_expression(), value.as_expression())
+    }
+
+    /// Uses the elements of a JSON array as the right hand side of
+    /// `eq_any`/`ne_all`, by rendering `SELECT value FROM json_each(self)`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let selected = users::table
+    ///     .filter(users::id.eq_any(json!([1, 2, 3]).into_sql::<Json>().json_each()))
+    ///     .load::<User>(conn)
+    ///     .await?;
+    /// ```
+    fn json_each<ST>(self) -> helper_types::JsonEach<Self, ST>
+    where
+        ST: SqlType,
+    {
+        JsonEach::new(self)
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl<T> TursoJsonExpressionMethods for T
+where
+    T: Expression,
+    T::SqlType: JsonOrNullableJsonOrJsonbOrNullableJsonb,
+{
+}
+
+/// Turso specific methods present on vector expressions.
+///
+/// The other vector has to be of the same type, distances are `NULL` if
+/// either vector is `NULL`.
+pub trait TursoVectorExpressionMethods: Expression + Sized {
+    /// Creates a `vector_distance_cos(self, other)` expression, the cosine
+    /// distance between the two vectors.
+    ///
+    /// The distance ranges from 0 for vectors pointing into the same direction
+    /// to 2 for vectors pointing into opposite directions.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let nearest = documents::table
+    ///     .select(documents::id)
+    ///     .order_by(documents::embedding.cosine_distance(query))
+    ///     .limit(k)
+    ///     .load::<i32>(conn)
+    ///     .await?;
+    /// ```
+    fn cosine_distance<T>(self, other: T) -> helper_types::CosineDistance<Self, T>
+    where
+        Self::SqlType: VectorOrNullableVector,
+        T: AsExpression<<Self::SqlType as VectorOrNullableVector>::Vector>,
+    {
+        VectorDistanceCos::new(self, other.as_expression())
+    }
+
+    /// Creates a `vector_distance_l2(self, other)` expression, the euclidean
+    /// distance between the two vectors.
+    fn l2_distance<T>(self, other: T) -> helper_types::L2Distance<Self, T>
+    where
+        Self::SqlType: VectorOrNullableVector,
+        T: AsExpression<<Self::SqlType as VectorOrNullableVector>::Vector>,
+    {
+        VectorDistanceL2::new(self, other.as_expression())
+    }
+
+    /// Creates a `vector_extract(self)` expression, which returns the vector
+    /// as text, e.g. `[1,2.5]`.
+    fn vector_extract(self) -> helper_types::VectorExtract<Self> {
+        VectorExtract::new(self)
+    }
+}
+
+impl<T> TursoVectorExpressionMethods for T
+where
+    T: Expression,
+    T::SqlType: VectorOrNullableVector,
+{
+}
+
+pub(crate) mod private {
+    use diesel::sql_types::{Double, Nullable, SqlType, Text};
+
+    #[cfg(feature = "serde_json")]
+    use crate::sql_types::{Json, Jsonb};
+    use crate::sql_types::{Vector32, Vector64};
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` is neither `diesel::sql_types::Text` nor `diesel::sql_types::Nullable<Text>`",
+        note = "try to provide an expression that produces one of the expected sql types"
+    )]
+    pub trait TextOrNullableText {}
+    impl TextOrNullableText for Text {}
+    impl TextOrNullableText for Nullable<Text> {}
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` is neither `diesel_turso::sql_types::Json`, `diesel_turso::sql_types::Jsonb` nor their `Nullable` variants",
+        note = "try to provide an expression that produces one of the expected sql types"
+    )]
+    #[cfg(feature = "serde_json")]
+    pub trait JsonOrNullableJsonOrJsonbOrNullableJsonb {}
+    #[cfg(feature = "serde_json")]
+    impl JsonOrNullableJsonOrJsonbOrNullableJsonb for Json {}
+    #[cfg(feature = "serde_json")]
+    impl JsonOrNullableJsonOrJsonbOrNullableJsonb for Nullable<Json> {}
+    #[cfg(feature = "serde_json")]
+    impl JsonOrNullableJsonOrJsonbOrNullableJsonb for Jsonb {}
+    #[cfg(feature = "serde_json")]
+    impl JsonOrNullableJsonOrJsonbOrNullableJsonb for Nullable<Jsonb> {}
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` is neither `diesel_turso::sql_types::Json` nor `diesel_turso::sql_types::Nullable<Json>`",
+        note = "try to provide an expression that produces one of the expected sql types"
+    )]
+    #[cfg(feature = "serde_json")]
+    pub trait JsonOrNullableJson {}
+    #[cfg(feature = "serde_json")]
+    impl JsonOrNullableJson for Json {}
+    #[cfg(feature = "serde_json")]
+    impl JsonOrNullableJson for Nullable<Json> {}
+
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` is neither `diesel_turso::sql_types::Vector32`, `diesel_turso::sql_types::Vector64` nor their `Nullable` variants",
+        note = "try to provide an expression that produces one of the expected sql types"
+    )]
+    pub trait VectorOrNullableVector {
+        /// The not nullable vector type
+        type Vector: SqlType;
+        /// The type of distances, nullable if the vector is
+        type Distance: SqlType;
+        /// The type of the text form, nullable if the vector is
+        type Extracted: SqlType;
+    }
+
+    impl VectorOrNullableVector for Vector32 {
+        type Vector = Vector32;
+        type Distance = Double;
+        type Extracted = Text;
+    }
+
+    impl VectorOrNullableVector for Nullable<Vector32> {
+        type Vector = Vector32;
+        type Distance = Nullable<Double>;
+        type Extracted = Nullable<Text>;
+    }
+
+    impl VectorOrNullableVector for Vector64 {
+        type Vector = Vector64;
+        type Distance = Double;
+        type Extracted = Text;
+    }
+
+    impl VectorOrNullableVector for Nullable<Vector64> {
+        type Vector = Vector64;
+        type Distance = Nullable<Double>;
+        type Extracted = Nullable<Text>;
+    }
+}
@@ -0,0 +1,109 @@
+//! Turso specific SQL functions.
+
+use diesel::expression::{AsExpression, Expression};
+use diesel::sql_types::Text;
+
+use super::date_and_time::{DateTimeCall, Strftime, TimeValue};
+use super::helper_types;
+
+/// Creates a `date(time)` expression, which returns the date as `YYYY-MM-DD`
+///
+/// `time` can be any expression of a date, time or timestamp type or text,
+/// literals have to be converted with `IntoSql`, e.g.
+/// `"now".into_sql::<Text>()`. Modifiers are appended with `.modifier()`.
+/// The result is `NULL` for invalid time values or modifiers.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let first_of_month = posts::table
+///     .select(date(posts::created_at).modifier("start of month"))
+///     .load::<NaiveDate>(conn)
+///     .await?;
+/// ```
+pub fn date<T>(time: T) -> helper_types::date<T>
+where
+    T: Expression,
+    T::SqlType: TimeValue,
+{
+    DateTimeCall::new(time)
+}
+
+/// Creates a `time(time)` expression, which returns the time as `HH:MM:SS`
+///
+/// See [`date`] for the accepted time values.
+pub fn time<T>(time: T) -> helper_types::time<T>
+where
+    T: Expression,
+    T::SqlType: TimeValue,
+{
+    DateTimeCall::new(time)
+}
+
+/// Creates a `datetime(time)` expression, which returns the timestamp as
+/// `YYYY-MM-DD HH:MM:SS`
+///
+/// See [`date`] for the accepted time values.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let recent = posts::table
+///     .filter(posts::created_at.gt(datetime("now".into_sql::<Text>()).modifier("-7 days")))
+///     .load::<Post>(conn)
+///     .await?;
+/// ```
+pub fn datetime<T>(time: T) -> helper_types::datetime<T>
+where
+    T: Expression,
+    T::SqlType: TimeValue,
+{
+    DateTimeCall::new(time)
+}
+
+/// Creates a `julianday(time)` expression, which returns the fractional
+/// julian day number
+///
+/// See [`date`] for the accepted time values.
+pub fn julianday<T>(time: T) -> helper_types::julianday<T>
+where
+    T: Expression,
+    T::SqlType: TimeValue,
+{
+    DateTimeCall::new(time)
+}
+
+/// Creates a `unixepoch(time)` expression, which returns the seconds since
+/// the unix epoch
+///
+/// See [`date`] for the accepted time values.
+pub fn unixepoch<T>(time: T) -> helper_types::unixepoch<T>
+where
+    T: Expression,
+    T::SqlType: TimeValue,
+{
+    DateTimeCall::new(time)
+}
+
+/// Creates a `strftime(format, time)` expression, which formats the time
+/// value according to `format`, e.g. `%Y-%m`
+///
+/// See [`date`] for the accepted time values.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let months = posts::table
+///     .select(strftime("%Y-%m", posts::created_at))
+///     .distinct()
+///     .load::<String>(conn)
+///     .await?;
+/// ```
+pub fn strftime<F, T>(format: F, time: T) -> helper_types::strftime<F, T>
+where
+    F: AsExpression<Text>,
+    T: Expression,
+    T::SqlType: TimeValue,
+{
+    Strftime::new(format.as_expression(), time)
+}
@@ -0,0 +1,85 @@
+//! Helper types for the return types of the Turso specific expression methods.
+
+use diesel::dsl::{AsExpr, AsExprOf, SqlTypeOf};
+use diesel::sql_types::Text;
+
+use super::expression_methods::private::VectorOrNullableVector;
+#[cfg(feature = "serde_json")]
+use crate::sql_types::Json;
+
+/// The return type of [`lhs.is(rhs)`](crate::dsl::TursoExpressionMethods::is)
+pub type Is<Lhs, Rhs> = super::operators::Is<Lhs, AsExpr<Rhs, Lhs>>;
+
+/// The return type of [`lhs.is_not(rhs)`](crate::dsl::TursoExpressionMethods::is_not)
+pub type IsNot<Lhs, Rhs> = super::operators::IsNot<Lhs, AsExpr<Rhs, Lhs>>;
+
+/// The return type of [`lhs.glob(rhs)`](crate::dsl::TursoExpressionMethods::glob)
+pub type Glob<Lhs, Rhs> = super::operators::Glob<Lhs, AsExpr<Rhs, Lhs>>;
+
+/// The return type of [`lhs.regexp(rhs)`](crate::dsl::TursoExpressionMethods::regexp)
+pub type Regexp<Lhs, Rhs> = super::operators::Regexp<Lhs, AsExpr<Rhs, Lhs>>;
+
+/// The return type of [`expr.collate(collation)`](crate::dsl::TursoExpressionMethods::collate)
+pub type Collate<Expr> = super::collate::Collate<Expr>;
+
+/// The return type of [`lhs.retrieve_as_object(rhs)`](crate::dsl::TursoJsonExpressionMethods::retrieve_as_object)
+#[cfg(feature = "serde_json")]
+pub type RetrieveAsObject<Lhs, Rhs> = super::operators::RetrieveAsObject<Lhs, AsExprOf<Rhs, Text>>;
+
+/// The return type of [`lhs.retrieve_as_text(rhs)`](crate::dsl::TursoJsonExpressionMethods::retrieve_as_text)
+#[cfg(feature = "serde_json")]
+pub type RetrieveAsText<Lhs, Rhs> = super::operators::RetrieveAsText<Lhs, AsExprOf<Rhs, Text>>;
+
+/// The return type of [`json.json_extract::<ST, _>(path)`](crate::dsl::TursoJsonExpressionMethods::json_extract)
+#[cfg(feature = "serde_json")]
+pub type JsonExtract<J, P, ST> = super::json::JsonExtract<J, AsExprOf<P, Text>, ST>;
+
+/// The return type of [`json.json_set(path, value)`](crate::dsl::TursoJsonExpressionMethods::json_set)
+#[cfg(feature = "serde_json")]
+pub type JsonSet<J, P, V> = super::json::JsonSet<J, AsExprOf<P, Text>, AsExprOf<V, Json>>;
+
+/// The return type of [`json.json_each::<ST>()`](crate::dsl::TursoJsonExpressionMethods::json_each)
+#[cfg(feature = "serde_json")]
+pub type JsonEach<J, ST> = super::json::JsonEach<J, ST>;
+
+/// The return type of [`lhs.cosine_distance(rhs)`](crate::dsl::TursoVectorExpressionMethods::cosine_distance)
+pub type CosineDistance<Lhs, Rhs> = super::vector::VectorDistanceCos<
+    Lhs,
+    AsExprOf<Rhs, <SqlTypeOf<Lhs> as VectorOrNullableVector>::Vector>,
+>;
+
+/// The return type of [`lhs.l2_distance(rhs)`](crate::dsl::TursoVectorExpressionMethods::l2_distance)
+pub type L2Distance<Lhs, Rhs> = super::vector::VectorDistanceL2<
+    Lhs,
+    AsExprOf<Rhs, <SqlTypeOf<Lhs> as VectorOrNullableVector>::Vector>,
+>;
+
+/// The return type of [`vector.vector_extract()`](crate::dsl::TursoVectorExpressionMethods::vector_extract)
+pub type VectorExtract<V> = super::vector::VectorExtract<V>;
+
+/// The return type of [`date(time)`](crate::dsl::date())
+#[allow(non_camel_case_types)]
+pub type date<T> = super::date_and_time::DateTimeCall<super::date_and_time::DateFunction, T>;
+
+/// The return type of [`time(time)`](crate::dsl::time())
+#[allow(non_camel_case_types)]
+pub type time<T> = super::date_and_time::DateTimeCall<super::date_and_time::TimeFunction, T>;
+
+/// The return type of [`datetime(time)`](crate::dsl::datetime())
+#[allow(non_camel_case_types)]
+pub type datetime<T> =
+    super::date_and_time::DateTimeCall<super::date_and_time::DatetimeFunction, T>;
+
+/// The return type of [`julianday(time)`](crate::dsl::julianday())
+#[allow(non_camel_case_types)]
+pub type julianday<T> =
+    super::date_and_time::DateTimeCall<super::date_and_time::JuliandayFunction, T>;
+
+/// The return type of [`unixepoch(time)`](crate::dsl::unixepoch())
+#[allow(non_camel_case_types)]
+pub type unixepoch<T> =
+    super::date_and_time::DateTimeCall<super::date_and_time::UnixepochFunction, T>;
+
+/// The return type of [`strftime(format, time)`](crate::dsl::strftime())
+#[allow(non_camel_case_types)]
+pub type strftime<F, T> = super::date_and_time::Strftime<AsExprOf<F, Text>, T>;
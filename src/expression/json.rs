@@ -0,0 +1,214 @@
+//! Query dsl nodes for the JSON functions which do not map onto a single operator
+
+use std::marker::PhantomData;
+
+use diesel::expression::array_comparison::{AsInExpression, InExpression};
+use diesel::expression::expression_types::NotSelectable;
+use diesel::expression::{
+    AppearsOnTable, Expression, SelectableExpression, TypedExpressionType, ValidGrouping,
+};
+use diesel::query_builder::{AstPass, QueryFragment, QueryId};
+use diesel::sql_types::{is_nullable, Nullable, SingleValue, SqlType};
+use diesel::QueryResult;
+
+use crate::backend::TursoBackend;
+
+/// The return type of
+/// [`TursoJsonExpressionMethods::json_extract`](crate::dsl::TursoJsonExpressionMethods::json_extract)
+#[derive(Debug, Clone, Copy, QueryId)]
+pub struct JsonExtract<J, P, ST> {
+    json: J,
+    path: P,
+    _sql_type: PhantomData<ST>,
+}
+
+impl<J, P, ST> JsonExtract<J, P, ST> {
+    pub(crate) fn new(json: J, path: P) -> Self {
+        Self {
+            json,
+            path,
+            _sql_type: PhantomData,
+        }
+    }
+}
+
+impl<J, P, ST> Expression for JsonExtract<J, P, ST>
+where
+    J: Expression,
+    P: Expression,
+    ST: SqlType<IsNull = is_nullable::NotNull> + SingleValue,
+{
+    // `json_extract` returns `NULL` for paths that do not exist
+    type SqlType = Nullable<ST>;
+}
+
+impl<J, P, ST> QueryFragment<TursoBackend> for JsonExtract<J, P, ST>
+where
+    J: QueryFragment<TursoBackend>,
+    P: QueryFragment<TursoBackend>,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        out.push_sql("json_extract(");
+        self.json.walk_ast(out.reborrow())?;
+        out.push_sql(", ");
+        self.path.walk_ast(out.reborrow())?;
+        out.push_sql(")");
+        Ok(())
+    }
+}
+
+impl<J, P, ST, GB> ValidGrouping<GB> for JsonExtract<J, P, ST>
+where
+    (J, P): ValidGrouping<GB>,
+{
+    type IsAggregate = <(J, P) as ValidGrouping<GB>>::IsAggregate;
+}
+
+impl<J, P, ST, QS> SelectableExpression<QS> for JsonExtract<J, P, ST>
+where
+    Self: AppearsOnTable<QS>,
+    J: SelectableExpression<QS>,
+    P: SelectableExpression<QS>,
+{
+}
+
+impl<J, P, ST, QS> AppearsOnTable<QS> for JsonExtract<J, P, ST>
+where
+    Self: Expression,
+    J: AppearsOnTable<QS>,
+    P: AppearsOnTable<QS>,
+{
+}
+
+/// The return type of
+/// [`TursoJsonExpressionMethods::json_set`](crate::dsl::TursoJsonExpressionMethods::json_set)
+#[derive(Debug, Clone, Copy, QueryId, ValidGrouping)]
+pub struct JsonSet<J, P, V> {
+    json: J,
+    path: P,
+    value: V,
+}
+
+impl<J, P, V> JsonSet<J, P, V> {
+    pub(crate) fn new(json: J, path: P, value: V) -> Self {
+        Self { json, path, value }
+    }
+}
+
+impl<J, P, V> Expression for JsonSet<J, P, V>
+where
+    J: Expression,
+    P: Expression,
+    V: Expression,
+{
+    type SqlType = J::SqlType;
+}
+
+impl<J, P, V> QueryFragment<TursoBackend> for JsonSet<J, P, V>
+where
+    J: QueryFragment<TursoBackend>,
+    P: QueryFragment<TursoBackend>,
+    V: QueryFragment<TursoBackend>,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        out.push_sql("json_set(");
+        self.json.walk_ast(out.reborrow())?;
+        out.push_sql(", ");
+        self.path.walk_ast(out.reborrow())?;
+        // Without wrapping the value into `json()` it would be inserted as a json string
+        out.push_sql(", json(");
+        self.value.walk_ast(out.reborrow())?;
+        out.push_sql("))");
+        Ok(())
+    }
+}
+
+diesel::impl_selectable_expression!(JsonSet<J, P, V>);
+
+/// The return type of
+/// [`TursoJsonExpressionMethods::json_each`](crate::dsl::TursoJsonExpressionMethods::json_each)
+///
+/// This node can only be used as the right hand side of `eq_any`/`ne_all`,
+/// it renders as a subselect over the elements of the json array.
+#[derive(Debug, Clone, Copy, QueryId)]
+pub struct JsonEach<J, ST> {
+    json: J,
+    _sql_type: PhantomData<ST>,
+}
+
+impl<J, ST> JsonEach<J, ST> {
+    pub(crate) fn new(json: J) -> Self {
+        Self {
+            json,
+            _sql_type: PhantomData,
+        }
+    }
+}
+
+impl<J, ST> Expression for JsonEach<J, ST>
+where
+    J: Expression,
+    ST: TypedExpressionType,
+{
+    // Same as for `Many`, this is not an actual expression
+    type SqlType = NotSelectable;
+}
+
+impl<J, ST> InExpression for JsonEach<J, ST>
+where
+    ST: SqlType,
+{
+    type SqlType = ST;
+
+    fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn is_array(&self) -> bool {
+        false
+    }
+}
+
+impl<J, ST> AsInExpression<ST> for JsonEach<J, ST>
+where
+    ST: SqlType,
+{
+    type InExpression = Self;
+
+    fn as_in_expression(self) -> Self::InExpression {
+        self
+    }
+}
+
+impl<J, ST> QueryFragment<TursoBackend> for JsonEach<J, ST>
+where
+    J: QueryFragment<TursoBackend>,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        out.push_sql("SELECT value FROM json_each(");
+        self.json.walk_ast(out.reborrow())?;
+        out.push_sql(")");
+        Ok(())
+    }
+}
+
+impl<J, ST, GB> ValidGrouping<GB> for JsonEach<J, ST>
+where
+    J: ValidGrouping<GB>,
+{
+    type IsAggregate = J::IsAggregate;
+}
+
+impl<J, ST, QS> SelectableExpression<QS> for JsonEach<J, ST>
+where
+    Self: AppearsOnTable<QS>,
+    J: SelectableExpression<QS>,
+{
+}
+
+impl<J, ST, QS> AppearsOnTable<QS> for JsonEach<J, ST>
+where
+    Self: Expression,
+    J: AppearsOnTable<QS>,
+{
+}
@@ -0,0 +1,13 @@
+//! Turso related query builder extensions.
+//!
+//! Everything public in this module is re-exported from [`crate::dsl`].
+
+pub(crate) mod collate;
+pub(crate) mod date_and_time;
+pub(crate) mod expression_methods;
+pub(crate) mod functions;
+pub(crate) mod helper_types;
+#[cfg(feature = "serde_json")]
+pub(crate) mod json;
+mod operators;
+pub(crate) mod vector;
@@ -0,0 +1,32 @@
+use diesel::sql_types::Bool;
+#[cfg(feature = "serde_json")]
+use diesel::sql_types::{Nullable, Text};
+
+use crate::backend::TursoBackend;
+#[cfg(feature = "serde_json")]
+use crate::sql_types::Json;
+
+diesel::__diesel_infix_operator!(Is, " IS ", ConstantNullability Bool, backend: TursoBackend);
+diesel::__diesel_infix_operator!(
+    IsNot,
+    " IS NOT ",
+    ConstantNullability Bool,
+    backend: TursoBackend
+);
+diesel::infix_operator!(Glob, " GLOB ", backend: TursoBackend);
+diesel::infix_operator!(Regexp, " REGEXP ", backend: TursoBackend);
+
+#[cfg(feature = "serde_json")]
+diesel::__diesel_infix_operator!(
+    RetrieveAsObject,
+    " -> ",
+    ConstantNullability Nullable<Json>,
+    backend: TursoBackend
+);
+#[cfg(feature = "serde_json")]
+diesel::__diesel_infix_operator!(
+    RetrieveAsText,
+    " ->> ",
+    ConstantNullability Nullable<Text>,
+    backend: TursoBackend
+);
@@ -0,0 +1,98 @@
+//! Query dsl nodes for the vector functions
+
+use diesel::expression::{Expression, ValidGrouping};
+use diesel::query_builder::{AstPass, QueryFragment, QueryId};
+use diesel::QueryResult;
+
+use super::expression_methods::private::VectorOrNullableVector;
+use crate::backend::TursoBackend;
+
+macro_rules! vector_distance {
+    ($(#[$meta:meta])* $name:ident => $function:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, QueryId, ValidGrouping)]
+        pub struct $name<L, R> {
+            left: L,
+            right: R,
+        }
+
+        impl<L, R> $name<L, R> {
+            pub(crate) fn new(left: L, right: R) -> Self {
+                Self { left, right }
+            }
+        }
+
+        impl<L, R> Expression for $name<L, R>
+        where
+            L: Expression,
+            L::SqlType: VectorOrNullableVector,
+            R: Expression,
+        {
+            type SqlType = <L::SqlType as VectorOrNullableVector>::Distance;
+        }
+
+        impl<L, R> QueryFragment<TursoBackend> for $name<L, R>
+        where
+            L: QueryFragment<TursoBackend>,
+            R: QueryFragment<TursoBackend>,
+        {
+            fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+                out.push_sql(concat!($function, "("));
+                self.left.walk_ast(out.reborrow())?;
+                out.push_sql(", ");
+                self.right.walk_ast(out.reborrow())?;
+                out.push_sql(")");
+                Ok(())
+            }
+        }
+
+        diesel::impl_selectable_expression!($name<L, R>);
+    };
+}
+
+vector_distance!(
+    /// The return type of
+    /// [`TursoVectorExpressionMethods::cosine_distance`](crate::dsl::TursoVectorExpressionMethods::cosine_distance)
+    VectorDistanceCos => "vector_distance_cos"
+);
+
+vector_distance!(
+    /// The return type of
+    /// [`TursoVectorExpressionMethods::l2_distance`](crate::dsl::TursoVectorExpressionMethods::l2_distance)
+    VectorDistanceL2 => "vector_distance_l2"
+);
+
+/// The return type of
+/// [`TursoVectorExpressionMethods::vector_extract`](crate::dsl::TursoVectorExpressionMethods::vector_extract)
+#[derive(Debug, Clone, Copy, QueryId, ValidGrouping)]
+pub struct VectorExtract<V> {
+    vector: V,
+}
+
+impl<V> VectorExtract<V> {
+    pub(crate) fn new(vector: V) -> Self {
+        Self { vector }
+    }
+}
+
+impl<V> Expression for VectorExtract<V>
+where
+    V: Expression,
+    V::SqlType: VectorOrNullableVector,
+{
+    type SqlType = <V::SqlType as VectorOrNullableVector>::Extracted;
+}
+
+impl<V> QueryFragment<TursoBackend> for VectorExtract<V>
+where
+    V: QueryFragment<TursoBackend>,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        out.push_sql("vector_extract(");
+        self.vector.walk_ast(out.reborrow())?;
+        out.push_sql(")");
+        Ok(())
+    }
+}
+
+diesel::impl_selectable_expression!(VectorExtract<V>);
@@ -0,0 +1,69 @@
+//! Detection of connections which can't be used anymore
+
+use crate::binding::TursoConnection;
+use crate::AsyncTursoConnection;
+
+impl AsyncTursoConnection {
+    /// Whether a statement of this connection failed with a fatal error,
+    /// e.g. an I/O error on the database file
+    ///
+    /// Such a connection should be dropped, connection pools don't hand it
+    /// out anymore.
+    pub fn has_fatal_error(&self) -> bool {
+        self.connection
+            .as_ref()
+            .is_some_and(TursoConnection::has_fatal_error)
+    }
+}
+
+#[cfg(any(
+    feature = "bb8",
+    feature = "deadpool",
+    feature = "mobc",
+    feature = "r2d2"
+))]
+mod pool {
+    use diesel::QueryResult;
+    use diesel_async::{AnsiTransactionManager, SimpleAsyncConnection, TransactionManager};
+
+    use crate::AsyncTursoConnection;
+
+    impl AsyncTursoConnection {
+        /// Whether the database has an open transaction the transaction
+        /// manager doesn't know about, e.g. one started with `BEGIN` through
+        /// `batch_execute`
+        pub(crate) fn has_untracked_transaction(&mut self) -> bool {
+            let idle = AnsiTransactionManager::transaction_manager_status_mut(self)
+                .transaction_state()
+                .is_ok_and(|status| status.in_transaction.is_none());
+            match &self.connection {
+                // A connection whose state can't be read is unusable as well
+                Some(connection) if idle => !connection.is_autocommit().unwrap_or(false),
+                _ => false,
+            }
+        }
+
+        /// Rolls back all open transactions, except a test transaction
+        pub(crate) async fn rollback_open_transactions(&mut self) -> QueryResult<()> {
+            while AnsiTransactionManager::transaction_manager_status_mut(self)
+                .transaction_state()?
+                .in_transaction
+                .as_ref()
+                .is_some_and(|transaction| !transaction.test_transaction)
+            {
+                AnsiTransactionManager::rollback_transaction(self).await?;
+            }
+            if self.has_untracked_transaction() {
+                self.batch_execute("ROLLBACK").await?;
+            }
+            Ok(())
+        }
+
+        /// Runs the cheapest possible statement, bypassing the
+        /// instrumentation
+        pub(crate) async fn ping_database(&mut self) -> QueryResult<()> {
+            self.ensure_connection().await?;
+            self.query_rows("SELECT 1", Vec::new()).await.map(|_| ())
+        }
+    }
+}
@@ -1,12 +1,11 @@
 use crate::backend::TursoBackend;
-use crate::AsyncTursoConnection;
-use diesel::insertable::{
-    CanInsertInSingleQuery, ColumnInsertValue, DefaultableColumnInsertValue, InsertValues,
-};
+use crate::{construct_query, AsyncTursoConnection};
+use diesel::insertable::{CanInsertInSingleQuery, ColumnInsertValue, DefaultableColumnInsertValue};
 use diesel::prelude::*;
-use diesel::query_builder::{AstPass, DebugQuery, QueryFragment, QueryId};
+use diesel::query_builder::{AstPass, QueryFragment, QueryId};
 use diesel::query_builder::{BatchInsert, InsertStatement, ValuesClause};
-use diesel_async::AsyncConnectionCore;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncConnectionCore};
 use std::fmt::{self, Debug, Display};
 
 use diesel_async::methods::ExecuteDsl;
@@ -49,8 +48,6 @@ impl Any<Yes> for Yes {
     type Out = Yes;
 }
 
-// TODO: do we need it?
-#[allow(dead_code)]
 pub trait ContainsDefaultableValue {
     type Out: Any<Yes> + Any<No>;
 }
@@ -84,18 +81,98 @@ where
     type Out = T::Out;
 }
 
-// TODO: do we need it?
-#[allow(dead_code)]
+macro_rules! impl_contains_defaultable_value {
+    (
+        @build
+        start_ts = [$($ST: ident,)*],
+        ts = [$T1: ident,],
+        bounds = [$($bounds: tt)*],
+        out = [$($out: tt)*],
+    ) => {
+        impl<$($ST,)*> ContainsDefaultableValue for ($($ST,)*)
+        where
+            $($ST: ContainsDefaultableValue,)*
+            $($bounds)*
+            $T1::Out: Any<$($out)*>,
+        {
+            type Out = <$T1::Out as Any<$($out)*>>::Out;
+        }
+    };
+    (
+        @build
+        start_ts = [$($ST: ident,)*],
+        ts = [$T1: ident, $($T: ident,)+],
+        bounds = [$($bounds: tt)*],
+        out = [$($out: tt)*],
+    ) => {
+        impl_contains_defaultable_value! {
+            @build
+            start_ts = [$($ST,)*],
+            ts = [$($T,)*],
+            bounds = [$($bounds)* $T1::Out: Any<$($out)*>,],
+            out = [<$T1::Out as Any<$($out)*>>::Out],
+        }
+    };
+    ($T1: ident, $($T: ident,)+) => {
+        impl_contains_defaultable_value! {
+            @build
+            start_ts = [$T1, $($T,)*],
+            ts = [$($T,)*],
+            bounds = [],
+            out = [$T1::Out],
+        }
+    };
+    ($T1: ident,) => {
+        impl<$T1> ContainsDefaultableValue for ($T1,)
+        where
+            $T1: ContainsDefaultableValue,
+        {
+            type Out = <$T1 as ContainsDefaultableValue>::Out;
+        }
+    };
+}
+
+// Implements `ContainsDefaultableValue` for every tuple size up to the
+// 32 columns supported by diesel's `table!` macro
+macro_rules! contains_defaultable_value_for_tuples {
+    ($T1: ident,) => {
+        impl_contains_defaultable_value!($T1,);
+    };
+    ($T1: ident, $($T: ident,)+) => {
+        impl_contains_defaultable_value!($T1, $($T,)+);
+        contains_defaultable_value_for_tuples!($($T,)+);
+    };
+}
+
+contains_defaultable_value_for_tuples!(
+    T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20, T21,
+    T22, T23, T24, T25, T26, T27, T28, T29, T30, T31, T32,
+);
+
+/// Renders the statements `AsyncTursoConnection` runs for a batch insert.
+///
+/// Batch inserts containing default values are executed as one `INSERT` per
+/// record, which `diesel::debug_query` cannot show for this backend. This
+/// prints each of those statements together with its bind values.
+pub fn debug_query<T>(query: &T) -> TursoDebugQuery<'_, T> {
+    TursoDebugQuery { query }
+}
+
+/// A struct that implements `fmt::Display` and `fmt::Debug` to show the SQL
+/// of a batch insert, as returned by [`debug_query`].
+pub struct TursoDebugQuery<'a, T> {
+    query: &'a T,
+}
+
 pub trait DebugQueryHelper<ContainsDefaultableValue> {
     fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
     fn fmt_display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
 }
 
 impl<T, V, QId, Op, Ret, const STATIC_QUERY_ID: bool> DebugQueryHelper<Yes>
-    for DebugQuery<
+    for TursoDebugQuery<
         '_,
         InsertStatement<T, BatchInsert<Vec<ValuesClause<V, T>>, T, QId, STATIC_QUERY_ID>, Op, Ret>,
-        TursoBackend,
     >
 where
     V: QueryFragment<TursoBackend>,
@@ -105,90 +182,151 @@ where
     for<'b> InsertStatement<T, &'b ValuesClause<V, T>, Op, Ret>: QueryFragment<TursoBackend>,
 {
     fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // Since we can't access query field, we'll provide a generic implementation
-        f.debug_struct("BatchInsertQuery")
-            .field("backend", &"Turso")
+        let statements = self.statements();
+        f.debug_list()
+            .entries(
+                statements
+                    .iter()
+                    .map(diesel::debug_query::<TursoBackend, _>),
+            )
             .finish()
     }
 
     fn fmt_display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // Since we can't access query field, we'll provide a generic implementation
-        writeln!(f, "-- Turso batch insert query")
+        for stmt in self.statements() {
+            writeln!(f, "{}", diesel::debug_query::<TursoBackend, _>(&stmt))?;
+        }
+        Ok(())
     }
 }
 
-#[allow(unsafe_code)] // cast to transparent wrapper type
-impl<'a, T, V, QId, Op, const STATIC_QUERY_ID: bool> DebugQueryHelper<No>
-    for DebugQuery<
+impl<'a, T, V, QId, Op, Ret, const STATIC_QUERY_ID: bool>
+    TursoDebugQuery<
         'a,
-        InsertStatement<T, BatchInsert<V, T, QId, STATIC_QUERY_ID>, Op>,
-        TursoBackend,
+        InsertStatement<T, BatchInsert<Vec<ValuesClause<V, T>>, T, QId, STATIC_QUERY_ID>, Op, Ret>,
     >
 where
     T: Copy + QuerySource,
     Op: Copy,
-    DebugQuery<
-        'a,
-        InsertStatement<T, TursoBatchInsertWrapper<V, T, QId, STATIC_QUERY_ID>, Op>,
-        TursoBackend,
-    >: Debug + Display,
+    Ret: Copy,
+{
+    // The per-record statements run by the `(Yes, InsertStatement)`
+    // `ExecuteDsl` impl below
+    fn statements(&self) -> Vec<InsertStatement<T, &'a ValuesClause<V, T>, Op, Ret>> {
+        let query = self.query;
+        query
+            .records
+            .values
+            .iter()
+            .map(|record| {
+                InsertStatement::new(query.target, record, query.operator, query.returning)
+            })
+            .collect()
+    }
+}
+
+impl<T, V, QId, Op, Ret, const STATIC_QUERY_ID: bool> DebugQueryHelper<No>
+    for TursoDebugQuery<'_, InsertStatement<T, BatchInsert<V, T, QId, STATIC_QUERY_ID>, Op, Ret>>
+where
+    T: Copy + QuerySource,
+    Op: Copy,
+    Ret: Copy,
+    for<'b> InsertStatement<T, &'b TursoBatchInsertWrapper<V, T, QId, STATIC_QUERY_ID>, Op, Ret>:
+        QueryFragment<TursoBackend>,
 {
     fn fmt_debug(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let value = unsafe {
-            // This cast is safe as `TursoBatchInsertWrapper` is #[repr(transparent)]
-            &*(self as *const DebugQuery<
-                'a,
-                InsertStatement<T, BatchInsert<V, T, QId, STATIC_QUERY_ID>, Op>,
-                TursoBackend,
-            >
-                as *const DebugQuery<
-                    'a,
-                    InsertStatement<T, TursoBatchInsertWrapper<V, T, QId, STATIC_QUERY_ID>, Op>,
-                    TursoBackend,
-                >)
-        };
-        <_ as Debug>::fmt(value, f)
+        <_ as Debug>::fmt(&diesel::debug_query::<TursoBackend, _>(&self.wrapped()), f)
     }
 
     fn fmt_display(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let value = unsafe {
+        <_ as Display>::fmt(&diesel::debug_query::<TursoBackend, _>(&self.wrapped()), f)
+    }
+}
+
+#[allow(unsafe_code)] // cast to transparent wrapper type
+impl<'a, T, V, QId, Op, Ret, const STATIC_QUERY_ID: bool>
+    TursoDebugQuery<'a, InsertStatement<T, BatchInsert<V, T, QId, STATIC_QUERY_ID>, Op, Ret>>
+where
+    T: Copy + QuerySource,
+    Op: Copy,
+    Ret: Copy,
+{
+    fn wrapped(
+        &self,
+    ) -> InsertStatement<T, &'a TursoBatchInsertWrapper<V, T, QId, STATIC_QUERY_ID>, Op, Ret> {
+        let query = self.query;
+        let records = unsafe {
             // This cast is safe as `TursoBatchInsertWrapper` is #[repr(transparent)]
-            &*(self as *const DebugQuery<
-                'a,
-                InsertStatement<T, BatchInsert<V, T, QId, STATIC_QUERY_ID>, Op>,
-                TursoBackend,
-            >
-                as *const DebugQuery<
-                    'a,
-                    InsertStatement<T, TursoBatchInsertWrapper<V, T, QId, STATIC_QUERY_ID>, Op>,
-                    TursoBackend,
-                >)
+            &*(&query.records as *const BatchInsert<V, T, QId, STATIC_QUERY_ID>
+                as *const TursoBatchInsertWrapper<V, T, QId, STATIC_QUERY_ID>)
         };
-        <_ as Display>::fmt(value, f)
+        InsertStatement::new(query.target, records, query.operator, query.returning)
     }
 }
 
-// Note: Removed generic ExecuteDsl implementation to avoid conflicts with diesel_async generic impl
-// The default diesel_async ExecuteDsl will handle normal cases now that QueryFragment is implemented
+impl<T, V, QId, Op, Ret, O, const STATIC_QUERY_ID: bool> Display
+    for TursoDebugQuery<
+        '_,
+        InsertStatement<T, BatchInsert<Vec<ValuesClause<V, T>>, T, QId, STATIC_QUERY_ID>, Op, Ret>,
+    >
+where
+    T: QuerySource,
+    V: ContainsDefaultableValue<Out = O>,
+    Self: DebugQueryHelper<O>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_display(f)
+    }
+}
+
+impl<T, V, QId, Op, Ret, O, const STATIC_QUERY_ID: bool> Debug
+    for TursoDebugQuery<
+        '_,
+        InsertStatement<T, BatchInsert<Vec<ValuesClause<V, T>>, T, QId, STATIC_QUERY_ID>, Op, Ret>,
+    >
+where
+    T: QuerySource,
+    V: ContainsDefaultableValue<Out = O>,
+    Self: DebugQueryHelper<O>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_debug(f)
+    }
+}
 
-// ExecuteDsl implementation for (Yes, InsertStatement) for async transactions
+// Batch inserts aren't a `QueryFragment`, so the generic `ExecuteDsl` impl of
+// diesel_async doesn't apply and they are dispatched on whether their records
+// contain default values
+impl<V, T, QId, Op, O, const STATIC_QUERY_ID: bool> ExecuteDsl<AsyncTursoConnection, TursoBackend>
+    for InsertStatement<T, BatchInsert<Vec<ValuesClause<V, T>>, T, QId, STATIC_QUERY_ID>, Op>
+where
+    T: QuerySource,
+    V: ContainsDefaultableValue<Out = O>,
+    O: Default + 'static,
+    (O, Self): ExecuteDsl<AsyncTursoConnection, TursoBackend>,
+{
+    fn execute<'conn, 'query>(
+        query: Self,
+        conn: &'conn mut AsyncTursoConnection,
+    ) -> <AsyncTursoConnection as AsyncConnectionCore>::ExecuteFuture<'conn, 'query>
+    where
+        Self: 'query,
+    {
+        ExecuteDsl::execute((O::default(), query), conn)
+    }
+}
+
+// SQLite has no `DEFAULT` keyword, so records which may contain default values
+// are inserted one statement at a time, in a transaction
 impl<V, T, QId, Op, const STATIC_QUERY_ID: bool> ExecuteDsl<AsyncTursoConnection, TursoBackend>
     for (
         Yes,
         InsertStatement<T, BatchInsert<Vec<ValuesClause<V, T>>, T, QId, STATIC_QUERY_ID>, Op>,
     )
 where
-    T: Table + Copy + QueryId + 'static + Send + Sync,
-    T::FromClause: QueryFragment<TursoBackend>,
-    Op: Copy + QueryId + QueryFragment<TursoBackend> + Send + Sync + 'static,
-    V: InsertValues<TursoBackend, T>
-        + CanInsertInSingleQuery<TursoBackend>
-        + QueryId
-        + Send
-        + Sync
-        + 'static,
-    for<'a> InsertStatement<T, &'a ValuesClause<V, T>, Op>:
-        ExecuteDsl<AsyncTursoConnection, TursoBackend>,
+    T: Copy + QuerySource,
+    Op: Copy,
+    for<'a> InsertStatement<T, &'a ValuesClause<V, T>, Op>: QueryFragment<TursoBackend>,
 {
     fn execute<'conn, 'query>(
         (Yes, query): Self,
@@ -197,14 +335,34 @@ where
     where
         Self: 'query,
     {
+        // Built upfront, the returned future can't borrow the query
+        let statements = query
+            .records
+            .values
+            .iter()
+            .map(|record| {
+                construct_query(&InsertStatement::new(
+                    query.target,
+                    record,
+                    query.operator,
+                    query.returning,
+                ))
+            })
+            .collect::<QueryResult<Vec<_>>>();
+
         Box::pin(async move {
-            let mut result = 0;
-            for record in &query.records.values {
-                let stmt =
-                    InsertStatement::new(query.target, record, query.operator, query.returning);
-                result += diesel_async::RunQueryDsl::execute(stmt, conn).await?;
-            }
-            Ok(result)
+            let statements = statements?;
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                async move {
+                    let mut result = 0;
+                    for statement in statements {
+                        result += conn.execute_query(statement).await?;
+                    }
+                    Ok(result)
+                }
+                .scope_boxed()
+            })
+            .await
         })
     }
 }
@@ -306,32 +464,6 @@ where
     }
 }
 
-// QueryFragment implementation for BatchInsert with TursoBackend and SqliteBatchInsert
-impl<Tab, V, QId, const HAS_STATIC_QUERY_ID: bool>
-    QueryFragment<TursoBackend, crate::backend::SqliteBatchInsert>
-    for BatchInsert<Vec<ValuesClause<V, Tab>>, Tab, QId, HAS_STATIC_QUERY_ID>
-where
-    ValuesClause<V, Tab>: QueryFragment<TursoBackend>,
-    V: QueryFragment<TursoBackend>,
-{
-    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
-        if !HAS_STATIC_QUERY_ID {
-            out.unsafe_to_cache_prepared();
-        }
-
-        let mut values = self.values.iter();
-        if let Some(value) = values.next() {
-            value.walk_ast(out.reborrow())?;
-        }
-        for value in values {
-            out.push_sql(", (");
-            value.values.walk_ast(out.reborrow())?;
-            out.push_sql(")");
-        }
-        Ok(())
-    }
-}
-
 // CanInsertInSingleQuery implementations for TursoBackend that does not support default keywords
 impl<T, Table, QId, const HAS_STATIC_QUERY_ID: bool> CanInsertInSingleQuery<TursoBackend>
     for BatchInsert<T, Table, QId, HAS_STATIC_QUERY_ID>
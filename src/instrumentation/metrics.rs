@@ -0,0 +1,447 @@
+//! In process statement metrics
+//!
+//! Every [`AsyncTursoConnection`] records the statements it runs in a
+//! [`QueryMetrics`] collector. Statements are grouped by the fingerprint of
+//! their SQL, the text built by the query builder with literals replaced by
+//! `?`, so `users.filter(id.eq(1))` and `users.filter(id.eq(2))` count as the
+//! same statement.
+//!
+//! ```rust,no_run
+//! # async fn run() -> diesel::QueryResult<()> {
+//! use diesel_turso::metrics::QueryMetrics;
+//! use diesel_turso::AsyncTursoConnection;
+//!
+//! // One collector can be shared by all connections of a pool
+//! let metrics = QueryMetrics::new();
+//! let mut conn = AsyncTursoConnection::new(":memory:").await.unwrap();
+//! conn.set_query_metrics(metrics.clone());
+//!
+//! // ... run queries ...
+//!
+//! for statement in metrics.snapshot().slowest(5) {
+//!     println!(
+//!         "{} {:?} x{} {}",
+//!         statement.fingerprint(),
+//!         statement.latency().mean(),
+//!         statement.calls(),
+//!         statement.sql(),
+//!     );
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use diesel::connection::InstrumentationEvent;
+
+use super::{error_kind, RowCount};
+use crate::AsyncTursoConnection;
+
+/// The upper bounds of the latency histogram buckets, in microseconds
+///
+/// The last bucket has no upper bound.
+const BUCKET_BOUNDS_MICROS: [u64; 16] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000,
+    1_000_000, 2_500_000, 5_000_000, 10_000_000,
+];
+
+/// The fingerprint of a normalized SQL statement
+///
+/// This is the 64 bit FNV-1a hash of [`StatementStats::sql`], it is stable
+/// across processes and versions of Rust.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StatementFingerprint(u64);
+
+impl StatementFingerprint {
+    fn of(sql: &str) -> Self {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+
+        let hash = sql.bytes().fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        });
+        Self(hash)
+    }
+
+    /// The fingerprint as a number
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for StatementFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// A histogram of durations with fixed buckets, from 100µs to 10s
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; BUCKET_BOUNDS_MICROS.len() + 1],
+    count: u64,
+    sum: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, duration: Duration) {
+        let micros = duration.as_micros();
+        let bucket = BUCKET_BOUNDS_MICROS
+            .iter()
+            .position(|bound| micros <= u128::from(*bound))
+            .unwrap_or(BUCKET_BOUNDS_MICROS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum += duration;
+        self.max = self.max.max(duration);
+    }
+
+    /// The number of recorded durations
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The sum of the recorded durations
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// The longest recorded duration
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// The mean of the recorded durations, zero if there are none
+    pub fn mean(&self) -> Duration {
+        match u32::try_from(self.count) {
+            Ok(0) => Duration::ZERO,
+            Ok(count) => self.sum / count,
+            Err(_) => Duration::from_secs_f64(self.sum.as_secs_f64() / self.count as f64),
+        }
+    }
+
+    /// An upper estimate of the `quantile` (between 0 and 1) of the recorded
+    /// durations
+    ///
+    /// This is the upper bound of the bucket which contains the quantile,
+    /// capped at [`max`](Self::max). Returns `None` if nothing was recorded.
+    pub fn quantile(&self, quantile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = (quantile.clamp(0.0, 1.0) * self.count as f64)
+            .ceil()
+            .max(1.0) as u64;
+        let mut seen = 0;
+        for (bound, count) in self.buckets() {
+            seen += count;
+            if seen >= rank {
+                return Some(bound.map_or(self.max, |bound| bound.min(self.max)));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// The buckets of the histogram with their upper bounds, the last bucket
+    /// has no upper bound
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        BUCKET_BOUNDS_MICROS
+            .iter()
+            .map(|bound| Some(Duration::from_micros(*bound)))
+            .chain([None])
+            .zip(self.buckets.iter().copied())
+    }
+}
+
+/// The metrics of the statements with the same fingerprint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementStats {
+    fingerprint: StatementFingerprint,
+    sql: String,
+    calls: u64,
+    errors: u64,
+    rows: u64,
+    latency: LatencyHistogram,
+}
+
+impl StatementStats {
+    /// The fingerprint of [`sql`](Self::sql)
+    pub fn fingerprint(&self) -> StatementFingerprint {
+        self.fingerprint
+    }
+
+    /// The normalized SQL of the statements
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// How often the statement was run
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// How often the statement failed
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// The number of rows returned or affected by the successful runs
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    /// The latency of all runs, including the failed ones
+    pub fn latency(&self) -> &LatencyHistogram {
+        &self.latency
+    }
+}
+
+/// How a transaction ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TransactionOutcome {
+    Commit,
+    Rollback,
+}
+
+impl fmt::Display for TransactionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionOutcome::Commit => f.write_str("commit"),
+            TransactionOutcome::Rollback => f.write_str("rollback"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MetricsData {
+    statements: HashMap<StatementFingerprint, StatementStats>,
+    transactions: BTreeMap<TransactionOutcome, LatencyHistogram>,
+    errors: BTreeMap<String, u64>,
+}
+
+/// A collector of statement metrics
+///
+/// Clones share the same metrics, so a collector can be given to several
+/// connections with [`AsyncTursoConnection::set_query_metrics`].
+#[derive(Debug, Clone, Default)]
+pub struct QueryMetrics {
+    data: Arc<Mutex<MetricsData>>,
+}
+
+impl QueryMetrics {
+    /// Creates an empty collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn data(&self) -> MutexGuard<'_, MetricsData> {
+        // The data stays consistent even if a thread panicked while holding
+        // the lock, every update is a single step
+        self.data.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns a copy of the metrics collected so far
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let data = self.data();
+        let mut statements = data.statements.values().cloned().collect::<Vec<_>>();
+        statements.sort_by(|a, b| a.sql.cmp(&b.sql));
+        MetricsSnapshot {
+            statements,
+            transactions: data.transactions.clone(),
+            errors: data.errors.clone(),
+        }
+    }
+
+    /// Clears the metrics collected so far
+    pub fn reset(&self) {
+        *self.data() = MetricsData::default();
+    }
+
+    fn record_statement(
+        &self,
+        sql: &str,
+        duration: Duration,
+        result: Result<RowCount, &diesel::result::Error>,
+    ) {
+        let fingerprint = StatementFingerprint::of(sql);
+        let error_kind = result.err().map(error_kind);
+
+        let mut data = self.data();
+        let stats = data
+            .statements
+            .entry(fingerprint)
+            .or_insert_with(|| StatementStats {
+                fingerprint,
+                sql: sql.to_owned(),
+                calls: 0,
+                errors: 0,
+                rows: 0,
+                latency: LatencyHistogram::default(),
+            });
+        stats.calls += 1;
+        stats.latency.record(duration);
+        match result {
+            Ok(RowCount::Returned(rows) | RowCount::Affected(rows)) => stats.rows += rows as u64,
+            Ok(RowCount::Unknown) => {}
+            Err(_) => stats.errors += 1,
+        }
+        if let Some(kind) = error_kind {
+            *data.errors.entry(kind).or_default() += 1;
+        }
+    }
+
+    fn record_transaction(&self, outcome: TransactionOutcome, duration: Duration) {
+        self.data()
+            .transactions
+            .entry(outcome)
+            .or_default()
+            .record(duration);
+    }
+}
+
+/// The metrics collected by a [`QueryMetrics`] at some point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    statements: Vec<StatementStats>,
+    transactions: BTreeMap<TransactionOutcome, LatencyHistogram>,
+    errors: BTreeMap<String, u64>,
+}
+
+impl MetricsSnapshot {
+    /// The metrics of every statement fingerprint, ordered by SQL
+    pub fn statements(&self) -> &[StatementStats] {
+        &self.statements
+    }
+
+    /// The metrics of the statement with the given fingerprint
+    pub fn statement(&self, fingerprint: StatementFingerprint) -> Option<&StatementStats> {
+        self.statements
+            .iter()
+            .find(|stats| stats.fingerprint == fingerprint)
+    }
+
+    /// The `n` statement fingerprints with the highest mean latency, the
+    /// slowest first
+    pub fn slowest(&self, n: usize) -> Vec<&StatementStats> {
+        let mut statements = self.statements.iter().collect::<Vec<_>>();
+        statements.sort_by(|a, b| {
+            b.latency
+                .mean()
+                .cmp(&a.latency.mean())
+                .then_with(|| b.latency.max.cmp(&a.latency.max))
+        });
+        statements.truncate(n);
+        statements
+    }
+
+    /// The duration of the transactions which ended with `outcome`, nested
+    /// transactions (savepoints) included
+    pub fn transactions(&self, outcome: TransactionOutcome) -> Option<&LatencyHistogram> {
+        self.transactions.get(&outcome)
+    }
+
+    /// The number of failed statements per kind of error, e.g.
+    /// `UniqueViolation`
+    pub fn errors(&self) -> &BTreeMap<String, u64> {
+        &self.errors
+    }
+}
+
+/// Per connection state of the metrics
+#[derive(Default)]
+pub(crate) struct MetricsState {
+    collector: QueryMetrics,
+    /// The start of the open transactions, the innermost one last
+    transactions: Vec<Instant>,
+}
+
+impl MetricsState {
+    pub(crate) fn finish_statement(
+        &self,
+        sql: &str,
+        duration: Duration,
+        result: Result<RowCount, &diesel::result::Error>,
+    ) {
+        self.collector.record_statement(sql, duration, result);
+    }
+
+    pub(crate) fn on_connection_event(&mut self, event: &InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::BeginTransaction { .. } => {
+                self.transactions.push(Instant::now());
+            }
+            InstrumentationEvent::CommitTransaction { depth, .. } => {
+                self.finish_transaction(depth.get(), TransactionOutcome::Commit);
+            }
+            InstrumentationEvent::RollbackTransaction { depth, .. } => {
+                self.finish_transaction(depth.get(), TransactionOutcome::Rollback);
+            }
+            _ => {}
+        }
+    }
+
+    fn finish_transaction(&mut self, depth: u32, outcome: TransactionOutcome) {
+        let depth = depth as usize;
+        // A failed commit is followed by a rollback of the same transaction
+        if depth == 0 || self.transactions.len() < depth {
+            return;
+        }
+        for start in self.transactions.drain(depth - 1..).rev() {
+            self.collector.record_transaction(outcome, start.elapsed());
+        }
+    }
+}
+
+impl AsyncTursoConnection {
+    /// The collector of the metrics of this connection
+    pub fn query_metrics(&self) -> &QueryMetrics {
+        &self.instrumentation.metrics.collector
+    }
+
+    /// Records the metrics of this connection in `metrics`, e.g. to collect
+    /// the metrics of all connections of a pool in one place
+    pub fn set_query_metrics(&mut self, metrics: QueryMetrics) {
+        self.instrumentation.metrics.collector = metrics;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{LatencyHistogram, StatementFingerprint};
+
+    #[test]
+    fn histogram_quantiles() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.quantile(0.5), None);
+        for millis in [1, 2, 3, 40, 700] {
+            histogram.record(Duration::from_millis(millis));
+        }
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.max(), Duration::from_millis(700));
+        assert_eq!(histogram.mean(), Duration::from_micros(149_200));
+        assert_eq!(histogram.quantile(0.0), Some(Duration::from_millis(1)));
+        assert_eq!(histogram.quantile(0.5), Some(Duration::from_millis(5)));
+        assert_eq!(histogram.quantile(0.8), Some(Duration::from_millis(50)));
+        assert_eq!(histogram.quantile(1.0), Some(Duration::from_millis(700)));
+    }
+
+    #[test]
+    fn fingerprints_are_stable() {
+        assert_eq!(StatementFingerprint::of("").value(), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(
+            StatementFingerprint::of("a").to_string(),
+            "af63dc4c8601ec8c"
+        );
+        assert_ne!(
+            StatementFingerprint::of("SELECT ?"),
+            StatementFingerprint::of("SELECT ? ")
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Observation of the statements run by `AsyncTursoConnection`
+//!
+//! Every statement is wrapped in a [`Statement`] between
+//! [`ConnectionInstrumentation::start_statement`] and
+//! [`ConnectionInstrumentation::finish_statement`], which feed the slow query
+//! log and the optional integrations enabled by cargo features.
+
+use std::future::Future;
+use std::time::Instant;
+
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use turso::Value;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub(crate) mod slow_query;
+#[cfg(feature = "tracing")]
+mod spans;
+
+use slow_query::{SlowQueryLog, SlowStatement};
+
+/// The instrumentation of a connection, the one set by the user through
+/// `AsyncConnection::set_instrumentation` and the built in integrations
+pub(crate) struct ConnectionInstrumentation {
+    pub(crate) user: Box<dyn Instrumentation>,
+    pub(crate) slow_query_log: Option<SlowQueryLog>,
+    #[cfg(feature = "tracing")]
+    pub(crate) tracing: spans::TracingState,
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: metrics::MetricsState,
+}
+
+impl ConnectionInstrumentation {
+    pub(crate) fn new(user: Box<dyn Instrumentation>) -> Self {
+        Self {
+            user,
+            slow_query_log: None,
+            #[cfg(feature = "tracing")]
+            tracing: spans::TracingState::default(),
+            #[cfg(feature = "metrics")]
+            metrics: metrics::MetricsState::default(),
+        }
+    }
+}
+
+impl Instrumentation for ConnectionInstrumentation {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        #[cfg(feature = "tracing")]
+        self.tracing.on_connection_event(&event);
+        #[cfg(feature = "metrics")]
+        self.metrics.on_connection_event(&event);
+        self.user.on_connection_event(event);
+    }
+}
+
+/// How a statement is run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StatementKind {
+    Load,
+    Execute,
+    BatchExecute,
+}
+
+/// The number of rows a successful statement produced
+#[cfg_attr(not(any(feature = "tracing", feature = "metrics")), allow(dead_code))]
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum RowCount {
+    Returned(usize),
+    Affected(usize),
+    Unknown,
+}
+
+/// A statement which is being run
+pub(crate) struct Statement {
+    #[cfg(feature = "tracing")]
+    span: ::tracing::Span,
+    #[cfg(feature = "metrics")]
+    normalized_sql: String,
+    /// A copy of the bind values, kept for the slow query log
+    binds: Option<Vec<Value>>,
+    start: Instant,
+}
+
+impl Statement {
+    /// Runs the statement in its span
+    pub(crate) fn instrument<F: Future>(&self, future: F) -> impl Future<Output = F::Output> {
+        #[cfg(feature = "tracing")]
+        let future = ::tracing::Instrument::instrument(future, self.span.clone());
+        future
+    }
+}
+
+impl ConnectionInstrumentation {
+    #[cfg_attr(
+        not(any(feature = "tracing", feature = "metrics")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn start_statement(
+        &self,
+        kind: StatementKind,
+        sql: &str,
+        binds: &[Value],
+    ) -> Statement {
+        #[cfg(any(feature = "tracing", feature = "metrics"))]
+        let normalized_sql = normalize_sql(sql);
+        Statement {
+            #[cfg(feature = "tracing")]
+            span: self
+                .tracing
+                .statement_span(kind, sql, &normalized_sql, binds),
+            #[cfg(feature = "metrics")]
+            normalized_sql,
+            binds: self
+                .slow_query_log
+                .as_ref()
+                .filter(|_| kind != StatementKind::BatchExecute)
+                .map(|_| binds.to_vec()),
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the statement if it has to be reported to the slow query log,
+    /// batches are never reported
+    pub(crate) fn finish_statement(
+        &mut self,
+        statement: Statement,
+        result: Result<RowCount, &diesel::result::Error>,
+    ) -> Option<SlowStatement> {
+        let duration = statement.start.elapsed();
+        #[cfg(feature = "tracing")]
+        spans::finish_statement(&statement.span, result);
+        #[cfg(feature = "metrics")]
+        self.metrics
+            .finish_statement(&statement.normalized_sql, duration, result);
+
+        let threshold = self.slow_query_log.as_ref()?.threshold();
+        match statement.binds {
+            Some(binds) if duration >= threshold => Some(SlowStatement {
+                binds,
+                duration,
+                failed: result.is_err(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A short name for the kind of an error, e.g. `UniqueViolation`
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+pub(crate) fn error_kind(error: &diesel::result::Error) -> String {
+    use diesel::result::Error;
+
+    match error {
+        Error::DatabaseError(kind, _) => format!("{kind:?}"),
+        Error::NotFound => "NotFound".to_owned(),
+        Error::QueryBuilderError(_) => "QueryBuilderError".to_owned(),
+        Error::DeserializationError(_) => "DeserializationError".to_owned(),
+        Error::SerializationError(_) => "SerializationError".to_owned(),
+        Error::RollbackTransaction => "RollbackTransaction".to_owned(),
+        Error::AlreadyInTransaction => "AlreadyInTransaction".to_owned(),
+        Error::NotInTransaction => "NotInTransaction".to_owned(),
+        Error::BrokenTransactionManager => "BrokenTransactionManager".to_owned(),
+        _ => "Unknown".to_owned(),
+    }
+}
+
+/// Collapses whitespace and replaces literals by `?`, so statements only
+/// differing in inlined values have the same text
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+pub(crate) fn normalize_sql(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    // Whether the previous character continues an identifier or keyword
+    let mut in_word = false;
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if !normalized.is_empty() && chars.peek().is_some() {
+                    normalized.push(' ');
+                }
+                in_word = false;
+                continue;
+            }
+            // Quoted identifiers are kept
+            '`' | '"' | '[' => {
+                let end = if c == '[' { ']' } else { c };
+                normalized.push(c);
+                for c in chars.by_ref() {
+                    normalized.push(c);
+                    if c == end {
+                        break;
+                    }
+                }
+            }
+            // String and blob literals
+            '\'' => {
+                if in_word && normalized.ends_with(['x', 'X']) {
+                    normalized.pop();
+                }
+                while let Some(c) = chars.next() {
+                    // A quote in the string is escaped as two quotes
+                    if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                }
+                normalized.push('?');
+            }
+            c if c.is_ascii_digit() && !in_word => {
+                while chars
+                    .next_if(|c| c.is_ascii_alphanumeric() || *c == '.')
+                    .is_some()
+                {}
+                normalized.push('?');
+            }
+            c => normalized.push(c),
+        }
+        in_word = c.is_alphanumeric() || c == '_' || c == '$';
+    }
+    normalized
+}
+
+#[cfg(all(test, any(feature = "tracing", feature = "metrics")))]
+mod tests {
+    use super::normalize_sql;
+
+    #[test]
+    fn normalizes_literals_and_whitespace() {
+        assert_eq!(
+            normalize_sql("SELECT  `t1`.`a2`\n FROM `t1`\tWHERE `a2` = 12.5e3 AND b = 'it''s' "),
+            "SELECT `t1`.`a2` FROM `t1` WHERE `a2` = ? AND b = ?"
+        );
+        assert_eq!(
+            normalize_sql("INSERT INTO \"my table\" (x1) VALUES (x'00ff', ?, -1)"),
+            "INSERT INTO \"my table\" (x1) VALUES (?, ?, -?)"
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Reporting of statements which take longer than a threshold
+
+use std::fmt;
+use std::time::Duration;
+
+use turso::Value;
+
+use crate::explain::QueryPlan;
+use crate::{AsyncTursoConnection, TursoValue};
+
+/// Reports the statements of a connection which run longer than a threshold
+///
+/// ```rust,no_run
+/// # async fn run() -> diesel::QueryResult<()> {
+/// use std::time::Duration;
+/// use diesel_turso::{AsyncTursoConnection, SlowQueryLog};
+///
+/// let mut conn = AsyncTursoConnection::new("app.db").await.unwrap();
+/// conn.set_slow_query_log(
+///     SlowQueryLog::new(Duration::from_millis(100), |query| {
+///         eprintln!("slow query ({:?}): {}", query.duration(), query.sql());
+///         if let Some(plan) = query.query_plan() {
+///             eprintln!("{plan}");
+///         }
+///     })
+///     .with_query_plan(true),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct SlowQueryLog {
+    threshold: Duration,
+    query_plan: bool,
+    callback: Box<dyn FnMut(&SlowQuery<'_>) + Send>,
+}
+
+impl SlowQueryLog {
+    /// Calls `callback` for every `load` or `execute` which takes at least
+    /// `threshold`
+    pub fn new(threshold: Duration, callback: impl FnMut(&SlowQuery<'_>) + Send + 'static) -> Self {
+        Self {
+            threshold,
+            query_plan: false,
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Whether to capture the `EXPLAIN QUERY PLAN` of slow statements
+    ///
+    /// The plan is captured by preparing the statement again on the same
+    /// connection once it finished, which takes some extra time. Disabled by
+    /// default.
+    pub fn with_query_plan(mut self, capture: bool) -> Self {
+        self.query_plan = capture;
+        self
+    }
+
+    /// The duration from which statements are reported
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+}
+
+impl fmt::Debug for SlowQueryLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SlowQueryLog")
+            .field("threshold", &self.threshold)
+            .field("query_plan", &self.query_plan)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A statement reported by a [`SlowQueryLog`]
+#[derive(Debug)]
+pub struct SlowQuery<'a> {
+    sql: &'a str,
+    binds: &'a [TursoValue],
+    duration: Duration,
+    failed: bool,
+    query_plan: Option<&'a QueryPlan>,
+}
+
+impl SlowQuery<'_> {
+    /// The SQL of the statement, as built by the query builder
+    pub fn sql(&self) -> &str {
+        self.sql
+    }
+
+    /// The values bound to the parameters of the statement
+    pub fn binds(&self) -> &[TursoValue] {
+        self.binds
+    }
+
+    /// How long the statement took
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Whether the statement returned an error
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+
+    /// The plan of the statement, if [`SlowQueryLog::with_query_plan`] is
+    /// enabled and the plan could be captured
+    pub fn query_plan(&self) -> Option<&QueryPlan> {
+        self.query_plan
+    }
+}
+
+/// A statement which took longer than the threshold of the slow query log
+pub(crate) struct SlowStatement {
+    pub(crate) binds: Vec<Value>,
+    pub(crate) duration: Duration,
+    pub(crate) failed: bool,
+}
+
+impl AsyncTursoConnection {
+    /// Reports every `load` and `execute` which takes at least the threshold
+    /// of `log`, replacing the previous slow query log
+    pub fn set_slow_query_log(&mut self, log: SlowQueryLog) {
+        self.instrumentation.slow_query_log = Some(log);
+    }
+
+    /// Stops reporting slow statements
+    pub fn clear_slow_query_log(&mut self) {
+        self.instrumentation.slow_query_log = None;
+    }
+
+    pub(crate) async fn report_slow_query(&mut self, sql: &str, statement: SlowStatement) {
+        let Some(capture_query_plan) = self
+            .instrumentation
+            .slow_query_log
+            .as_ref()
+            .map(|log| log.query_plan)
+        else {
+            return;
+        };
+        // A statement which can't be explained, e.g. one rejected by the
+        // database, is still reported
+        let query_plan = if capture_query_plan {
+            self.query_plan(sql, statement.binds.clone()).await.ok()
+        } else {
+            None
+        };
+        let binds = statement
+            .binds
+            .into_iter()
+            .map(TursoValue::from_turso_value)
+            .collect::<Vec<_>>();
+        if let Some(log) = &mut self.instrumentation.slow_query_log {
+            (log.callback)(&SlowQuery {
+                sql,
+                binds: &binds,
+                duration: statement.duration,
+                failed: statement.failed,
+                query_plan: query_plan.as_ref(),
+            });
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! `tracing` spans for statements and transactions
+//!
+//! Field names follow the OpenTelemetry semantic conventions for database
+//! client spans where one exists.
+
+use diesel::connection::InstrumentationEvent;
+use tracing::field::{debug, Empty};
+use tracing::{info_span, Span};
+use turso::Value;
+
+use super::{error_kind, RowCount, StatementKind};
+use crate::AsyncTursoConnection;
+
+macro_rules! statement_span {
+    ($name:literal, $operation:expr, $sql:expr, $bind_count:expr) => {
+        info_span!(
+            $name,
+            otel.kind = "client",
+            otel.name = $operation,
+            db.system.name = "sqlite",
+            db.operation.name = $operation,
+            db.query.text = $sql,
+            db.query.parameter_count = $bind_count,
+            db.query.parameters = Empty,
+            db.response.returned_rows = Empty,
+            db.response.affected_rows = Empty,
+            error.type = Empty,
+        )
+    };
+}
+
+/// Per connection state of the `tracing` integration
+#[derive(Default)]
+pub(crate) struct TracingState {
+    record_bind_values: bool,
+    /// The spans of the open transactions, the innermost one last
+    transactions: Vec<Span>,
+}
+
+impl TracingState {
+    pub(crate) fn statement_span(
+        &self,
+        kind: StatementKind,
+        sql: &str,
+        normalized_sql: &str,
+        binds: &[Value],
+    ) -> Span {
+        let operation = sql
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        let new_span = || match kind {
+            StatementKind::Load => {
+                statement_span!("load", &*operation, normalized_sql, binds.len())
+            }
+            StatementKind::Execute => {
+                statement_span!("execute", &*operation, normalized_sql, binds.len())
+            }
+            StatementKind::BatchExecute => {
+                statement_span!("batch_execute", &*operation, normalized_sql, binds.len())
+            }
+        };
+        let span = match self.transactions.last() {
+            Some(transaction) => transaction.in_scope(new_span),
+            None => new_span(),
+        };
+        if self.record_bind_values {
+            span.record("db.query.parameters", debug(binds));
+        }
+        span
+    }
+
+    pub(crate) fn on_connection_event(&mut self, event: &InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::BeginTransaction { depth, .. } => {
+                let new_span = || {
+                    info_span!(
+                        "transaction",
+                        otel.kind = "client",
+                        db.system.name = "sqlite",
+                        db.transaction.depth = depth.get(),
+                        db.transaction.outcome = Empty,
+                    )
+                };
+                let span = match self.transactions.last() {
+                    Some(transaction) => transaction.in_scope(new_span),
+                    None => new_span(),
+                };
+                self.transactions.push(span);
+            }
+            InstrumentationEvent::CommitTransaction { depth, .. } => {
+                self.finish_transaction(depth.get(), "commit");
+            }
+            InstrumentationEvent::RollbackTransaction { depth, .. } => {
+                self.finish_transaction(depth.get(), "rollback");
+            }
+            _ => {}
+        }
+    }
+
+    fn finish_transaction(&mut self, depth: u32, outcome: &str) {
+        let depth = depth as usize;
+        // A failed commit is followed by a rollback of the same transaction
+        if depth == 0 || self.transactions.len() < depth {
+            return;
+        }
+        for span in self.transactions.drain(depth - 1..).rev() {
+            span.record("db.transaction.outcome", outcome);
+        }
+    }
+}
+
+pub(crate) fn finish_statement(span: &Span, result: Result<RowCount, &diesel::result::Error>) {
+    match result {
+        Ok(RowCount::Returned(rows)) => {
+            span.record("db.response.returned_rows", rows);
+        }
+        Ok(RowCount::Affected(rows)) => {
+            span.record("db.response.affected_rows", rows);
+        }
+        Ok(RowCount::Unknown) => {}
+        Err(error) => {
+            span.record("error.type", error_kind(error).as_str());
+        }
+    }
+}
+
+impl AsyncTursoConnection {
+    /// Records the values of bind parameters in the `db.query.parameters`
+    /// field of statement spans
+    ///
+    /// Bind values are redacted by default, only their number is recorded.
+    /// Enable this only if the values don't contain sensitive data.
+    pub fn set_trace_bind_values(&mut self, record: bool) {
+        self.instrumentation.tracing.record_bind_values = record;
+    }
+}
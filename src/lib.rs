@@ -1,14 +1,18 @@
 use backend::TursoBackend;
 use bind_collector::TursoBindCollector;
 use binding::{TursoConnection, TursoDatabase};
+use diesel::result::DatabaseErrorInformation;
 use diesel::{
     connection::{
         get_default_instrumentation, CacheSize, Instrumentation, InstrumentationEvent,
         StrQueryHelper,
     },
     query_builder::{AsQuery, QueryFragment, QueryId},
+    row::Row,
+    sql_types::BigInt,
     ConnectionResult, QueryResult,
 };
+use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::AnsiTransactionManager;
 use diesel_async::{AsyncConnection, AsyncConnectionCore, SimpleAsyncConnection};
 use futures_util::{
@@ -16,26 +20,75 @@ use futures_util::{
     stream::{self, BoxStream},
     FutureExt, StreamExt,
 };
-use query_builder::TursoQueryBuilder;
+use instrumentation::{ConnectionInstrumentation, RowCount, StatementKind};
+use query_builder::{ReturningStatement, TursoQueryBuilder, WriteTarget};
 use row::TursoRow;
+use std::time::Duration;
 use utils::TursoError;
 
 pub mod backend;
+mod backup;
 mod bind_collector;
 mod binding;
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive;
+mod explain;
+mod expression;
+mod health;
 mod insert_with_default_for_turso;
 mod insertable;
+mod instrumentation;
+mod meta;
+pub mod pragma;
 mod query_builder;
 mod row;
+pub mod sql_types;
+mod timeout;
 mod types;
+mod update_and_fetch;
 mod utils;
 mod value;
 
+pub use backup::BackupProgress;
+pub use explain::{ExplainInstruction, QueryPlan, QueryPlanNode};
+pub use insert_with_default_for_turso::{debug_query, TursoDebugQuery};
+#[cfg(feature = "metrics")]
+pub use instrumentation::metrics;
+pub use instrumentation::slow_query::{SlowQuery, SlowQueryLog};
+pub use meta::ExecuteResult;
+pub use timeout::TursoErrorExt;
+pub use value::{TursoStorageClass, TursoValue};
+
+#[cfg(feature = "derive")]
+#[doc(inline)]
+pub use diesel_turso_derive::TursoEnum;
+
+// Lets the code generated by the derive macros refer to `diesel_turso` within this crate
+extern crate self as diesel_turso;
+
+/// Turso specific expression methods, SQL functions and helper types.
+pub mod dsl {
+    #[doc(inline)]
+    pub use crate::expression::collate::Collation;
+    #[doc(inline)]
+    pub use crate::expression::date_and_time::TimeValue;
+    #[doc(inline)]
+    pub use crate::expression::expression_methods::*;
+    #[doc(inline)]
+    pub use crate::expression::functions::*;
+    #[doc(inline)]
+    pub use crate::expression::helper_types::*;
+}
+
 pub struct AsyncTursoConnection {
     transaction_manager: AnsiTransactionManager,
     binding: TursoDatabase,
     pub(crate) connection: Option<TursoConnection>,
-    instrumentation: Box<dyn Instrumentation>,
+    instrumentation: ConnectionInstrumentation,
+    /// Cleared once the database rejected a `RETURNING` clause
+    pub(crate) supports_returning: bool,
+    fetch_timeout: Option<Duration>,
 }
 
 impl AsyncTursoConnection {
@@ -45,7 +98,11 @@ impl AsyncTursoConnection {
             transaction_manager: AnsiTransactionManager::default(),
             binding,
             connection: None,
-            instrumentation: Box::new(get_default_instrumentation()),
+            instrumentation: ConnectionInstrumentation::new(
+                Box::new(get_default_instrumentation()),
+            ),
+            supports_returning: true,
+            fetch_timeout: None,
         })
     }
 
@@ -76,14 +133,23 @@ impl SimpleAsyncConnection for AsyncTursoConnection {
         let conn = self.connection.as_ref().unwrap();
         let stmt = conn.prepare(query);
 
-        let result = conn.execute_batch(&stmt).await.map_err(|e| {
-            diesel::result::Error::DatabaseError(
-                diesel::result::DatabaseErrorKind::UnableToSendCommand,
-                Box::new(TursoError {
-                    message: e.to_string(),
-                }),
-            )
-        });
+        let statement =
+            self.instrumentation
+                .start_statement(StatementKind::BatchExecute, query, &[]);
+        let result = statement
+            .instrument(conn.execute_batch(&stmt))
+            .await
+            .map_err(|e| {
+                diesel::result::Error::DatabaseError(
+                    diesel::result::DatabaseErrorKind::UnableToSendCommand,
+                    Box::new(TursoError {
+                        message: e.to_string(),
+                    }),
+                )
+            })
+            .map(|_| ());
+        self.instrumentation
+            .finish_statement(statement, result.as_ref().map(|_| RowCount::Unknown));
 
         self.instrumentation()
             .on_connection_event(InstrumentationEvent::finish_query(
@@ -106,56 +172,11 @@ impl AsyncConnectionCore for AsyncTursoConnection {
         T: AsQuery + 'query,
         T::Query: QueryFragment<Self::Backend> + QueryId + 'query,
     {
-        let source = source.as_query();
-        let mut query_builder = TursoQueryBuilder::default();
-        source.to_sql(&mut query_builder, &TursoBackend).unwrap();
-        let sql = query_builder.sql.clone();
-        let binds = construct_bind_data(&source).unwrap();
+        let query = construct_query(&source.as_query());
 
         async move {
-            self.ensure_connection().await?;
-            let conn = self.connection.as_ref().unwrap();
-
-            let mut stmt = conn.prepare(&sql);
-            stmt.bind(binds);
-
-            let result = conn.query(&stmt).await.map_err(|e| {
-                diesel::result::Error::DatabaseError(
-                    diesel::result::DatabaseErrorKind::Unknown,
-                    Box::new(TursoError {
-                        message: e.to_string(),
-                    }),
-                )
-            })?;
-
-            if let Some(error) = result.error() {
-                return Err(diesel::result::Error::DatabaseError(
-                    diesel::result::DatabaseErrorKind::Unknown,
-                    Box::new(TursoError { message: error }),
-                ));
-            }
-
-            let results = result.results().unwrap_or_else(Vec::new);
-
-            if results.is_empty() {
-                return Ok(stream::iter(vec![]).boxed());
-            }
-
-            let field_keys: Vec<String> = if !results.is_empty() && !results[0].is_empty() {
-                results[0].iter().map(|(key, _)| key.clone()).collect()
-            } else {
-                Vec::new()
-            };
-
-            let rows: Vec<QueryResult<TursoRow>> = results
-                .iter()
-                .map(|row| {
-                    let values: Vec<turso::Value> = row.iter().map(|(_, v)| v.clone()).collect();
-                    Ok(TursoRow::from_turso_values(values, field_keys.clone()))
-                })
-                .collect();
-            let iter = stream::iter(rows).boxed();
-            Ok(iter)
+            let rows = self.load_query(query?).await?;
+            Ok(stream::iter(rows.into_iter().map(Ok)).boxed())
         }
         .boxed()
     }
@@ -168,38 +189,309 @@ impl AsyncConnectionCore for AsyncTursoConnection {
     where
         T: QueryFragment<Self::Backend> + QueryId + 'query,
     {
-        let mut query_builder = TursoQueryBuilder::default();
-        source.to_sql(&mut query_builder, &TursoBackend).unwrap();
-        let sql = query_builder.sql.clone();
-        let binds = construct_bind_data(&source).unwrap();
+        let query = construct_query(&source);
 
-        async move {
-            self.ensure_connection().await?;
-            let conn = self.connection.as_ref().unwrap();
+        async move { self.execute_query(query?).await }.boxed()
+    }
+}
 
-            let mut stmt = conn.prepare(&sql);
-            stmt.bind(binds);
+/// The SQL and bind values of a query, built before it is run so the future
+/// running it doesn't borrow the query
+pub(crate) struct ConstructedQuery {
+    sql: String,
+    returning: Option<ReturningStatement>,
+    binds: Vec<turso::Value>,
+}
 
-            let result = conn.execute(&stmt).await.map_err(|e| {
-                diesel::result::Error::DatabaseError(
-                    diesel::result::DatabaseErrorKind::Unknown,
-                    Box::new(TursoError {
-                        message: e.to_string(),
-                    }),
-                )
-            })?;
+pub(crate) fn construct_query<T>(query: &T) -> QueryResult<ConstructedQuery>
+where
+    T: QueryFragment<TursoBackend>,
+{
+    let mut query_builder = TursoQueryBuilder::default();
+    query.to_sql(&mut query_builder, &TursoBackend)?;
+    Ok(ConstructedQuery {
+        sql: query_builder.sql,
+        returning: query_builder.returning(),
+        binds: construct_bind_data(query)?,
+    })
+}
 
-            if let Some(error) = result.error() {
-                return Err(diesel::result::Error::DatabaseError(
-                    diesel::result::DatabaseErrorKind::Unknown,
-                    Box::new(TursoError { message: error }),
+impl AsyncTursoConnection {
+    /// Runs a query which returns rows, reporting it to the instrumentation
+    pub(crate) async fn load_query(
+        &mut self,
+        query: ConstructedQuery,
+    ) -> QueryResult<Vec<TursoRow>> {
+        let ConstructedQuery {
+            sql,
+            returning,
+            binds,
+        } = query;
+        self.ensure_connection().await?;
+
+        let statement = self
+            .instrumentation
+            .start_statement(StatementKind::Load, &sql, &binds);
+        let result = statement
+            .instrument(self.load_rows(&sql, returning, binds))
+            .await;
+        let slow = self.instrumentation.finish_statement(
+            statement,
+            result.as_ref().map(|rows| RowCount::Returned(rows.len())),
+        );
+        if let Some(slow) = slow {
+            self.report_slow_query(&sql, slow).await;
+        }
+        result
+    }
+
+    /// Runs a statement and returns the number of changed rows, reporting it
+    /// to the instrumentation
+    pub(crate) async fn execute_query(&mut self, query: ConstructedQuery) -> QueryResult<usize> {
+        let ConstructedQuery { sql, binds, .. } = query;
+        self.ensure_connection().await?;
+
+        let statement = self
+            .instrumentation
+            .start_statement(StatementKind::Execute, &sql, &binds);
+        let result = statement
+            .instrument(self.execute_statement(&sql, binds))
+            .await;
+        let slow = self.instrumentation.finish_statement(
+            statement,
+            result.as_ref().map(|changes| RowCount::Affected(*changes)),
+        );
+        if let Some(slow) = slow {
+            self.report_slow_query(&sql, slow).await;
+        }
+        result
+    }
+
+    async fn load_rows(
+        &mut self,
+        sql: &str,
+        returning: Option<ReturningStatement>,
+        binds: Vec<turso::Value>,
+    ) -> QueryResult<Vec<TursoRow>> {
+        match returning {
+            None => self.query_rows(sql, binds).await,
+            Some(returning) if !self.supports_returning => {
+                self.load_without_returning(sql, returning, binds).await
+            }
+            Some(returning) => match self.query_rows(sql, binds.clone()).await {
+                Err(e) if is_unsupported_returning(&e) => {
+                    self.supports_returning = false;
+                    self.load_without_returning(sql, returning, binds).await
+                }
+                result => result,
+            },
+        }
+    }
+
+    async fn query_rows(
+        &mut self,
+        sql: &str,
+        binds: Vec<turso::Value>,
+    ) -> QueryResult<Vec<TursoRow>> {
+        let conn = self.connection.as_ref().unwrap();
+
+        let mut stmt = conn.prepare(sql);
+        stmt.bind(binds).deadline(self.fetch_deadline());
+
+        let result = conn.query(&stmt).await.map_err(|e| {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::Unknown,
+                Box::new(TursoError {
+                    message: e.to_string(),
+                }),
+            )
+        })?;
+
+        if let Some(error) = result.error() {
+            return Err(diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::Unknown,
+                Box::new(TursoError { message: error }),
+            ));
+        }
+        if result.is_interrupted() {
+            return Err(self.fetch_timeout_error());
+        }
+
+        let results = result.results().unwrap_or_else(Vec::new);
+
+        let field_keys: Vec<String> = if !results.is_empty() && !results[0].is_empty() {
+            results[0].iter().map(|(key, _)| key.clone()).collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(results
+            .iter()
+            .map(|row| {
+                let values: Vec<turso::Value> = row.iter().map(|(_, v)| v.clone()).collect();
+                TursoRow::from_turso_values(values, field_keys.clone())
+            })
+            .collect())
+    }
+
+    async fn execute_statement(
+        &mut self,
+        sql: &str,
+        binds: Vec<turso::Value>,
+    ) -> QueryResult<usize> {
+        let conn = self.connection.as_ref().unwrap();
+
+        let mut stmt = conn.prepare(sql);
+        stmt.bind(binds);
+
+        let result = conn.execute(&stmt).await.map_err(|e| {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::Unknown,
+                Box::new(TursoError {
+                    message: e.to_string(),
+                }),
+            )
+        })?;
+
+        if let Some(error) = result.error() {
+            return Err(diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::Unknown,
+                Box::new(TursoError { message: error }),
+            ));
+        }
+        let meta = result.meta();
+        Ok(meta.changes)
+    }
+
+    /// Emulates `RETURNING` for databases which don't support it
+    ///
+    /// An `INSERT` is followed by a lookup of the inserted row by its rowid,
+    /// an `UPDATE` by a lookup of the rows its `WHERE` clause matched before,
+    /// both in the same transaction.
+    async fn load_without_returning(
+        &mut self,
+        sql: &str,
+        returning: ReturningStatement,
+        mut binds: Vec<turso::Value>,
+    ) -> QueryResult<Vec<TursoRow>> {
+        let clause = returning.clause;
+        let statement = &sql[..clause.sql];
+        let columns = &sql[clause.sql + query_builder::RETURNING.len()..];
+        let select_binds = binds.split_off(clause.bind_params);
+        let table = match &returning.target {
+            Some(WriteTarget::Update { table, filter }) => {
+                let filter = filter.map(|filter| {
+                    (
+                        &statement[filter.sql..],
+                        binds[filter.bind_params..].to_vec(),
+                    )
+                });
+                return self
+                    .update_without_returning(
+                        statement,
+                        table,
+                        filter,
+                        columns,
+                        binds,
+                        select_binds,
+                    )
+                    .await;
+            }
+            // An upsert which updates an existing row doesn't set the last
+            // inserted rowid
+            Some(WriteTarget::Insert { upsert: true, .. }) => {
+                return Err(unsupported_returning(
+                    "it can't be emulated for `ON CONFLICT DO UPDATE`",
                 ));
             }
+            Some(WriteTarget::Insert { table, .. }) => table,
+            None => {
+                return Err(unsupported_returning(
+                    "it can only be emulated for `INSERT` and `UPDATE` statements",
+                ))
+            }
+        };
+        let select = format!("SELECT {columns} FROM {table} WHERE rowid = last_insert_rowid()");
+
+        self.transaction(|conn| {
+            async move {
+                match conn.execute_statement(statement, binds).await? {
+                    // e.g. `INSERT OR IGNORE` of an existing row
+                    0 => Ok(Vec::new()),
+                    1 => conn.query_rows(&select, select_binds).await,
+                    _ => Err(unsupported_returning(
+                        "it can only be emulated for single row inserts",
+                    )),
+                }
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    /// `filter` is the `WHERE` clause of the statement and its binds
+    async fn update_without_returning(
+        &mut self,
+        statement: &str,
+        table: &str,
+        filter: Option<(&str, Vec<turso::Value>)>,
+        columns: &str,
+        binds: Vec<turso::Value>,
+        select_binds: Vec<turso::Value>,
+    ) -> QueryResult<Vec<TursoRow>> {
+        let (filter, filter_binds) = filter.unwrap_or_default();
+        let select_rowids = format!("SELECT rowid FROM {table}{filter}");
+
+        self.transaction(|conn| {
+            async move {
+                let rowids = conn
+                    .query_rows(&select_rowids, filter_binds)
+                    .await?
+                    .iter()
+                    .map(|row| row.get_value::<BigInt, i64, _>(0))
+                    .collect::<diesel::deserialize::Result<Vec<_>>>()
+                    .map_err(diesel::result::Error::DeserializationError)?;
+                let changes = conn.execute_statement(statement, binds).await?;
+                if rowids.is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                let placeholders = vec!["?"; rowids.len()].join(", ");
+                let select = format!(
+                    "SELECT {columns} FROM {table} WHERE rowid IN ({placeholders}) ORDER BY rowid"
+                );
+                let mut binds = select_binds;
+                binds.extend(rowids.into_iter().map(turso::Value::Integer));
+                let rows = conn.query_rows(&select, binds).await?;
+                // Rows whose rowid changed can't be found again
+                if rows.len() != changes {
+                    return Err(unsupported_returning(
+                        "it can't be emulated for updates which change the rowid",
+                    ));
+                }
+                Ok(rows)
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+}
 
-            let meta = result.meta();
-            Ok(meta.changes)
+fn unsupported_returning(reason: &str) -> diesel::result::Error {
+    diesel::result::Error::QueryBuilderError(
+        format!("The database doesn't support `RETURNING`, {reason}").into(),
+    )
+}
+
+/// Returns `true` for the syntax error of databases which don't support
+/// `RETURNING`, e.g. `near "RETURNING": syntax error`
+fn is_unsupported_returning(error: &diesel::result::Error) -> bool {
+    match error {
+        diesel::result::Error::DatabaseError(_, info) => {
+            let message = info.message();
+            message.contains("near \"RETURNING\"") && message.contains("syntax error")
         }
-        .boxed()
+        _ => false,
     }
 }
 
@@ -218,12 +510,12 @@ impl AsyncConnection for AsyncTursoConnection {
 
     #[doc(hidden)]
     fn instrumentation(&mut self) -> &mut dyn Instrumentation {
-        &mut *self.instrumentation
+        &mut self.instrumentation
     }
 
     #[doc = " Set a specific [`Instrumentation`] implementation for this connection"]
     fn set_instrumentation(&mut self, instrumentation: impl Instrumentation) {
-        self.instrumentation = Box::new(instrumentation);
+        self.instrumentation.user = Box::new(instrumentation);
     }
 
     #[doc = " Set the prepared statement cache size to [`CacheSize`] for this connection"]
@@ -254,7 +546,50 @@ where
     feature = "mobc",
     feature = "r2d2"
 ))]
-impl diesel_async::pooled_connection::PoolableConnection for AsyncTursoConnection {}
+impl diesel_async::pooled_connection::PoolableConnection for AsyncTursoConnection {
+    /// Rolls back open transactions, except a test transaction, and checks
+    /// the connection according to `config`
+    ///
+    /// `RecyclingMethod::Verified` runs `SELECT 1` without reporting it to the
+    /// instrumentation.
+    async fn ping(
+        &mut self,
+        config: &diesel_async::pooled_connection::RecyclingMethod<Self>,
+    ) -> QueryResult<()> {
+        use diesel_async::pooled_connection::RecyclingMethod;
+        use diesel_async::RunQueryDsl;
+
+        if self.has_fatal_error() {
+            return Err(diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::ClosedConnection,
+                Box::new(TursoError {
+                    message: "The connection failed with a fatal error before".to_owned(),
+                }),
+            ));
+        }
+        self.rollback_open_transactions().await?;
+        match config {
+            RecyclingMethod::Fast => Ok(()),
+            RecyclingMethod::Verified => self.ping_database().await,
+            RecyclingMethod::CustomQuery(query) => diesel::sql_query(query.as_ref())
+                .execute(self)
+                .await
+                .map(|_| ()),
+            RecyclingMethod::CustomFunction(check) => check(self).await,
+        }
+    }
+
+    /// Whether the connection has to be discarded: after a fatal error, with
+    /// an open or broken transaction, or if the database has a transaction
+    /// the transaction manager doesn't know about
+    fn is_broken(&mut self) -> bool {
+        use diesel_async::TransactionManager;
+
+        self.has_fatal_error()
+            || AnsiTransactionManager::is_broken_transaction_manager(self)
+            || self.has_untracked_transaction()
+    }
+}
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,88 @@
+//! Row ids and change counts of executed statements
+
+use diesel::result::Error;
+use diesel::row::Row;
+use diesel::sql_types::BigInt;
+use diesel::QueryResult;
+use diesel_async::methods::ExecuteDsl;
+
+use crate::AsyncTursoConnection;
+
+/// The outcome of [`AsyncTursoConnection::execute_with_meta`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecuteResult {
+    changes: usize,
+    last_insert_rowid: i64,
+}
+
+impl ExecuteResult {
+    /// The number of rows inserted, updated or deleted by the statement
+    pub fn changes(&self) -> usize {
+        self.changes
+    }
+
+    /// The rowid of the most recent successful `INSERT` on the connection
+    ///
+    /// For a statement inserting several rows this is the rowid of the last
+    /// one. Statements which don't insert anything leave it unchanged.
+    pub fn last_insert_rowid(&self) -> i64 {
+        self.last_insert_rowid
+    }
+}
+
+impl AsyncTursoConnection {
+    /// Executes a statement like [`RunQueryDsl::execute`] and returns the
+    /// number of changed rows together with the rowid of the last inserted row
+    ///
+    /// ```rust,ignore
+    /// let result = connection
+    ///     .execute_with_meta(diesel::insert_into(users::table).values(users::name.eq("Jane")))
+    ///     .await?;
+    /// assert_eq!(result.changes(), 1);
+    /// let jane = users::table.find(result.last_insert_rowid() as i32);
+    /// ```
+    pub async fn execute_with_meta<T>(&mut self, query: T) -> QueryResult<ExecuteResult>
+    where
+        T: ExecuteDsl<Self>,
+    {
+        let changes = ExecuteDsl::execute(query, self).await?;
+        Ok(ExecuteResult {
+            changes,
+            last_insert_rowid: self.last_insert_rowid().await?,
+        })
+    }
+
+    /// The rowid of the most recent successful `INSERT` on this connection,
+    /// `0` if nothing was inserted yet
+    pub async fn last_insert_rowid(&mut self) -> QueryResult<i64> {
+        self.ensure_connection().await?;
+        Ok(self.connection.as_ref().unwrap().last_insert_rowid())
+    }
+
+    /// The number of rows inserted, updated or deleted by the most recent
+    /// `INSERT`, `UPDATE` or `DELETE` statement
+    pub async fn changes(&mut self) -> QueryResult<usize> {
+        self.count("changes").await
+    }
+
+    /// The number of rows inserted, updated or deleted since the connection
+    /// was opened
+    pub async fn total_changes(&mut self) -> QueryResult<usize> {
+        self.count("total_changes").await
+    }
+
+    /// Runs `SELECT {function}()`, bypassing the instrumentation like the
+    /// pragmas
+    async fn count(&mut self, function: &str) -> QueryResult<usize> {
+        self.ensure_connection().await?;
+        let sql = format!("SELECT {function}()");
+        let rows = self.query_rows(&sql, Vec::new()).await?;
+        let row = rows.first().ok_or_else(|| {
+            Error::DeserializationError(format!("`{sql}` returned no row").into())
+        })?;
+        let count = row
+            .get_value::<BigInt, i64, _>(0)
+            .map_err(Error::DeserializationError)?;
+        Ok(count as usize)
+    }
+}
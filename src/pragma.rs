@@ -0,0 +1,735 @@
+//! Typed access to `PRAGMA` statements
+//!
+//! ```rust,ignore
+//! use diesel_turso::pragma::{CheckpointMode, JournalMode, Synchronous};
+//!
+//! connection.set_journal_mode(JournalMode::Wal).await?;
+//! connection.set_synchronous(Synchronous::Normal).await?;
+//! connection.set_foreign_keys(true).await?;
+//!
+//! for column in connection.table_info("users").await? {
+//!     println!("{}: {}", column.name(), column.type_name());
+//! }
+//! assert!(connection.foreign_key_check(None).await?.is_empty());
+//!
+//! // Keeps the WAL of a long-running service small
+//! connection.wal_checkpoint(CheckpointMode::Truncate).await?;
+//! let stats = connection.database_stats().await?;
+//! println!("{} pages, {} free", stats.page_count(), stats.freelist_count());
+//! ```
+//!
+//! The pragmas run on the connection without being reported to the
+//! instrumentation, like `EXPLAIN`.
+
+use diesel::deserialize::{self, FromSql};
+use diesel::result::Error;
+use diesel::row::Row;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text};
+use diesel::QueryResult;
+
+use crate::backend::TursoBackend;
+use crate::row::TursoRow;
+use crate::utils::quote_identifier;
+use crate::AsyncTursoConnection;
+
+/// How the database keeps its rollback journal, `PRAGMA journal_mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JournalMode {
+    /// The journal is deleted at the end of each transaction
+    Delete,
+    /// The journal is truncated at the end of each transaction
+    Truncate,
+    /// The header of the journal is overwritten at the end of each
+    /// transaction
+    Persist,
+    /// The journal is kept in memory
+    Memory,
+    /// A write-ahead log is used instead of a rollback journal
+    Wal,
+    /// No journal, transactions can't be rolled back reliably
+    Off,
+}
+
+impl JournalMode {
+    /// The name of the mode as used in SQL, e.g. `wal`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalMode::Delete => "delete",
+            JournalMode::Truncate => "truncate",
+            JournalMode::Persist => "persist",
+            JournalMode::Memory => "memory",
+            JournalMode::Wal => "wal",
+            JournalMode::Off => "off",
+        }
+    }
+
+    fn parse(mode: &str) -> deserialize::Result<Self> {
+        match mode.to_ascii_lowercase().as_str() {
+            "delete" => Ok(JournalMode::Delete),
+            "truncate" => Ok(JournalMode::Truncate),
+            "persist" => Ok(JournalMode::Persist),
+            "memory" => Ok(JournalMode::Memory),
+            "wal" => Ok(JournalMode::Wal),
+            "off" => Ok(JournalMode::Off),
+            _ => Err(format!("Unknown journal mode `{mode}`").into()),
+        }
+    }
+}
+
+/// How often the database syncs writes to disk, `PRAGMA synchronous`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Synchronous {
+    /// Never sync, the database can be corrupted by a power loss
+    Off,
+    /// Sync at the most critical moments
+    Normal,
+    /// Sync at the end of each transaction
+    Full,
+    /// Like `Full`, and also sync the directory of a deleted journal
+    Extra,
+}
+
+impl Synchronous {
+    /// The name of the level as used in SQL, e.g. `NORMAL`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Synchronous::Off => "OFF",
+            Synchronous::Normal => "NORMAL",
+            Synchronous::Full => "FULL",
+            Synchronous::Extra => "EXTRA",
+        }
+    }
+
+    fn parse(level: i64) -> deserialize::Result<Self> {
+        match level {
+            0 => Ok(Synchronous::Off),
+            1 => Ok(Synchronous::Normal),
+            2 => Ok(Synchronous::Full),
+            3 => Ok(Synchronous::Extra),
+            _ => Err(format!("Unknown synchronous level `{level}`").into()),
+        }
+    }
+}
+
+/// The suggested maximum size of the page cache, `PRAGMA cache_size`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CacheSize {
+    /// A number of pages
+    Pages(u64),
+    /// A number of KiB, independent of the page size
+    Kibibytes(u64),
+}
+
+impl CacheSize {
+    // The database stores sizes in KiB as negative numbers
+    fn to_pragma_value(self) -> i64 {
+        match self {
+            CacheSize::Pages(pages) => pages as i64,
+            CacheSize::Kibibytes(kibibytes) => -(kibibytes as i64),
+        }
+    }
+
+    fn from_pragma_value(value: i64) -> Self {
+        if value < 0 {
+            CacheSize::Kibibytes(value.unsigned_abs())
+        } else {
+            CacheSize::Pages(value as u64)
+        }
+    }
+}
+
+/// The result of `PRAGMA integrity_check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityCheck {
+    problems: Vec<String>,
+}
+
+impl IntegrityCheck {
+    /// Whether the check found no problem
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// The descriptions of the problems the check found
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+}
+
+/// A column of a table, one row of `PRAGMA table_info`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnInfo {
+    cid: i64,
+    name: String,
+    type_name: String,
+    not_null: bool,
+    default_value: Option<String>,
+    primary_key: i64,
+}
+
+impl ColumnInfo {
+    /// The position of the column in the table, starting at `0`
+    pub fn cid(&self) -> i64 {
+        self.cid
+    }
+
+    /// The name of the column
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The declared type of the column, e.g. `INTEGER`, empty if the column
+    /// has no type
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Whether the column is `NOT NULL`
+    pub fn not_null(&self) -> bool {
+        self.not_null
+    }
+
+    /// The SQL of the default value of the column
+    pub fn default_value(&self) -> Option<&str> {
+        self.default_value.as_deref()
+    }
+
+    /// The position of the column in the primary key, starting at `1`, or
+    /// `0` if the column isn't part of the primary key
+    pub fn primary_key(&self) -> i64 {
+        self.primary_key
+    }
+
+    fn from_row(row: &TursoRow) -> deserialize::Result<Self> {
+        // The columns are `cid`, `name`, `type`, `notnull`, `dflt_value` and
+        // `pk`
+        Ok(Self {
+            cid: row.get_value::<BigInt, i64, _>(0)?,
+            name: row.get_value::<Text, String, _>(1)?,
+            type_name: row.get_value::<Text, String, _>(2)?,
+            not_null: row.get_value::<BigInt, i64, _>(3)? != 0,
+            default_value: row.get_value::<Nullable<Text>, Option<String>, _>(4)?,
+            primary_key: row.get_value::<BigInt, i64, _>(5)?,
+        })
+    }
+}
+
+/// Why an index exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexOrigin {
+    /// A `CREATE INDEX` statement
+    CreateIndex,
+    /// A `UNIQUE` constraint
+    Unique,
+    /// A `PRIMARY KEY` constraint
+    PrimaryKey,
+}
+
+/// An index of a table, one row of `PRAGMA index_list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexInfo {
+    seq: i64,
+    name: String,
+    unique: bool,
+    origin: IndexOrigin,
+    partial: bool,
+}
+
+impl IndexInfo {
+    /// The position of the index in the list
+    pub fn seq(&self) -> i64 {
+        self.seq
+    }
+
+    /// The name of the index
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the index is `UNIQUE`
+    pub fn unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Why the index exists
+    pub fn origin(&self) -> IndexOrigin {
+        self.origin
+    }
+
+    /// Whether the index only covers the rows matching a `WHERE` clause
+    pub fn partial(&self) -> bool {
+        self.partial
+    }
+
+    fn from_row(row: &TursoRow) -> deserialize::Result<Self> {
+        // The columns are `seq`, `name`, `unique`, `origin` and `partial`
+        let origin = match row.get_value::<Text, String, _>(3)?.as_str() {
+            "c" => IndexOrigin::CreateIndex,
+            "u" => IndexOrigin::Unique,
+            "pk" => IndexOrigin::PrimaryKey,
+            origin => return Err(format!("Unknown index origin `{origin}`").into()),
+        };
+        Ok(Self {
+            seq: row.get_value::<BigInt, i64, _>(0)?,
+            name: row.get_value::<Text, String, _>(1)?,
+            unique: row.get_value::<BigInt, i64, _>(2)? != 0,
+            origin,
+            partial: row.get_value::<BigInt, i64, _>(4)? != 0,
+        })
+    }
+}
+
+/// A row whose foreign key doesn't match a row of the parent table, one row
+/// of `PRAGMA foreign_key_check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeyViolation {
+    table: String,
+    rowid: Option<i64>,
+    parent: String,
+    foreign_key_id: i64,
+}
+
+impl ForeignKeyViolation {
+    /// The table of the row
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// The rowid of the row, `None` for tables without rowid
+    pub fn rowid(&self) -> Option<i64> {
+        self.rowid
+    }
+
+    /// The table the foreign key refers to
+    pub fn parent(&self) -> &str {
+        &self.parent
+    }
+
+    /// The id of the foreign key, see `PRAGMA foreign_key_list`
+    pub fn foreign_key_id(&self) -> i64 {
+        self.foreign_key_id
+    }
+
+    fn from_row(row: &TursoRow) -> deserialize::Result<Self> {
+        // The columns are `table`, `rowid`, `parent` and `fkid`
+        Ok(Self {
+            table: row.get_value::<Text, String, _>(0)?,
+            rowid: row.get_value::<Nullable<BigInt>, Option<i64>, _>(1)?,
+            parent: row.get_value::<Text, String, _>(2)?,
+            foreign_key_id: row.get_value::<BigInt, i64, _>(3)?,
+        })
+    }
+}
+
+/// How much a WAL checkpoint does, see
+/// [`AsyncTursoConnection::wal_checkpoint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckpointMode {
+    /// Copies as many frames as possible without waiting for readers or
+    /// writers
+    Passive,
+    /// Waits for writers, then copies all frames
+    Full,
+    /// Like `Full`, then waits for readers so the next writer starts at the
+    /// beginning of the WAL
+    Restart,
+    /// Like `Restart`, and truncates the WAL file to zero bytes
+    Truncate,
+}
+
+impl CheckpointMode {
+    /// The name of the mode as used in SQL, e.g. `TRUNCATE`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckpointMode::Passive => "PASSIVE",
+            CheckpointMode::Full => "FULL",
+            CheckpointMode::Restart => "RESTART",
+            CheckpointMode::Truncate => "TRUNCATE",
+        }
+    }
+}
+
+/// The result of `PRAGMA wal_checkpoint`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    busy: bool,
+    wal_frames: Option<u64>,
+    checkpointed_frames: Option<u64>,
+}
+
+impl Checkpoint {
+    /// Whether the checkpoint couldn't finish because of other readers or
+    /// writers
+    pub fn busy(&self) -> bool {
+        self.busy
+    }
+
+    /// The number of frames in the WAL, `None` if the database isn't in WAL
+    /// mode
+    pub fn wal_frames(&self) -> Option<u64> {
+        self.wal_frames
+    }
+
+    /// The number of frames copied into the database file, `None` if the
+    /// database isn't in WAL mode
+    pub fn checkpointed_frames(&self) -> Option<u64> {
+        self.checkpointed_frames
+    }
+
+    fn from_row(row: &TursoRow) -> deserialize::Result<Self> {
+        // The columns are `busy`, `log` and `checkpointed`, the last two are
+        // `-1` outside of WAL mode
+        let frames = |index| -> deserialize::Result<Option<u64>> {
+            Ok(u64::try_from(row.get_value::<BigInt, i64, _>(index)?).ok())
+        };
+        Ok(Self {
+            busy: row.get_value::<BigInt, i64, _>(0)? != 0,
+            wal_frames: frames(1)?,
+            checkpointed_frames: frames(2)?,
+        })
+    }
+}
+
+/// A snapshot of the storage used by a database, see
+/// [`AsyncTursoConnection::database_stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatabaseStats {
+    page_size: u32,
+    page_count: u64,
+    freelist_count: u64,
+    file_size: Option<u64>,
+    wal_size: Option<u64>,
+}
+
+impl DatabaseStats {
+    /// The size of the pages in bytes
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
+
+    /// The number of pages of the database
+    pub fn page_count(&self) -> u64 {
+        self.page_count
+    }
+
+    /// The number of unused pages, which a `VACUUM` would free
+    pub fn freelist_count(&self) -> u64 {
+        self.freelist_count
+    }
+
+    /// The size of the database file in bytes, `None` for in-memory
+    /// databases
+    ///
+    /// Pages which are only in the WAL yet aren't included.
+    pub fn file_size(&self) -> Option<u64> {
+        self.file_size
+    }
+
+    /// The size of the WAL file in bytes, `None` for in-memory databases
+    /// and databases without a WAL file
+    ///
+    /// The file only shrinks with a [`CheckpointMode::Truncate`] checkpoint,
+    /// [`AsyncTursoConnection::wal_checkpoint`] returns the number of frames
+    /// which aren't copied into the database file yet.
+    pub fn wal_size(&self) -> Option<u64> {
+        self.wal_size
+    }
+
+    /// The size of the database in bytes, the page count times the page size
+    pub fn database_size(&self) -> u64 {
+        self.page_count * u64::from(self.page_size)
+    }
+}
+
+impl AsyncTursoConnection {
+    /// The journal mode of the database
+    pub async fn journal_mode(&mut self) -> QueryResult<JournalMode> {
+        let mode = self.pragma_value::<Text, String>("journal_mode").await?;
+        JournalMode::parse(&mode).map_err(Error::DeserializationError)
+    }
+
+    /// Changes the journal mode of the database, returns the mode in effect
+    /// afterwards
+    ///
+    /// The database keeps its mode if it can't switch, e.g. in-memory
+    /// databases only support [`JournalMode::Memory`] and
+    /// [`JournalMode::Off`].
+    pub async fn set_journal_mode(&mut self, mode: JournalMode) -> QueryResult<JournalMode> {
+        let pragma = format!("journal_mode = {}", mode.as_str());
+        let mode = self.pragma_value::<Text, String>(&pragma).await?;
+        JournalMode::parse(&mode).map_err(Error::DeserializationError)
+    }
+
+    /// How often the database syncs writes to disk
+    pub async fn synchronous(&mut self) -> QueryResult<Synchronous> {
+        let level = self.pragma_value::<BigInt, i64>("synchronous").await?;
+        Synchronous::parse(level).map_err(Error::DeserializationError)
+    }
+
+    /// Changes how often the database syncs writes to disk
+    pub async fn set_synchronous(&mut self, level: Synchronous) -> QueryResult<()> {
+        self.pragma(&format!("synchronous = {}", level.as_str()))
+            .await
+            .map(|_| ())
+    }
+
+    /// Whether foreign key constraints are enforced
+    pub async fn foreign_keys(&mut self) -> QueryResult<bool> {
+        let enabled = self.pragma_value::<BigInt, i64>("foreign_keys").await?;
+        Ok(enabled != 0)
+    }
+
+    /// Enables or disables the enforcement of foreign key constraints
+    ///
+    /// This has no effect inside a transaction.
+    pub async fn set_foreign_keys(&mut self, enabled: bool) -> QueryResult<()> {
+        self.pragma(&format!(
+            "foreign_keys = {}",
+            if enabled { "ON" } else { "OFF" }
+        ))
+        .await
+        .map(|_| ())
+    }
+
+    /// The user version of the database, which the database itself doesn't
+    /// use, e.g. for the version of the schema
+    pub async fn user_version(&mut self) -> QueryResult<i32> {
+        self.pragma_value::<Integer, i32>("user_version").await
+    }
+
+    /// Changes the user version of the database
+    pub async fn set_user_version(&mut self, version: i32) -> QueryResult<()> {
+        self.pragma(&format!("user_version = {version}"))
+            .await
+            .map(|_| ())
+    }
+
+    /// The application id of the database, which identifies the file format
+    /// of applications using the database as their file format
+    pub async fn application_id(&mut self) -> QueryResult<i32> {
+        self.pragma_value::<Integer, i32>("application_id").await
+    }
+
+    /// Changes the application id of the database
+    pub async fn set_application_id(&mut self, id: i32) -> QueryResult<()> {
+        self.pragma(&format!("application_id = {id}"))
+            .await
+            .map(|_| ())
+    }
+
+    /// The suggested maximum size of the page cache of this connection
+    pub async fn cache_size(&mut self) -> QueryResult<CacheSize> {
+        let size = self.pragma_value::<BigInt, i64>("cache_size").await?;
+        Ok(CacheSize::from_pragma_value(size))
+    }
+
+    /// Changes the suggested maximum size of the page cache of this
+    /// connection
+    pub async fn set_cache_size(&mut self, size: CacheSize) -> QueryResult<()> {
+        self.pragma(&format!("cache_size = {}", size.to_pragma_value()))
+            .await
+            .map(|_| ())
+    }
+
+    /// The size of the pages of the database in bytes
+    pub async fn page_size(&mut self) -> QueryResult<u32> {
+        let size = self.pragma_value::<BigInt, i64>("page_size").await?;
+        u32::try_from(size).map_err(|e| Error::DeserializationError(e.into()))
+    }
+
+    /// Changes the size of the pages of the database in bytes, a power of
+    /// two between 512 and 65536
+    ///
+    /// This only has an effect before the database is created.
+    pub async fn set_page_size(&mut self, size: u32) -> QueryResult<()> {
+        self.pragma(&format!("page_size = {size}"))
+            .await
+            .map(|_| ())
+    }
+
+    /// Checks the whole database for corruption
+    pub async fn integrity_check(&mut self) -> QueryResult<IntegrityCheck> {
+        let problems = self
+            .pragma("integrity_check")
+            .await?
+            .iter()
+            .map(|row| row.get_value::<Text, String, _>(0))
+            .filter(|problem| !matches!(problem.as_deref(), Ok("ok")))
+            .collect::<deserialize::Result<_>>()
+            .map_err(Error::DeserializationError)?;
+        Ok(IntegrityCheck { problems })
+    }
+
+    /// The columns of the table `table`, empty if there is no such table
+    pub async fn table_info(&mut self, table: &str) -> QueryResult<Vec<ColumnInfo>> {
+        self.pragma(&format!("table_info({})", quote_identifier(table)))
+            .await?
+            .iter()
+            .map(ColumnInfo::from_row)
+            .collect::<deserialize::Result<_>>()
+            .map_err(Error::DeserializationError)
+    }
+
+    /// The indexes of the table `table`
+    pub async fn index_list(&mut self, table: &str) -> QueryResult<Vec<IndexInfo>> {
+        self.pragma(&format!("index_list({})", quote_identifier(table)))
+            .await?
+            .iter()
+            .map(IndexInfo::from_row)
+            .collect::<deserialize::Result<_>>()
+            .map_err(Error::DeserializationError)
+    }
+
+    /// The rows whose foreign keys don't match a row of their parent table,
+    /// in the table `table` or in all tables
+    pub async fn foreign_key_check(
+        &mut self,
+        table: Option<&str>,
+    ) -> QueryResult<Vec<ForeignKeyViolation>> {
+        let pragma = match table {
+            Some(table) => format!("foreign_key_check({})", quote_identifier(table)),
+            None => "foreign_key_check".to_owned(),
+        };
+        self.pragma(&pragma)
+            .await?
+            .iter()
+            .map(ForeignKeyViolation::from_row)
+            .collect::<deserialize::Result<_>>()
+            .map_err(Error::DeserializationError)
+    }
+
+    /// Copies the frames of the WAL into the database file
+    ///
+    /// Long-running services with steady writes can call this periodically,
+    /// e.g. with [`CheckpointMode::Truncate`], to keep the WAL from growing.
+    pub async fn wal_checkpoint(&mut self, mode: CheckpointMode) -> QueryResult<Checkpoint> {
+        let rows = self
+            .pragma(&format!("wal_checkpoint({})", mode.as_str()))
+            .await?;
+        let row = rows.first().ok_or_else(|| {
+            Error::DeserializationError("`PRAGMA wal_checkpoint` returned no row".into())
+        })?;
+        Checkpoint::from_row(row).map_err(Error::DeserializationError)
+    }
+
+    /// The number of WAL frames from which a commit runs a passive
+    /// checkpoint, `0` if automatic checkpoints are disabled
+    pub async fn wal_autocheckpoint(&mut self) -> QueryResult<u32> {
+        let frames = self
+            .pragma_value::<BigInt, i64>("wal_autocheckpoint")
+            .await?;
+        // Negative values disable automatic checkpoints as well
+        Ok(u32::try_from(frames).unwrap_or(0))
+    }
+
+    /// Changes the number of WAL frames from which a commit runs a passive
+    /// checkpoint, `0` disables automatic checkpoints
+    pub async fn set_wal_autocheckpoint(&mut self, frames: u32) -> QueryResult<()> {
+        self.pragma(&format!("wal_autocheckpoint = {frames}"))
+            .await
+            .map(|_| ())
+    }
+
+    /// The number of pages of the database
+    pub async fn page_count(&mut self) -> QueryResult<u64> {
+        let pages = self.pragma_value::<BigInt, i64>("page_count").await?;
+        u64::try_from(pages).map_err(|e| Error::DeserializationError(e.into()))
+    }
+
+    /// The number of unused pages of the database
+    pub async fn freelist_count(&mut self) -> QueryResult<u64> {
+        let pages = self.pragma_value::<BigInt, i64>("freelist_count").await?;
+        u64::try_from(pages).map_err(|e| Error::DeserializationError(e.into()))
+    }
+
+    /// Takes a snapshot of the storage used by the database, e.g. to export
+    /// it as metrics
+    ///
+    /// Only reads the database, the WAL isn't checkpointed.
+    pub async fn database_stats(&mut self) -> QueryResult<DatabaseStats> {
+        let page_size = self.page_size().await?;
+        let page_count = self.page_count().await?;
+        let freelist_count = self.freelist_count().await?;
+
+        let path = database_file(&self.binding.path);
+        let file_size = |path: &str| std::fs::metadata(path).ok().map(|file| file.len());
+        let wal_size = path.and_then(|path| file_size(&format!("{path}-wal")));
+        let file_size = path.and_then(file_size);
+
+        Ok(DatabaseStats {
+            page_size,
+            page_count,
+            freelist_count,
+            file_size,
+            wal_size,
+        })
+    }
+
+    /// Runs `PRAGMA {pragma}`, bypassing the instrumentation
+    ///
+    /// The rows are read like the ones of a query, pragmas which return rows
+    /// don't need the fallback of `execute`.
+    async fn pragma(&mut self, pragma: &str) -> QueryResult<Vec<TursoRow>> {
+        self.ensure_connection().await?;
+        self.query_rows(&format!("PRAGMA {pragma}"), Vec::new())
+            .await
+    }
+
+    /// The first column of the first row of `PRAGMA {pragma}`
+    async fn pragma_value<ST, T>(&mut self, pragma: &str) -> QueryResult<T>
+    where
+        T: FromSql<ST, TursoBackend>,
+    {
+        let rows = self.pragma(pragma).await?;
+        let row = rows.first().ok_or_else(|| {
+            Error::DeserializationError(format!("`PRAGMA {pragma}` returned no row").into())
+        })?;
+        row.get_value::<ST, T, _>(0)
+            .map_err(Error::DeserializationError)
+    }
+}
+
+/// The file behind the path a database was opened with, `None` for in-memory
+/// databases
+///
+/// `file:` URIs are reduced to their path, percent-encoded characters aren't
+/// decoded.
+fn database_file(path: &str) -> Option<&str> {
+    let Some(uri) = path.strip_prefix("file:") else {
+        return (!path.is_empty() && path != ":memory:").then_some(path);
+    };
+    let (path, query) = uri.split_once('?').unwrap_or((uri, ""));
+    let path = path.split_once('#').map_or(path, |(path, _)| path);
+    // `file://host/path`, the host is ignored
+    let path = match path.strip_prefix("//") {
+        Some(rest) => &rest[rest.find('/')?..],
+        None => path,
+    };
+    let in_memory = query.split('&').any(|parameter| parameter == "mode=memory");
+    (!path.is_empty() && path != ":memory:" && !in_memory).then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::database_file;
+
+    #[test]
+    fn database_files() {
+        assert_eq!(database_file("app.db"), Some("app.db"));
+        assert_eq!(database_file("/var/lib/app.db"), Some("/var/lib/app.db"));
+        assert_eq!(database_file(""), None);
+        assert_eq!(database_file(":memory:"), None);
+        assert_eq!(database_file("file:app.db?mode=rwc"), Some("app.db"));
+        assert_eq!(
+            database_file("file:///var/lib/app.db"),
+            Some("/var/lib/app.db")
+        );
+        assert_eq!(
+            database_file("file://localhost/var/lib/app.db"),
+            Some("/var/lib/app.db")
+        );
+        assert_eq!(database_file("file::memory:"), None);
+        assert_eq!(database_file("file::memory:?cache=shared"), None);
+        assert_eq!(database_file("file:app?mode=memory&cache=shared"), None);
+    }
+}
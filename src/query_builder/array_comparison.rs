@@ -0,0 +1,157 @@
+//! `IN` and `NOT IN` for lists of values
+//!
+//! Short lists are rendered with one placeholder per value, like diesel does
+//! for the other backends. Longer lists would run into the limit on the number
+//! of bind parameters, they are bound as a single JSON array instead and
+//! expanded with `json_each`.
+
+use std::fmt::{Debug, Write};
+
+use diesel::expression::array_comparison::{In, InExpression, Many, NotIn};
+use diesel::query_builder::{AstPass, QueryFragment};
+use diesel::result::QueryResult;
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::{HasSqlType, SingleValue};
+use turso::Value;
+
+use crate::backend::{TursoArrayComparison, TursoBackend, TursoType};
+use crate::value::TursoValue;
+
+/// Lists with more values than this are bound as a single JSON array
+pub(crate) const MAX_IN_LIST_PLACEHOLDERS: usize = 100;
+
+impl<T, U> QueryFragment<TursoBackend, TursoArrayComparison> for In<T, U>
+where
+    T: QueryFragment<TursoBackend>,
+    U: QueryFragment<TursoBackend> + InExpression,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        if self.values.is_empty() {
+            out.push_sql("1=0");
+        } else {
+            self.left.walk_ast(out.reborrow())?;
+            out.push_sql(" IN (");
+            self.values.walk_ast(out.reborrow())?;
+            out.push_sql(")");
+        }
+        Ok(())
+    }
+}
+
+impl<T, U> QueryFragment<TursoBackend, TursoArrayComparison> for NotIn<T, U>
+where
+    T: QueryFragment<TursoBackend>,
+    U: QueryFragment<TursoBackend> + InExpression,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        if self.values.is_empty() {
+            out.push_sql("1=1");
+        } else {
+            self.left.walk_ast(out.reborrow())?;
+            out.push_sql(" NOT IN (");
+            self.values.walk_ast(out.reborrow())?;
+            out.push_sql(")");
+        }
+        Ok(())
+    }
+}
+
+impl<ST, I> QueryFragment<TursoBackend, TursoArrayComparison> for Many<ST, I>
+where
+    ST: SingleValue + Debug,
+    I: ToSql<ST, TursoBackend>,
+    TursoBackend: HasSqlType<ST>,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
+        // Blobs have no JSON representation
+        let is_blob = <TursoBackend as HasSqlType<ST>>::metadata(&mut ()) == TursoType::Binary;
+        if self.values.len() > MAX_IN_LIST_PLACEHOLDERS && !is_blob {
+            out.push_sql("SELECT value FROM json_each(");
+            out.push_bind_param::<JsonValueList, _>(self)?;
+            out.push_sql(")");
+            return Ok(());
+        }
+
+        out.unsafe_to_cache_prepared();
+        let mut first = true;
+        for value in &self.values {
+            if first {
+                first = false;
+            } else {
+                out.push_sql(", ");
+            }
+            out.push_bind_param(value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Bind type of the values of a long `IN` list, a JSON array as text
+#[derive(Debug, Clone, Copy)]
+pub struct JsonValueList;
+
+impl HasSqlType<JsonValueList> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Text
+    }
+}
+
+// `ToSql` requires `Debug`, which `Many` only implements for `ST: Debug`
+impl<ST, I> ToSql<JsonValueList, TursoBackend> for Many<ST, I>
+where
+    ST: Debug,
+    I: ToSql<ST, TursoBackend>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        let mut json = String::from("[");
+        for (i, value) in self.values.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let mut lookup = ();
+            let mut element = Output::new(TursoValue::from_turso_value(Value::Null), &mut lookup);
+            match value.to_sql(&mut element)? {
+                IsNull::Yes => json.push_str("null"),
+                IsNull::No => write_json(&mut json, &element.into_inner())?,
+            }
+        }
+        json.push(']');
+        out.set_value(json);
+        Ok(IsNull::No)
+    }
+}
+
+fn write_json(
+    json: &mut String,
+    value: &TursoValue,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(int) = value.as_integer() {
+        write!(json, "{int}")?;
+    } else if let Some(real) = value.as_real() {
+        if real.is_nan() {
+            json.push_str("null");
+        } else if real.is_infinite() {
+            // The database reads numbers beyond the range of a REAL as infinity
+            json.push_str(if real > 0.0 { "9e999" } else { "-9e999" });
+        } else {
+            // `Debug` keeps the fraction of integral values, e.g. `1.0`
+            write!(json, "{real:?}")?;
+        }
+    } else if let Some(text) = value.as_text() {
+        json.push('"');
+        for c in text.chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                c if u32::from(c) < 0x20 => write!(json, "\\u{:04x}", u32::from(c))?,
+                c => json.push(c),
+            }
+        }
+        json.push('"');
+    } else if value.is_null() {
+        json.push_str("null");
+    } else {
+        return Err(format!("{value:?} can't be bound as part of a JSON array").into());
+    }
+    Ok(())
+}
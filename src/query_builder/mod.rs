@@ -4,14 +4,62 @@ use super::backend::TursoBackend;
 use diesel::query_builder::QueryBuilder;
 use diesel::result::QueryResult;
 
+mod array_comparison;
 mod limit_offset;
 mod returning;
 
+pub(crate) use returning::RETURNING;
+use returning::RETURNING_CLAUSE_MARKER;
+
 /// Constructs SQL queries for use with the SQLite backend
 #[allow(missing_debug_implementations)]
 #[derive(Default)]
 pub struct TursoQueryBuilder {
     pub(crate) sql: String,
+    bind_params: usize,
+    /// The nesting depth of parentheses, e.g. of subqueries
+    depth: isize,
+    /// Whether the identifiers pushed next are the name of `target`
+    reading_target: bool,
+    target: Option<WriteTarget>,
+    returning: Option<SqlPosition>,
+}
+
+/// A position in the SQL of a statement
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SqlPosition {
+    /// Byte offset in the SQL
+    pub(crate) sql: usize,
+    /// Number of bind parameters before the offset
+    pub(crate) bind_params: usize,
+}
+
+/// The table an `INSERT` or `UPDATE` statement writes to, as diesel walked
+/// the statement
+#[derive(Debug, Clone)]
+pub(crate) enum WriteTarget {
+    Insert {
+        /// The quoted, possibly schema qualified, name of the table
+        table: String,
+        /// Whether the statement has an `ON CONFLICT DO UPDATE` clause
+        upsert: bool,
+    },
+    Update {
+        /// The quoted, possibly schema qualified, name of the table
+        table: String,
+        /// Where the `WHERE` clause of the statement starts
+        filter: Option<SqlPosition>,
+    },
+}
+
+/// A statement with a `RETURNING` clause, which `AsyncTursoConnection`
+/// emulates for databases which don't support the clause
+#[derive(Debug, Clone)]
+pub(crate) struct ReturningStatement {
+    /// Where the `RETURNING` clause starts
+    pub(crate) clause: SqlPosition,
+    /// `None` for other statements than `INSERT` and `UPDATE`
+    pub(crate) target: Option<WriteTarget>,
 }
 
 impl TursoQueryBuilder {
@@ -19,21 +67,114 @@ impl TursoQueryBuilder {
     pub fn new() -> Self {
         TursoQueryBuilder::default()
     }
+
+    /// The `RETURNING` clause of the statement built, if any
+    pub(crate) fn returning(&self) -> Option<ReturningStatement> {
+        self.returning.map(|clause| ReturningStatement {
+            clause,
+            target: self.target.clone(),
+        })
+    }
+
+    fn position(&self) -> SqlPosition {
+        SqlPosition {
+            sql: self.sql.len(),
+            bind_params: self.bind_params,
+        }
+    }
+
+    /// Records the parts of the statement `RETURNING` is emulated with,
+    /// `sql` is pushed next
+    fn record_statement(&mut self, sql: &str) {
+        if self.reading_target && sql == "." {
+            if let Some(WriteTarget::Insert { table, .. } | WriteTarget::Update { table, .. }) =
+                &mut self.target
+            {
+                table.push('.');
+            }
+            return;
+        }
+        self.reading_target = false;
+        if self.depth != 0 {
+            return;
+        }
+        let position = self.position();
+        match &mut self.target {
+            // The operator is `INSERT`, `REPLACE` or `INSERT OR IGNORE`,
+            // followed by the table
+            None if sql == " INTO "
+                && (self.sql.starts_with("INSERT") || self.sql.starts_with("REPLACE")) =>
+            {
+                self.target = Some(WriteTarget::Insert {
+                    table: String::new(),
+                    upsert: false,
+                });
+                self.reading_target = true;
+            }
+            None if sql == "UPDATE " && self.sql.is_empty() => {
+                self.target = Some(WriteTarget::Update {
+                    table: String::new(),
+                    filter: None,
+                });
+                self.reading_target = true;
+            }
+            Some(WriteTarget::Insert { upsert, .. }) if sql.starts_with(" DO UPDATE") => {
+                *upsert = true;
+            }
+            Some(WriteTarget::Update { filter, .. }) if filter.is_none() && sql == " WHERE " => {
+                *filter = Some(position);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// How much `sql` changes the nesting depth of parentheses, parentheses in
+/// quotes don't count
+fn nesting(sql: &str) -> isize {
+    let mut quote = None;
+    let mut depth = 0;
+    for byte in sql.bytes() {
+        match (quote, byte) {
+            // A doubled quote closes and reopens the quote
+            (Some(q), _) if q == byte => quote = None,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"' | b'`') => quote = Some(byte),
+            (None, b'(') => depth += 1,
+            (None, b')') => depth -= 1,
+            (None, _) => {}
+        }
+    }
+    depth
 }
 
 impl QueryBuilder<TursoBackend> for TursoQueryBuilder {
     fn push_sql(&mut self, sql: &str) {
+        if sql == RETURNING_CLAUSE_MARKER {
+            self.returning = Some(self.position());
+            return;
+        }
+        self.record_statement(sql);
+        self.depth += nesting(sql);
         self.sql.push_str(sql);
     }
 
     fn push_identifier(&mut self, identifier: &str) -> QueryResult<()> {
-        self.push_sql("`");
-        self.push_sql(&identifier.replace('`', "``"));
-        self.push_sql("`");
+        // Pushed directly, identifiers don't change the nesting depth
+        let identifier = format!("`{}`", identifier.replace('`', "``"));
+        if self.reading_target {
+            if let Some(WriteTarget::Insert { table, .. } | WriteTarget::Update { table, .. }) =
+                &mut self.target
+            {
+                table.push_str(&identifier);
+            }
+        }
+        self.sql.push_str(&identifier);
         Ok(())
     }
 
     fn push_bind_param(&mut self) {
+        self.bind_params += 1;
         self.push_sql("?");
     }
 
@@ -41,3 +182,16 @@ impl QueryBuilder<TursoBackend> for TursoQueryBuilder {
         self.sql
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::nesting;
+
+    #[test]
+    fn nesting_depth() {
+        assert_eq!(nesting(" IN ("), 1);
+        assert_eq!(nesting(")"), -1);
+        assert_eq!(nesting("f(a, (b))"), 0);
+        assert_eq!(nesting("name = ')' OR name = '''('"), 0);
+    }
+}
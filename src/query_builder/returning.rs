@@ -3,13 +3,22 @@ use diesel::query_builder::ReturningClause;
 use diesel::query_builder::{AstPass, QueryFragment};
 use diesel::result::QueryResult;
 
+/// The keyword starting a `RETURNING` clause
+pub(crate) const RETURNING: &str = " RETURNING ";
+
+/// Pushed in front of a `RETURNING` clause, `AstPass` doesn't give access to
+/// the query builder, which records the position of the clause instead of
+/// writing the marker so `AsyncTursoConnection` can emulate the clause
+pub(super) const RETURNING_CLAUSE_MARKER: &str = "\0returning clause\0";
+
 impl<Expr> QueryFragment<TursoBackend, SqliteReturningClause> for ReturningClause<Expr>
 where
     Expr: QueryFragment<TursoBackend>,
 {
     fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, TursoBackend>) -> QueryResult<()> {
         out.skip_from(true);
-        out.push_sql(" RETURNING ");
+        out.push_sql(RETURNING_CLAUSE_MARKER);
+        out.push_sql(RETURNING);
         self.0.walk_ast(out.reborrow())?;
         Ok(())
     }
@@ -0,0 +1,217 @@
+//! SQL types which only exist for the Turso backend
+//!
+//! These types are stored using one of the storage classes of the database,
+//! they exist to select a specific encoding for a Rust type.
+
+use diesel::query_builder::QueryId;
+use diesel::sql_types::SqlType;
+
+/// A timestamp with a time zone offset
+///
+/// This is the equivalent of diesel's `TimestamptzSqlite`. Values are stored
+/// as RFC 3339 text, e.g. `2024-05-01T12:30:00+02:00`, and keep their offset.
+/// Note that comparisons on the column compare the text, so values with
+/// different offsets don't compare chronologically.
+///
+/// ### [`ToSql`](diesel::serialize::ToSql) impls
+///
+/// - [`chrono::DateTime<Tz>`][DateTime] with `feature = "chrono"`
+/// - [`time::OffsetDateTime`][OffsetDateTime] with `feature = "time"`
+///
+/// ### [`FromSql`](diesel::deserialize::FromSql) impls
+///
+/// - [`chrono::DateTime<Utc>`][DateTime], [`chrono::DateTime<FixedOffset>`][DateTime]
+///   and [`chrono::DateTime<Local>`][DateTime] with `feature = "chrono"`
+/// - [`time::OffsetDateTime`][OffsetDateTime] with `feature = "time"`
+///
+/// Text without an offset is read as UTC.
+///
+/// [DateTime]: https://docs.rs/chrono/0.4/chrono/struct.DateTime.html
+/// [OffsetDateTime]: https://docs.rs/time/0.3/time/struct.OffsetDateTime.html
+#[derive(Debug, Clone, Copy, Default, QueryId, SqlType)]
+pub struct Timestamptz;
+
+/// A timestamp stored as INTEGER seconds since the unix epoch
+///
+/// This is the representation produced by `unixepoch()`. Encoding drops
+/// sub-second precision.
+///
+/// ### [`ToSql`](diesel::serialize::ToSql) impls
+///
+/// - [`chrono::NaiveDateTime`][NaiveDateTime] with `feature = "chrono"`
+/// - [`time::PrimitiveDateTime`][PrimitiveDateTime] with `feature = "time"`
+///
+/// ### [`FromSql`](diesel::deserialize::FromSql) impls
+///
+/// - [`chrono::NaiveDateTime`][NaiveDateTime] with `feature = "chrono"`
+/// - [`time::PrimitiveDateTime`][PrimitiveDateTime] with `feature = "time"`
+///
+/// Decoding accepts every representation that is accepted for
+/// [`Timestamp`](diesel::sql_types::Timestamp).
+///
+/// [NaiveDateTime]: https://docs.rs/chrono/0.4/chrono/naive/struct.NaiveDateTime.html
+/// [PrimitiveDateTime]: https://docs.rs/time/0.3/time/struct.PrimitiveDateTime.html
+#[derive(Debug, Clone, Copy, Default, QueryId, SqlType)]
+pub struct TimestampUnix;
+
+/// A timestamp stored as REAL fractional julian day number
+///
+/// This is the representation produced by `julianday()`. The precision of
+/// the encoding is roughly a millisecond.
+///
+/// ### [`ToSql`](diesel::serialize::ToSql) impls
+///
+/// - [`chrono::NaiveDateTime`][NaiveDateTime] with `feature = "chrono"`
+/// - [`time::PrimitiveDateTime`][PrimitiveDateTime] with `feature = "time"`
+///
+/// ### [`FromSql`](diesel::deserialize::FromSql) impls
+///
+/// - [`chrono::NaiveDateTime`][NaiveDateTime] with `feature = "chrono"`
+/// - [`time::PrimitiveDateTime`][PrimitiveDateTime] with `feature = "time"`
+///
+/// Decoding accepts every representation that is accepted for
+/// [`Timestamp`](diesel::sql_types::Timestamp).
+///
+/// [NaiveDateTime]: https://docs.rs/chrono/0.4/chrono/naive/struct.NaiveDateTime.html
+/// [PrimitiveDateTime]: https://docs.rs/time/0.3/time/struct.PrimitiveDateTime.html
+#[derive(Debug, Clone, Copy, Default, QueryId, SqlType)]
+pub struct TimestampJulian;
+
+/// A decimal number stored as REAL
+///
+/// This is an alternative to [`Numeric`](diesel::sql_types::Numeric), which
+/// binds decimals as text. REAL values are compact and work with the math
+/// functions, but only hold 15 significant digits. Encoding a decimal with
+/// more digits fails instead of silently rounding it.
+///
+/// ### [`ToSql`](diesel::serialize::ToSql) impls
+///
+/// - [`bigdecimal::BigDecimal`][BigDecimal] with `feature = "bigdecimal"`
+/// - [`rust_decimal::Decimal`][Decimal] with `feature = "rust_decimal"`
+///
+/// ### [`FromSql`](diesel::deserialize::FromSql) impls
+///
+/// - [`bigdecimal::BigDecimal`][BigDecimal] with `feature = "bigdecimal"`
+/// - [`rust_decimal::Decimal`][Decimal] with `feature = "rust_decimal"`
+///
+/// Decoding accepts every representation that is accepted for
+/// [`Numeric`](diesel::sql_types::Numeric).
+///
+/// [BigDecimal]: https://docs.rs/bigdecimal/0.4/bigdecimal/struct.BigDecimal.html
+/// [Decimal]: https://docs.rs/rust_decimal/1/rust_decimal/struct.Decimal.html
+#[derive(Debug, Clone, Copy, Default, QueryId, SqlType)]
+pub struct NumericReal;
+
+/// A dense vector of 32 bit floats
+///
+/// Values are stored as a blob in the format of the `vector32()` function,
+/// so columns of this type can be used with the vector functions, e.g.
+/// [`cosine_distance`](crate::dsl::TursoVectorExpressionMethods::cosine_distance).
+///
+/// ### [`ToSql`](diesel::serialize::ToSql) impls
+///
+/// - [`Vec<f32>`]
+///
+/// ### [`FromSql`](diesel::deserialize::FromSql) impls
+///
+/// - [`Vec<f32>`]
+///
+/// Decoding also accepts the text form `[1.0, 2.0]` returned by
+/// `vector_extract()`.
+#[derive(Debug, Clone, Copy, Default, QueryId, SqlType)]
+pub struct Vector32;
+
+/// A dense vector of 64 bit floats
+///
+/// Values are stored as a blob in the format of the `vector64()` function.
+///
+/// ### [`ToSql`](diesel::serialize::ToSql) impls
+///
+/// - [`Vec<f64>`]
+///
+/// ### [`FromSql`](diesel::deserialize::FromSql) impls
+///
+/// - [`Vec<f64>`]
+///
+/// Decoding also accepts `vector32()` blobs and the text form returned by
+/// `vector_extract()`.
+#[derive(Debug, Clone, Copy, Default, QueryId, SqlType)]
+pub struct Vector64;
+
+/// A JSON document stored as TEXT
+///
+/// This is the type of the values of the `json*` functions. Diesel's
+/// `Json` only comes with `FromSqlRow` and `AsExpression` for
+/// `serde_json::Value` together with one of its own backends.
+///
+/// ### [`ToSql`](diesel::serialize::ToSql) impls
+///
+/// - [`serde_json::Value`][Value] with `feature = "serde_json"`
+///
+/// ### [`FromSql`](diesel::deserialize::FromSql) impls
+///
+/// - [`serde_json::Value`][Value] with `feature = "serde_json"`
+///
+/// [Value]: https://docs.rs/serde_json/1/serde_json/enum.Value.html
+#[cfg(feature = "serde_json")]
+#[derive(Debug, Clone, Copy, Default, QueryId, SqlType)]
+pub struct Json;
+
+/// A JSON document stored as a BLOB in the binary format of the `jsonb*`
+/// functions
+///
+/// ### [`ToSql`](diesel::serialize::ToSql) impls
+///
+/// - [`serde_json::Value`][Value] with `feature = "serde_json"`
+///
+/// ### [`FromSql`](diesel::deserialize::FromSql) impls
+///
+/// - [`serde_json::Value`][Value] with `feature = "serde_json"`
+///
+/// Decoding also accepts JSON text, which the `jsonb*` functions accept as
+/// well.
+///
+/// [Value]: https://docs.rs/serde_json/1/serde_json/enum.Value.html
+#[cfg(feature = "serde_json")]
+#[derive(Debug, Clone, Copy, Default, QueryId, SqlType)]
+pub struct Jsonb;
+
+/// Implements `AsExpression` for a foreign type towards one of the SQL types
+/// of this module
+///
+/// This mirrors `#[derive(AsExpression)]` with `#[diesel(foreign_derive)]`,
+/// minus the impls for `Nullable<ST>` which the orphan rules don't allow for
+/// a foreign type. `Option<T>` can be used for nullable columns instead.
+macro_rules! foreign_as_expression {
+    ($(<$($generics:ident $(: $bound:path)?),+>)? $ty:ty => $sql_type:ty) => {
+        impl<$($($generics $(: $bound)?),+)?> diesel::expression::AsExpression<$sql_type> for $ty {
+            type Expression = diesel::internal::derives::as_expression::Bound<$sql_type, Self>;
+
+            fn as_expression(self) -> Self::Expression {
+                diesel::internal::derives::as_expression::Bound::new(self)
+            }
+        }
+
+        impl<'expr, $($($generics $(: $bound)?),+)?> diesel::expression::AsExpression<$sql_type>
+            for &'expr $ty
+        {
+            type Expression = diesel::internal::derives::as_expression::Bound<$sql_type, Self>;
+
+            fn as_expression(self) -> Self::Expression {
+                diesel::internal::derives::as_expression::Bound::new(self)
+            }
+        }
+
+        impl<'expr, 'expr2, $($($generics $(: $bound)?),+)?> diesel::expression::AsExpression<$sql_type>
+            for &'expr2 &'expr $ty
+        {
+            type Expression = diesel::internal::derives::as_expression::Bound<$sql_type, Self>;
+
+            fn as_expression(self) -> Self::Expression {
+                diesel::internal::derives::as_expression::Bound::new(self)
+            }
+        }
+    };
+}
+
+pub(crate) use foreign_as_expression;
@@ -0,0 +1,118 @@
+use super::{connection, connection_without_transaction, setup, users, TestConnection, User};
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+
+fn backup_path(name: &str) -> String {
+    let path = std::env::temp_dir().join(format!("diesel-turso-{name}-{}.db", std::process::id()));
+    let path = path.to_str().unwrap().to_owned();
+    for file in [path.clone(), format!("{path}-wal")] {
+        let _ = std::fs::remove_file(file);
+    }
+    path
+}
+
+#[tokio::test]
+async fn backup_and_restore() {
+    let path = backup_path("backup_and_restore");
+    // A backup can't be written in a test transaction
+    let conn = &mut connection_without_transaction().await;
+    setup(conn).await;
+    conn.batch_execute("CREATE INDEX idx_users_name ON users (name)")
+        .await
+        .unwrap();
+    conn.batch_execute(
+        "CREATE TABLE events (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL);
+         INSERT INTO events (name) VALUES ('a'), ('b'), ('c');
+         DELETE FROM events WHERE id = 3;
+         CREATE TABLE sqlitelike (id INTEGER PRIMARY KEY);
+         INSERT INTO sqlitelike (id) VALUES (1);
+         CREATE TABLE tags (name TEXT NOT NULL);
+         INSERT INTO tags (name) VALUES ('a'), ('b'), ('c');
+         DELETE FROM tags WHERE name = 'b';
+         PRAGMA user_version = 3;",
+    )
+    .await
+    .unwrap();
+    let names = (0..2500).map(|i| users::name.eq(format!("User {i}")));
+    diesel::insert_into(users::table)
+        .values(names.collect::<Vec<_>>())
+        .execute(conn)
+        .await
+        .unwrap();
+
+    let mut reports = Vec::new();
+    conn.backup_to_with_progress(&path, |progress| reports.push(progress))
+        .await
+        .unwrap();
+    // Once before the first row, then once per batch, three of them for `users`
+    assert_eq!(reports.len(), 7);
+    assert_eq!(reports[0].copied_rows(), 0);
+    assert_eq!(reports[0].total_rows(), 2505);
+    assert!(reports.last().unwrap().is_done());
+
+    // The target has to be new
+    assert!(conn.backup_to(&path).await.is_err());
+
+    let restored = &mut TestConnection::new(":memory:").await.unwrap();
+    restored.restore_from(&path).await.unwrap();
+    let users = users::table
+        .order(users::id)
+        .load::<User>(restored)
+        .await
+        .unwrap();
+    assert_eq!(users.len(), 2500);
+    assert_eq!(users[42].id, 43);
+    assert_eq!(users[42].name, "User 42");
+    restored
+        .assert_uses_index(
+            &users::table.filter(users::name.eq("User 7")),
+            "idx_users_name",
+        )
+        .await;
+
+    // `AUTOINCREMENT` doesn't reuse the id of the deleted row
+    restored
+        .batch_execute("INSERT INTO events (name) VALUES ('d')")
+        .await
+        .unwrap();
+    let id = diesel::dsl::sql::<diesel::sql_types::BigInt>("SELECT max(id) FROM events")
+        .get_result::<i64>(restored)
+        .await
+        .unwrap();
+    assert_eq!(id, 4);
+    let count = diesel::dsl::sql::<diesel::sql_types::BigInt>("SELECT count(*) FROM sqlitelike")
+        .get_result::<i64>(restored)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(restored.user_version().await.unwrap(), 3);
+
+    // Rowids are kept without an `INTEGER PRIMARY KEY` as well
+    let rowids =
+        diesel::dsl::sql::<diesel::sql_types::BigInt>("SELECT rowid FROM tags ORDER BY rowid")
+            .load::<i64>(restored)
+            .await
+            .unwrap();
+    assert_eq!(rowids, [1, 3]);
+
+    // Restoring needs an empty database
+    assert!(restored.restore_from(&path).await.is_err());
+
+    for file in [path.clone(), format!("{path}-wal")] {
+        let _ = std::fs::remove_file(file);
+    }
+}
+
+#[tokio::test]
+async fn backup_rejects_open_transactions() {
+    let path = backup_path("backup_rejects_open_transactions");
+    // The test transaction holds uncommitted rows
+    let conn = &mut connection().await;
+    diesel::insert_into(users::table)
+        .values(users::name.eq("Uncommitted"))
+        .execute(conn)
+        .await
+        .unwrap();
+    assert!(conn.backup_to(&path).await.is_err());
+    assert!(!std::path::Path::new(&path).exists());
+}
@@ -0,0 +1,60 @@
+use super::{connection, users, TestConnection};
+use diesel::prelude::*;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+
+async fn connection_with_index() -> TestConnection {
+    let mut conn = connection().await;
+    conn.batch_execute("CREATE INDEX idx_users_name ON users (name)")
+        .await
+        .unwrap();
+    conn
+}
+
+#[tokio::test]
+async fn explain_query_plan() {
+    let conn = &mut connection_with_index().await;
+
+    let by_name = users::table.filter(users::name.eq("Jane"));
+    let plan = conn.explain_query_plan(&by_name).await.unwrap();
+    assert!(!plan.nodes().is_empty());
+    assert!(plan.uses_index("idx_users_name"), "{plan}");
+    assert_eq!(plan.full_scans().count(), 0, "{plan}");
+    assert!(plan.to_string().starts_with("QUERY PLAN\n"));
+    conn.assert_uses_index(&by_name, "idx_users_name").await;
+
+    let all = users::table.select(users::id);
+    let plan = conn.explain_query_plan(&all).await.unwrap();
+    assert!(!plan.uses_index("idx_users_name"), "{plan}");
+}
+
+#[tokio::test]
+#[should_panic(expected = "to use the index `idx_users_name`")]
+async fn assert_uses_index_fails_without_index() {
+    let conn = &mut connection_with_index().await;
+
+    conn.assert_uses_index(&users::table.filter(users::id.gt(1)), "idx_users_name")
+        .await;
+}
+
+#[tokio::test]
+async fn explain() {
+    let conn = &mut connection().await;
+
+    let program = conn
+        .explain(&users::table.filter(users::name.eq("Jane")))
+        .await
+        .unwrap();
+    assert_eq!(program[0].addr(), 0);
+    assert!(program
+        .iter()
+        .any(|instruction| instruction.opcode() == "Halt"));
+
+    // Explaining a statement doesn't run it
+    let program = conn
+        .explain(&diesel::insert_into(users::table).values(users::name.eq("Jane")))
+        .await
+        .unwrap();
+    assert!(!program.is_empty());
+    let count = users::table.count().get_result::<i64>(conn).await.unwrap();
+    assert_eq!(count, 0);
+}
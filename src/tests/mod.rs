@@ -6,6 +6,8 @@ use diesel_async::*;
 use scoped_futures::ScopedFutureExt;
 use std::fmt::Debug;
 
+mod backup;
+mod explain;
 #[cfg(any(
     feature = "bb8",
     feature = "deadpool",
@@ -13,6 +15,15 @@ use std::fmt::Debug;
     feature = "r2d2"
 ))]
 mod pooling;
+mod pragma;
+#[cfg(feature = "metrics")]
+mod query_metrics;
+#[cfg(feature = "tracing")]
+mod query_spans;
+mod slow_query;
+mod timeout;
+#[cfg(feature = "derive")]
+mod turso_enum;
 mod turso_unit_tests;
 mod type_check;
 
@@ -805,6 +816,116 @@ async fn test_batch_operations() -> QueryResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_debug_query_for_batch_insert() {
+    let new_users = vec![
+        NewUser {
+            name: "John".into(),
+        },
+        NewUser {
+            name: "Jane".into(),
+        },
+    ];
+    let query = diesel::insert_into(users::table).values(&new_users);
+    assert_eq!(
+        crate::debug_query(&query).to_string(),
+        "INSERT INTO `users` (`name`) VALUES (?), (?) -- binds: [\"John\", \"Jane\"]"
+    );
+
+    // Records containing default values are inserted one statement at a time
+    let new_comments = vec![
+        NewComment {
+            post_id: 1,
+            user_id: 1,
+            content: "first",
+            rating: Some(5),
+        },
+        NewComment {
+            post_id: 1,
+            user_id: 2,
+            content: "second",
+            rating: None,
+        },
+    ];
+    let query = diesel::insert_into(comments::table).values(&new_comments);
+    let sql = crate::debug_query(&query).to_string();
+    let statements = sql.lines().collect::<Vec<_>>();
+    assert_eq!(statements.len(), 2, "unexpected statements: {sql}");
+    assert!(statements[0].starts_with("INSERT INTO `comments` ("));
+    assert!(statements[0].contains("`rating`"));
+    assert!(statements[0].ends_with("-- binds: [1, 1, \"first\", 5]"));
+    assert!(statements[1].starts_with("INSERT INTO `comments` ("));
+    assert!(!statements[1].contains("`rating`"));
+    assert!(statements[1].ends_with("-- binds: [1, 2, \"second\"]"));
+
+    let debug = format!("{:?}", crate::debug_query(&query));
+    assert!(!debug.contains("Turso batch insert query"));
+    assert_eq!(debug.matches("INSERT INTO").count(), 2);
+}
+
+#[tokio::test]
+async fn test_debug_query_matches_executed_batch_insert() -> QueryResult<()> {
+    let conn = &mut connection().await;
+    conn.batch_execute(
+        "INSERT INTO users (id, name) VALUES (1, 'John'), (2, 'Jane');
+         INSERT INTO posts (id, title, body, user_id, created_at)
+         VALUES (1, 'Title', 'Body', 1, '2024-01-01 00:00:00');",
+    )
+    .await?;
+
+    // Records every statement the connection receives
+    let executed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    conn.set_slow_query_log(crate::SlowQueryLog::new(std::time::Duration::ZERO, {
+        let executed = executed.clone();
+        move |query| executed.lock().unwrap().push(query.sql().to_owned())
+    }));
+    let mut rendered = Vec::new();
+    let statements = |debug: String| {
+        debug
+            .lines()
+            .map(|line| line.split(" -- binds: ").next().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    let new_users = vec![
+        NewUser {
+            name: "Dave".into(),
+        },
+        NewUser { name: "Eve".into() },
+    ];
+    let query = diesel::insert_into(users::table).values(&new_users);
+    rendered.extend(statements(crate::debug_query(&query).to_string()));
+    assert_eq!(query.execute(conn).await?, 2);
+
+    let new_comments = vec![
+        NewComment {
+            post_id: 1,
+            user_id: 1,
+            content: "first",
+            rating: Some(5),
+        },
+        NewComment {
+            post_id: 1,
+            user_id: 2,
+            content: "second",
+            rating: None,
+        },
+    ];
+    let query = diesel::insert_into(comments::table).values(&new_comments);
+    rendered.extend(statements(crate::debug_query(&query).to_string()));
+    assert_eq!(query.execute(conn).await?, 2);
+
+    assert_eq!(rendered.len(), 3, "{rendered:?}");
+    assert_eq!(rendered, *executed.lock().unwrap());
+    let ratings = comments::table
+        .select(comments::rating)
+        .order(comments::id)
+        .load::<Option<i32>>(conn)
+        .await?;
+    assert_eq!(ratings, vec![Some(5), None]);
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_nullable_fields() -> QueryResult<()> {
     let conn = &mut connection().await;
@@ -960,3 +1081,378 @@ async fn test_distinct_and_grouping() -> QueryResult<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_turso_expression_methods() -> QueryResult<()> {
+    use crate::dsl::{Collation, TursoExpressionMethods};
+    use diesel::expression_methods::EscapeExpressionMethods;
+
+    let conn = &mut connection().await;
+
+    for (name, desc) in &[
+        ("Rust", Some("100% safe")),
+        ("rusty", Some("1000 safe")),
+        ("Go", None),
+    ] {
+        diesel::insert_into(categories::table)
+            .values((categories::name.eq(name), categories::description.eq(*desc)))
+            .execute(conn)
+            .await?;
+    }
+
+    let without_desc = categories::table
+        .select(categories::name)
+        .filter(categories::description.is(None::<String>))
+        .load::<String>(conn)
+        .await?;
+    assert_eq!(without_desc, vec!["Go"]);
+
+    let not_percent = categories::table
+        .select(categories::name)
+        .filter(categories::description.is_not("100% safe"))
+        .order_by(categories::id)
+        .load::<String>(conn)
+        .await?;
+    assert_eq!(not_percent, vec!["rusty", "Go"]);
+
+    let null_is_null = categories::table
+        .select(categories::description.is(categories::description))
+        .load::<bool>(conn)
+        .await?;
+    assert_eq!(null_is_null, vec![true, true, true]);
+
+    let glob = categories::table
+        .select(categories::name)
+        .filter(categories::name.glob("R*"))
+        .load::<String>(conn)
+        .await?;
+    assert_eq!(glob, vec!["Rust"]);
+
+    let no_case = categories::table
+        .select(categories::name)
+        .filter(categories::name.collate(Collation::NoCase).eq("RUST"))
+        .load::<String>(conn)
+        .await?;
+    assert_eq!(no_case, vec!["Rust"]);
+
+    let ordered = categories::table
+        .select(categories::name)
+        .order_by(categories::name.collate(Collation::NoCase))
+        .load::<String>(conn)
+        .await?;
+    assert_eq!(ordered, vec!["Go", "Rust", "rusty"]);
+
+    let escaped = categories::table
+        .select(categories::name)
+        .filter(categories::description.like("100!%%").escape('!'))
+        .load::<String>(conn)
+        .await?;
+    assert_eq!(escaped, vec!["Rust"]);
+
+    let query = categories::table.select(categories::id).filter(
+        categories::name
+            .regexp("^R")
+            .or(categories::name.collate(Collation::RTrim).eq("Go")),
+    );
+    assert_eq!(
+        diesel::debug_query::<TursoBackend, _>(&query).to_string(),
+        "SELECT `categories`.`id` FROM `categories` WHERE (`categories`.`name` REGEXP ? \
+         OR (`categories`.`name` COLLATE RTRIM = ?)) -- binds: [\"^R\", \"Go\"]"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_large_in_lists() -> QueryResult<()> {
+    let conn = &mut connection().await;
+
+    let names = (0..50)
+        .map(|i| format!("User \"{i}\"\\"))
+        .collect::<Vec<_>>();
+    for name in &names {
+        diesel::insert_into(users::table)
+            .values(users::name.eq(name))
+            .execute(conn)
+            .await?;
+    }
+
+    // Far more values than bind parameters are allowed in one statement
+    let ids = (1..=50_000).collect::<Vec<i32>>();
+    let count = users::table
+        .filter(users::id.eq_any(&ids))
+        .count()
+        .get_result::<i64>(conn)
+        .await?;
+    assert_eq!(count, 50);
+
+    let count = users::table
+        .filter(users::id.ne_all(ids.iter().skip(10)))
+        .count()
+        .get_result::<i64>(conn)
+        .await?;
+    assert_eq!(count, 10);
+
+    let mut wanted = names.iter().step_by(5).cloned().collect::<Vec<_>>();
+    wanted.extend((0..1_000).map(|i| format!("missing {i}")));
+    let found = users::table
+        .select(users::name)
+        .filter(users::name.eq_any(&wanted))
+        .order_by(users::id)
+        .load::<String>(conn)
+        .await?;
+    assert_eq!(found, names.iter().step_by(5).cloned().collect::<Vec<_>>());
+
+    let small = users::table
+        .select(users::id)
+        .filter(users::id.eq_any(vec![1, 2, 3]));
+    assert_eq!(
+        diesel::debug_query::<TursoBackend, _>(&small).to_string(),
+        "SELECT `users`.`id` FROM `users` WHERE (`users`.`id` IN (?, ?, ?)) -- binds: [1, 2, 3]"
+    );
+
+    let large = users::table
+        .select(users::id)
+        .filter(users::id.eq_any(1..=101));
+    let sql = diesel::debug_query::<TursoBackend, _>(&large).to_string();
+    assert!(
+        sql.starts_with(
+            "SELECT `users`.`id` FROM `users` WHERE \
+             (`users`.`id` IN (SELECT value FROM json_each(?))) -- binds: "
+        ),
+        "{sql}"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_result_and_save_changes() -> QueryResult<()> {
+    use crate::query_builder::{ReturningStatement, TursoQueryBuilder, WriteTarget};
+    use diesel::query_builder::QueryFragment;
+    use diesel::NullableExpressionMethods;
+
+    let conn = &mut connection().await;
+
+    let john = diesel::insert_into(users::table)
+        .values(users::name.eq("John"))
+        .get_result::<User>(conn)
+        .await?;
+    assert_eq!(john.name, "John");
+
+    let renamed = diesel::update(users::table.find(john.id))
+        .set(users::name.eq("Johnny"))
+        .get_result::<User>(conn)
+        .await?;
+    assert_eq!(
+        renamed,
+        User {
+            id: john.id,
+            name: "Johnny".into()
+        }
+    );
+
+    let mut user = renamed;
+    user.name = "Jane".into();
+    let saved: User = user.save_changes(conn).await?;
+    assert_eq!(saved, user);
+
+    let query = diesel::insert_into(users::table)
+        .values(users::name.eq("Dave"))
+        .returning(users::id);
+    assert_eq!(
+        diesel::debug_query::<TursoBackend, _>(&query).to_string(),
+        "INSERT INTO `users` (`name`) VALUES (?) RETURNING `id` -- binds: [\"Dave\"]"
+    );
+
+    // Emulated with an insert followed by a lookup of the inserted row
+    conn.supports_returning = false;
+    let eve = diesel::insert_into(users::table)
+        .values(users::name.eq("Eve"))
+        .get_result::<User>(conn)
+        .await?;
+    assert_eq!(eve.name, "Eve");
+    assert_ne!(eve.id, john.id);
+
+    let id = diesel::insert_into(users::table)
+        .values(users::name.eq("Frank"))
+        .returning(users::id)
+        .get_result::<i32>(conn)
+        .await?;
+    assert_eq!(
+        users::table
+            .find(id)
+            .select(users::name)
+            .first::<String>(conn)
+            .await?,
+        "Frank"
+    );
+
+    let mut user = eve;
+    user.name = "Eva".into();
+    let saved: User = user.save_changes(conn).await?;
+    assert_eq!(saved, user);
+
+    // Emulated with a lookup of the rows matched before the update
+    let renamed = diesel::update(users::table.find(user.id))
+        .set(users::name.eq("Evelyn"))
+        .get_result::<User>(conn)
+        .await?;
+    assert_eq!(
+        renamed,
+        User {
+            id: user.id,
+            name: "Evelyn".into()
+        }
+    );
+    let renamed = diesel::update(users::table.filter(users::name.like("Fr%")))
+        .set(users::name.eq("Fred"))
+        .get_results::<User>(conn)
+        .await?;
+    assert_eq!(
+        renamed,
+        vec![User {
+            id,
+            name: "Fred".into()
+        }]
+    );
+    let none = diesel::update(users::table.filter(users::name.eq("Nobody")))
+        .set(users::name.eq("Somebody"))
+        .get_results::<User>(conn)
+        .await?;
+    assert!(none.is_empty());
+
+    // The last inserted rowid doesn't point to a row updated by an upsert
+    let upsert = diesel::insert_into(users::table)
+        .values((users::id.eq(id), users::name.eq("Frank")))
+        .on_conflict(users::id)
+        .do_update()
+        .set(users::name.eq("Frank"))
+        .get_result::<User>(conn)
+        .await;
+    assert!(
+        matches!(upsert, Err(diesel::result::Error::QueryBuilderError(_))),
+        "{upsert:?}"
+    );
+
+    // The query builder records the target of the statement while diesel
+    // walks it
+    fn returning(query: &dyn QueryFragment<TestBackend>) -> ReturningStatement {
+        let mut query_builder = TursoQueryBuilder::default();
+        query.to_sql(&mut query_builder, &TursoBackend).unwrap();
+        query_builder.returning().unwrap()
+    }
+    diesel::table! {
+        #[sql_name = "we`ird"]
+        main.weird (id) {
+            id -> Integer,
+        }
+    }
+    let insert = returning(
+        &diesel::insert_into(weird::table)
+            .values(weird::id.eq(1))
+            .returning(weird::id),
+    );
+    let Some(WriteTarget::Insert { table, upsert }) = &insert.target else {
+        panic!("{insert:?}");
+    };
+    assert_eq!(table, "`main`.`we``ird`");
+    assert!(!upsert);
+    let upsert = returning(
+        &diesel::insert_into(users::table)
+            .values((users::id.eq(1), users::name.eq("Frank")))
+            .on_conflict(users::id)
+            .do_update()
+            .set(users::name.eq("Frank"))
+            .returning(users::id),
+    );
+    assert!(
+        matches!(
+            upsert.target,
+            Some(WriteTarget::Insert { upsert: true, .. })
+        ),
+        "{upsert:?}"
+    );
+    // The `WHERE` of the subquery isn't the filter of the update
+    let title = posts::table
+        .select(posts::title)
+        .filter(posts::id.eq(1))
+        .single_value()
+        .assume_not_null();
+    let query = diesel::update(users::table.filter(users::name.eq("Frank")))
+        .set(users::name.eq(title))
+        .returning(users::id);
+    let sql = diesel::debug_query::<TestBackend, _>(&query).to_string();
+    let update = returning(&query);
+    let Some(WriteTarget::Update {
+        table,
+        filter: Some(filter),
+    }) = &update.target
+    else {
+        panic!("{update:?}");
+    };
+    assert_eq!(table, "`users`");
+    assert!(
+        sql[filter.sql..].starts_with(" WHERE `users`.`name` = ? RETURNING "),
+        "{sql}"
+    );
+    assert_eq!(filter.bind_params, 2);
+    assert_eq!(update.clause.bind_params, 3);
+
+    let error = |message: &str| {
+        diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::Unknown,
+            Box::new(super::TursoError {
+                message: message.into(),
+            }),
+        )
+    };
+    assert!(super::is_unsupported_returning(&error(
+        "near \"RETURNING\": syntax error"
+    )));
+    assert!(!super::is_unsupported_returning(&error(
+        "no such column: RETURNING_count"
+    )));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execute_with_meta() -> QueryResult<()> {
+    let conn = &mut connection().await;
+    let total_before = conn.total_changes().await?;
+
+    let result = conn
+        .execute_with_meta(diesel::insert_into(users::table).values(users::name.eq("John")))
+        .await?;
+    assert_eq!(result.changes(), 1);
+    assert_eq!(result.last_insert_rowid(), conn.last_insert_rowid().await?);
+    let john = users::table
+        .find(result.last_insert_rowid() as i32)
+        .first::<User>(conn)
+        .await?;
+    assert_eq!(john.name, "John");
+
+    let result = conn
+        .execute_with_meta(
+            diesel::insert_into(users::table)
+                .values(&vec![users::name.eq("Jane"), users::name.eq("Dave")]),
+        )
+        .await?;
+    assert_eq!(result.changes(), 2);
+    assert_eq!(
+        users::table
+            .select(users::name)
+            .find(result.last_insert_rowid() as i32)
+            .first::<String>(conn)
+            .await?,
+        "Dave"
+    );
+
+    let result = conn
+        .execute_with_meta(diesel::update(users::table).set(users::name.eq("Anonymous")))
+        .await?;
+    assert_eq!(result.changes(), 3);
+    assert_eq!(conn.changes().await?, 3);
+    assert_eq!(conn.total_changes().await?, total_before + 6);
+    Ok(())
+}
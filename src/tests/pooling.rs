@@ -1,8 +1,6 @@
 use super::{users, User};
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
-//#[cfg(not(feature = "sqlite"))]
-//use diesel_async::SaveChangesDsl;
+use diesel_async::{RunQueryDsl, SaveChangesDsl};
 
 #[tokio::test]
 #[cfg(feature = "bb8")]
@@ -28,14 +26,11 @@ async fn save_changes_bb8() {
     let u = users::table.first::<User>(&mut conn).await.unwrap();
     assert_eq!(u.name, "John");
 
-    // #[cfg(not(feature = "sqlite"))]
-    // {
-    //     let mut u = u;
-    //     u.name = "Jane".into();
-    //     let u2: User = u.save_changes(&mut conn).await.unwrap();
+    let mut u = u;
+    u.name = "Jane".into();
+    let u2: User = u.save_changes(&mut conn).await.unwrap();
 
-    //     assert_eq!(u2.name, "Jane");
-    // }
+    assert_eq!(u2.name, "Jane");
 }
 
 #[tokio::test]
@@ -62,14 +57,11 @@ async fn save_changes_deadpool() {
     let u = users::table.first::<User>(&mut conn).await.unwrap();
     assert_eq!(u.name, "John");
 
-    // #[cfg(not(feature = "sqlite"))]
-    // {
-    //     let mut u = u;
-    //     u.name = "Jane".into();
-    //     let u2: User = u.save_changes(&mut conn).await.unwrap();
+    let mut u = u;
+    u.name = "Jane".into();
+    let u2: User = u.save_changes(&mut conn).await.unwrap();
 
-    //     assert_eq!(u2.name, "Jane");
-    // }
+    assert_eq!(u2.name, "Jane");
 }
 
 #[tokio::test]
@@ -96,14 +88,11 @@ async fn save_changes_mobc() {
     let u = users::table.first::<User>(&mut conn).await.unwrap();
     assert_eq!(u.name, "John");
 
-    // #[cfg(not(feature = "sqlite"))]
-    // {
-    //     let mut u = u;
-    //     u.name = "Jane".into();
-    //     let u2: User = u.save_changes(&mut conn).await.unwrap();
+    let mut u = u;
+    u.name = "Jane".into();
+    let u2: User = u.save_changes(&mut conn).await.unwrap();
 
-    //     assert_eq!(u2.name, "Jane");
-    // }
+    assert_eq!(u2.name, "Jane");
 }
 
 #[tokio::test]
@@ -132,12 +121,62 @@ async fn save_changes_r2d2() {
     let u = users::table.first::<User>(&mut conn).await.unwrap();
     assert_eq!(u.name, "John");
 
-    // #[cfg(not(feature = "sqlite"))]
-    // {
-    //     let mut u = u;
-    //     u.name = "Jane".into();
-    //     let u2: User = u.save_changes(&mut conn).await.unwrap();
+    let mut u = u;
+    u.name = "Jane".into();
+    let u2: User = u.save_changes(&mut conn).await.unwrap();
 
-    //     assert_eq!(u2.name, "Jane");
-    // }
+    assert_eq!(u2.name, "Jane");
+}
+
+#[tokio::test]
+async fn ping_rolls_back_open_transactions() {
+    use diesel_async::pooled_connection::{PoolableConnection, RecyclingMethod};
+    use diesel_async::{AnsiTransactionManager, SimpleAsyncConnection, TransactionManager};
+
+    let mut conn = super::connection_without_transaction().await;
+    super::setup(&mut conn).await;
+    assert!(!conn.is_broken());
+    conn.ping(&RecyclingMethod::Verified).await.unwrap();
+
+    AnsiTransactionManager::begin_transaction(&mut conn)
+        .await
+        .unwrap();
+    diesel::insert_into(users::table)
+        .values(users::name.eq("John"))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    assert!(conn.is_broken());
+    conn.ping(&RecyclingMethod::Verified).await.unwrap();
+    assert!(!conn.is_broken());
+    let count = users::table.count().get_result::<i64>(&mut conn).await;
+    assert_eq!(count.unwrap(), 0);
+
+    // A transaction the transaction manager doesn't know about
+    conn.batch_execute("BEGIN").await.unwrap();
+    assert!(conn.is_broken());
+    conn.ping(&RecyclingMethod::Fast).await.unwrap();
+    assert!(!conn.is_broken());
+    assert!(!conn.has_fatal_error());
+
+    // A test transaction is kept
+    let mut conn = super::connection().await;
+    assert!(!conn.is_broken());
+    conn.ping(&RecyclingMethod::Fast).await.unwrap();
+    assert!(!conn.is_broken());
+}
+
+#[tokio::test]
+async fn statement_errors_are_not_fatal() {
+    use diesel::sql_types::Text;
+    use diesel_async::pooled_connection::{PoolableConnection, RecyclingMethod};
+
+    let mut conn = super::connection_without_transaction().await;
+    let malformed = diesel::select(diesel::dsl::sql::<Text>("json('{\"a\": ')"))
+        .get_result::<String>(&mut conn)
+        .await;
+    assert!(malformed.is_err());
+    assert!(!conn.has_fatal_error());
+    assert!(!conn.is_broken());
+    conn.ping(&RecyclingMethod::Verified).await.unwrap();
 }
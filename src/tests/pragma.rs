@@ -0,0 +1,113 @@
+use super::{connection, connection_without_transaction, TestConnection};
+use crate::pragma::{CacheSize, CheckpointMode, IndexOrigin, JournalMode, Synchronous};
+use diesel_async::SimpleAsyncConnection;
+
+#[tokio::test]
+async fn settings() {
+    let conn = &mut connection_without_transaction().await;
+
+    conn.set_user_version(7).await.unwrap();
+    assert_eq!(conn.user_version().await.unwrap(), 7);
+    conn.set_application_id(0x7475_7273).await.unwrap();
+    assert_eq!(conn.application_id().await.unwrap(), 0x7475_7273);
+
+    conn.set_cache_size(CacheSize::Kibibytes(4096))
+        .await
+        .unwrap();
+    assert_eq!(conn.cache_size().await.unwrap(), CacheSize::Kibibytes(4096));
+    conn.set_synchronous(Synchronous::Full).await.unwrap();
+    assert_eq!(conn.synchronous().await.unwrap(), Synchronous::Full);
+
+    conn.set_foreign_keys(true).await.unwrap();
+    assert!(conn.foreign_keys().await.unwrap());
+    conn.set_foreign_keys(false).await.unwrap();
+    assert!(!conn.foreign_keys().await.unwrap());
+
+    assert!(conn.page_size().await.unwrap().is_power_of_two());
+    conn.journal_mode().await.unwrap();
+}
+
+#[tokio::test]
+async fn schema_and_checks() {
+    let conn = &mut connection().await;
+
+    let columns = conn.table_info("users").await.unwrap();
+    assert_eq!(columns.len(), 2);
+    assert_eq!(columns[0].name(), "id");
+    assert_eq!(columns[0].type_name(), "INTEGER");
+    assert_eq!(columns[0].primary_key(), 1);
+    assert_eq!(columns[1].name(), "name");
+    assert!(columns[1].not_null());
+    assert_eq!(columns[1].default_value(), None);
+    assert!(conn.table_info("missing").await.unwrap().is_empty());
+
+    conn.batch_execute("CREATE INDEX idx_users_name ON users (name)")
+        .await
+        .unwrap();
+    let indexes = conn.index_list("users").await.unwrap();
+    assert_eq!(indexes.len(), 1);
+    assert_eq!(indexes[0].name(), "idx_users_name");
+    assert_eq!(indexes[0].origin(), IndexOrigin::CreateIndex);
+    assert!(!indexes[0].unique());
+    let indexes = conn.index_list("post_categories").await.unwrap();
+    assert_eq!(indexes[0].origin(), IndexOrigin::PrimaryKey);
+
+    assert!(conn.foreign_key_check(None).await.unwrap().is_empty());
+    conn.batch_execute(
+        "INSERT INTO posts (id, title, body, user_id, created_at) \
+         VALUES (1, 'Title', 'Body', 99, '2024-01-01 00:00:00')",
+    )
+    .await
+    .unwrap();
+    let violations = conn.foreign_key_check(Some("posts")).await.unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].table(), "posts");
+    assert_eq!(violations[0].rowid(), Some(1));
+    assert_eq!(violations[0].parent(), "users");
+
+    assert!(conn.integrity_check().await.unwrap().is_ok());
+}
+
+#[tokio::test]
+async fn wal_checkpoints_and_stats() {
+    let path = std::env::temp_dir().join(format!("diesel-turso-wal-{}.db", std::process::id()));
+    let path = path.to_str().unwrap().to_owned();
+    for file in [path.clone(), format!("{path}-wal")] {
+        let _ = std::fs::remove_file(file);
+    }
+    let conn = &mut TestConnection::new(&path).await.unwrap();
+    assert_eq!(
+        conn.set_journal_mode(JournalMode::Wal).await.unwrap(),
+        JournalMode::Wal
+    );
+    conn.set_wal_autocheckpoint(0).await.unwrap();
+    assert_eq!(conn.wal_autocheckpoint().await.unwrap(), 0);
+
+    conn.batch_execute(
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, data TEXT NOT NULL);
+         INSERT INTO items (data) VALUES ('a'), ('b'), ('c');",
+    )
+    .await
+    .unwrap();
+    let stats = conn.database_stats().await.unwrap();
+    assert!(stats.page_count() >= 2);
+    assert_eq!(
+        stats.database_size(),
+        stats.page_count() * u64::from(stats.page_size())
+    );
+    assert!(stats.wal_size().unwrap() > 0);
+    assert!(stats.file_size().is_some());
+
+    let checkpoint = conn.wal_checkpoint(CheckpointMode::Truncate).await.unwrap();
+    assert!(!checkpoint.busy());
+    // Taking the stats didn't checkpoint the WAL
+    assert!(checkpoint.wal_frames().unwrap() > 0);
+    assert_eq!(checkpoint.wal_frames(), checkpoint.checkpointed_frames());
+    let stats = conn.database_stats().await.unwrap();
+    assert_eq!(stats.wal_size(), Some(0));
+    assert_eq!(stats.file_size(), Some(stats.database_size()));
+
+    for file in [path.clone(), format!("{path}-wal")] {
+        let _ = std::fs::remove_file(file);
+    }
+}
@@ -0,0 +1,96 @@
+use super::{connection, users};
+use crate::metrics::{QueryMetrics, TransactionOutcome};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+
+#[tokio::test]
+async fn metrics_for_statements_and_transactions() {
+    let conn = &mut connection().await;
+    let metrics = QueryMetrics::new();
+    conn.set_query_metrics(metrics.clone());
+
+    for name in ["John", "Jane", "Jim"] {
+        diesel::insert_into(users::table)
+            .values(users::name.eq(name))
+            .execute(conn)
+            .await
+            .unwrap();
+    }
+    let names = users::table
+        .select(users::name)
+        .filter(users::name.ne("Jane"))
+        .order(users::name)
+        .load::<String>(conn)
+        .await
+        .unwrap();
+    assert_eq!(names, vec!["Jim", "John"]);
+    let error = diesel::sql_query("SELECT * FROM missing_table WHERE id = 42")
+        .execute(conn)
+        .await;
+    assert!(error.is_err());
+
+    let _ = conn
+        .transaction::<(), diesel::result::Error, _>(|conn| {
+            async move {
+                diesel::delete(users::table).execute(conn).await?;
+                Err(diesel::result::Error::RollbackTransaction)
+            }
+            .scope_boxed()
+        })
+        .await;
+
+    let snapshot = metrics.snapshot();
+    // Statements which only differ in bind values or literals are grouped
+    let insert = snapshot
+        .statements()
+        .iter()
+        .find(|stats| stats.sql() == "INSERT INTO `users` (`name`) VALUES (?)")
+        .unwrap();
+    assert_eq!(insert.calls(), 3);
+    assert_eq!(insert.rows(), 3);
+    assert_eq!(insert.errors(), 0);
+    assert_eq!(insert.latency().count(), 3);
+    assert_eq!(snapshot.statement(insert.fingerprint()), Some(insert));
+
+    let select = snapshot
+        .statements()
+        .iter()
+        .find(|stats| stats.sql().starts_with("SELECT `users`.`name`"))
+        .unwrap();
+    assert_eq!(select.calls(), 1);
+    assert_eq!(select.rows(), 2);
+
+    let failed = snapshot
+        .statements()
+        .iter()
+        .find(|stats| stats.sql() == "SELECT * FROM missing_table WHERE id = ?")
+        .unwrap();
+    assert_eq!(failed.errors(), 1);
+    assert_eq!(snapshot.errors().get("Unknown"), Some(&1));
+
+    let rollbacks = snapshot.transactions(TransactionOutcome::Rollback).unwrap();
+    assert_eq!(rollbacks.count(), 1);
+    assert!(snapshot.transactions(TransactionOutcome::Commit).is_none());
+
+    let slowest = snapshot.slowest(2);
+    assert_eq!(slowest.len(), 2);
+    assert!(slowest[0].latency().mean() >= slowest[1].latency().mean());
+    assert!(snapshot
+        .statements()
+        .iter()
+        .all(|stats| stats.latency().mean() <= slowest[0].latency().mean()));
+
+    metrics.reset();
+    assert!(metrics.snapshot().statements().is_empty());
+    // Every connection records its own metrics unless a collector is set
+    assert!(
+        connection()
+            .await
+            .query_metrics()
+            .snapshot()
+            .statements()
+            .len()
+            > 0
+    );
+}
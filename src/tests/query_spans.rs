@@ -0,0 +1,140 @@
+use super::{connection, users};
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+#[derive(Debug, Clone)]
+struct RecordedSpan {
+    name: &'static str,
+    fields: BTreeMap<&'static str, String>,
+}
+
+/// Records the fields of all spans
+#[derive(Default)]
+struct Recorder {
+    spans: Mutex<Vec<RecordedSpan>>,
+}
+
+struct FieldVisitor<'a>(&'a mut BTreeMap<&'static str, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_owned());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name(), format!("{value:?}"));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attributes: &Attributes<'_>) -> Id {
+        let mut spans = self.spans.lock().unwrap();
+        let mut fields = BTreeMap::new();
+        attributes.record(&mut FieldVisitor(&mut fields));
+        spans.push(RecordedSpan {
+            name: attributes.metadata().name(),
+            fields,
+        });
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let span = &mut spans[span.into_u64() as usize - 1];
+        values.record(&mut FieldVisitor(&mut span.fields));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+impl Recorder {
+    fn find(&self, name: &str, sql: &str) -> RecordedSpan {
+        let spans = self.spans.lock().unwrap();
+        spans
+            .iter()
+            .rev()
+            .find(|span| {
+                span.name == name
+                    && span.fields.get("db.query.text").map(String::as_str) == Some(sql)
+            })
+            .cloned()
+            .unwrap_or_else(|| panic!("no `{name}` span for `{sql}` in {spans:#?}"))
+    }
+}
+
+#[tokio::test]
+async fn spans_for_statements_and_transactions() {
+    let recorder = Arc::new(Recorder::default());
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let conn = &mut connection().await;
+
+    diesel::insert_into(users::table)
+        .values(users::name.eq("John"))
+        .execute(conn)
+        .await
+        .unwrap();
+    let insert = recorder.find("execute", "INSERT INTO `users` (`name`) VALUES (?)");
+    assert_eq!(insert.fields["db.system.name"], "sqlite");
+    assert_eq!(insert.fields["db.operation.name"], "INSERT");
+    assert_eq!(insert.fields["db.query.parameter_count"], "1");
+    assert_eq!(insert.fields["db.response.affected_rows"], "1");
+    // Durations are metrics, spans carry their own timing
+    assert!(!insert.fields.contains_key("db.client.operation.duration"));
+    // Bind values are redacted by default
+    assert!(!insert.fields.contains_key("db.query.parameters"));
+
+    conn.set_trace_bind_values(true);
+    let names = users::table
+        .select(users::name)
+        .filter(users::name.ne("Jane"))
+        .load::<String>(conn)
+        .await
+        .unwrap();
+    assert_eq!(names, vec!["John"]);
+    let select = recorder.find(
+        "load",
+        "SELECT `users`.`name` FROM `users` WHERE (`users`.`name` != ?)",
+    );
+    assert_eq!(select.fields["db.response.returned_rows"], "1");
+    assert!(select.fields["db.query.parameters"].contains("Jane"));
+
+    let error = diesel::sql_query("SELECT * FROM missing_table WHERE id = 42")
+        .execute(conn)
+        .await;
+    assert!(error.is_err());
+    let failed = recorder.find("execute", "SELECT * FROM missing_table WHERE id = ?");
+    assert_eq!(failed.fields["error.type"], "Unknown");
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            diesel::delete(users::table).execute(conn).await?;
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await
+    .unwrap();
+    let spans = recorder.spans.lock().unwrap();
+    let transaction = spans
+        .iter()
+        .rev()
+        .find(|span| span.name == "transaction")
+        .unwrap();
+    assert_eq!(transaction.fields["db.transaction.outcome"], "commit");
+}
@@ -0,0 +1,100 @@
+use super::{connection, users};
+use crate::{SlowQueryLog, TursoValue};
+use diesel::prelude::*;
+use diesel_async::RunQueryDsl;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+struct ReportedQuery {
+    sql: String,
+    binds: Vec<TursoValue>,
+    query_plan: Option<Vec<String>>,
+}
+
+fn recording_log(threshold: Duration) -> (SlowQueryLog, Arc<Mutex<Vec<ReportedQuery>>>) {
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let log = SlowQueryLog::new(threshold, {
+        let reported = reported.clone();
+        move |query| {
+            reported.lock().unwrap().push(ReportedQuery {
+                sql: query.sql().to_owned(),
+                binds: query.binds().to_vec(),
+                query_plan: query.query_plan().map(|plan| {
+                    plan.nodes()
+                        .iter()
+                        .map(|step| step.detail().to_owned())
+                        .collect()
+                }),
+            });
+        }
+    });
+    (log, reported)
+}
+
+#[tokio::test]
+async fn reports_statements_over_the_threshold() {
+    let conn = &mut connection().await;
+    let (log, reported) = recording_log(Duration::ZERO);
+    conn.set_slow_query_log(log.with_query_plan(true));
+
+    diesel::insert_into(users::table)
+        .values(users::name.eq("John"))
+        .execute(conn)
+        .await
+        .unwrap();
+    let names = users::table
+        .select(users::name)
+        .filter(users::name.eq("John"))
+        .load::<String>(conn)
+        .await
+        .unwrap();
+    assert_eq!(names, vec!["John"]);
+
+    {
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 2);
+        assert_eq!(reported[0].sql, "INSERT INTO `users` (`name`) VALUES (?)");
+        assert_eq!(reported[0].binds[0].as_text(), Some("John"));
+        let select = &reported[1];
+        assert_eq!(
+            select.sql,
+            "SELECT `users`.`name` FROM `users` WHERE (`users`.`name` = ?)"
+        );
+        let plan = select.query_plan.as_ref().unwrap();
+        assert!(plan.iter().any(|step| step.contains("users")), "{plan:?}");
+    }
+
+    // Capturing the plan doesn't run the statement again
+    let count = users::table.count().get_result::<i64>(conn).await.unwrap();
+    assert_eq!(count, 1);
+
+    let (log, reported) = recording_log(Duration::from_secs(3600));
+    conn.set_slow_query_log(log);
+    users::table.load::<(i32, String)>(conn).await.unwrap();
+    assert!(reported.lock().unwrap().is_empty());
+
+    let (log, reported) = recording_log(Duration::ZERO);
+    conn.set_slow_query_log(log);
+    conn.clear_slow_query_log();
+    users::table.load::<(i32, String)>(conn).await.unwrap();
+    assert!(reported.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn change_counts_are_not_reported() {
+    let conn = &mut connection().await;
+    let (log, reported) = recording_log(Duration::ZERO);
+    conn.set_slow_query_log(log);
+
+    diesel::insert_into(users::table)
+        .values(users::name.eq("John"))
+        .execute(conn)
+        .await
+        .unwrap();
+    assert_eq!(conn.changes().await.unwrap(), 1);
+    assert!(conn.total_changes().await.unwrap() >= 1);
+    let reported = reported.lock().unwrap();
+    assert_eq!(reported.len(), 1);
+    assert_eq!(reported[0].sql, "INSERT INTO `users` (`name`) VALUES (?)");
+}
@@ -0,0 +1,92 @@
+use super::{connection, users};
+use crate::TursoErrorExt;
+use diesel::prelude::*;
+use diesel::sql_types::Integer;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn fetch_timeout_interrupts_queries() {
+    let conn = &mut connection().await;
+    assert_eq!(conn.fetch_timeout(), None);
+
+    conn.set_fetch_timeout(Some(Duration::ZERO));
+    let error = users::table.load::<(i32, String)>(conn).await.unwrap_err();
+    assert!(error.is_interrupted(), "{error}");
+
+    // Statements which return no rows aren't limited
+    diesel::insert_into(users::table)
+        .values(users::name.eq("John"))
+        .execute(conn)
+        .await
+        .unwrap();
+    conn.batch_execute("UPDATE users SET name = 'Jane'")
+        .await
+        .unwrap();
+
+    // The connection stays usable
+    conn.set_fetch_timeout(Some(Duration::from_secs(60)));
+    let count = users::table.count().get_result::<i64>(conn).await.unwrap();
+    assert_eq!(count, 1);
+
+    let error = diesel::sql_query("SELECT * FROM missing_table")
+        .execute(conn)
+        .await
+        .unwrap_err();
+    assert!(!error.is_interrupted());
+}
+
+#[tokio::test]
+async fn fetch_timeout_stops_a_running_query() {
+    let conn = &mut connection().await;
+    let values = (0..200)
+        .map(|i| format!("('User{i}')"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    conn.batch_execute(&format!("INSERT INTO users (name) VALUES {values}"))
+        .await
+        .unwrap();
+
+    // 8 million rows, far more than fit into the timeout
+    conn.set_fetch_timeout(Some(Duration::from_millis(50)));
+    let start = Instant::now();
+    let error = diesel::sql_query("SELECT a.id FROM users a, users b, users c")
+        .load::<Id>(conn)
+        .await
+        .unwrap_err();
+    assert!(error.is_interrupted(), "{error}");
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    conn.set_fetch_timeout(None);
+    let count = users::table.count().get_result::<i64>(conn).await.unwrap();
+    assert_eq!(count, 200);
+}
+
+#[derive(QueryableByName)]
+struct Id {
+    #[diesel(sql_type = Integer)]
+    #[allow(dead_code)]
+    id: i32,
+}
+
+#[tokio::test]
+async fn with_fetch_timeout_restores_the_timeout() {
+    let conn = &mut connection().await;
+    conn.set_fetch_timeout(Some(Duration::from_secs(60)));
+
+    let result = conn
+        .with_fetch_timeout(Duration::ZERO, |conn| {
+            async move { users::table.load::<(i32, String)>(conn).await }.scope_boxed()
+        })
+        .await;
+    assert!(result.unwrap_err().is_interrupted());
+    assert_eq!(conn.fetch_timeout(), Some(Duration::from_secs(60)));
+
+    let names = users::table
+        .select(users::name)
+        .load::<String>(conn)
+        .await
+        .unwrap();
+    assert!(names.is_empty());
+}
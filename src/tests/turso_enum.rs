@@ -0,0 +1,145 @@
+use super::connection;
+use crate::TursoEnum;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Nullable, Text};
+use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+
+#[derive(Debug, Clone, Copy, PartialEq, TursoEnum)]
+#[turso(rename_all = "snake_case")]
+enum Status {
+    Active,
+    OnHold,
+    #[turso(rename = "done")]
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, TursoEnum)]
+#[turso(sql_type = Integer)]
+enum Priority {
+    Low = 1,
+    Normal = 5,
+    High = 10,
+}
+
+table! {
+    tasks {
+        id -> Integer,
+        status -> Text,
+        priority -> Integer,
+        previous_status -> Nullable<Text>,
+    }
+}
+
+#[tokio::test]
+async fn round_trip_enums() -> QueryResult<()> {
+    let conn = &mut connection().await;
+    conn.batch_execute(
+        "CREATE TABLE tasks (
+            id INTEGER PRIMARY KEY,
+            status TEXT NOT NULL,
+            priority INTEGER NOT NULL,
+            previous_status TEXT
+        )",
+    )
+    .await?;
+
+    diesel::insert_into(tasks::table)
+        .values(vec![
+            (
+                tasks::id.eq(1),
+                tasks::status.eq(Status::OnHold),
+                tasks::priority.eq(Priority::High),
+                tasks::previous_status.eq(Some(Status::Active)),
+            ),
+            (
+                tasks::id.eq(2),
+                tasks::status.eq(Status::Finished),
+                tasks::priority.eq(Priority::Low),
+                tasks::previous_status.eq(None::<Status>),
+            ),
+        ])
+        .execute(conn)
+        .await?;
+
+    let stored = tasks::table
+        .select((tasks::status, tasks::priority))
+        .order_by(tasks::id)
+        .load::<(String, i32)>(conn)
+        .await?;
+    assert_eq!(
+        stored,
+        vec![("on_hold".to_owned(), 10), ("done".to_owned(), 1)]
+    );
+
+    let loaded = tasks::table
+        .select((tasks::status, tasks::priority, tasks::previous_status))
+        .filter(tasks::status.eq(&Status::OnHold))
+        .get_result::<(Status, Priority, Option<Status>)>(conn)
+        .await?;
+    assert_eq!(
+        loaded,
+        (Status::OnHold, Priority::High, Some(Status::Active))
+    );
+
+    let finished = tasks::table
+        .select(tasks::id)
+        .filter(tasks::priority.lt(Priority::Normal))
+        .load::<i32>(conn)
+        .await?;
+    assert_eq!(finished, vec![2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unknown_enum_values() {
+    let conn = &mut connection().await;
+
+    let text = diesel::select(sql::<Text>("'paused'"))
+        .get_result::<Status>(conn)
+        .await
+        .unwrap_err();
+    assert!(
+        text.to_string().contains(
+            "Unknown value `paused` for `Status`, expected one of `active`, `on_hold`, `done`"
+        ),
+        "{text}"
+    );
+
+    let integer = diesel::select(sql::<Integer>("7"))
+        .get_result::<Priority>(conn)
+        .await
+        .unwrap_err();
+    assert!(
+        integer
+            .to_string()
+            .contains("Unknown value 7 for `Priority`, expected one of 1, 5, 10"),
+        "{integer}"
+    );
+
+    // Only the storage class of the SQL type is accepted
+    let discriminant = diesel::select(sql::<Text>("1"))
+        .get_result::<Status>(conn)
+        .await
+        .unwrap_err();
+    assert!(
+        discriminant
+            .to_string()
+            .contains("Expected a TEXT value for `Status`, but got INTEGER"),
+        "{discriminant}"
+    );
+    let name = diesel::select(sql::<Integer>("'5'"))
+        .get_result::<Priority>(conn)
+        .await
+        .unwrap_err();
+    assert!(
+        name.to_string()
+            .contains("Expected an INTEGER value for `Priority`, but got TEXT"),
+        "{name}"
+    );
+
+    let null = diesel::select(sql::<Nullable<Text>>("NULL"))
+        .get_result::<Option<Status>>(conn)
+        .await;
+    assert_eq!(null, Ok(None));
+}
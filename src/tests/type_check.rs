@@ -61,6 +61,65 @@ async fn check_big_int() {
     type_check::<_, sql_types::BigInt>(conn, i64::MAX).await;
 }
 
+#[tokio::test]
+async fn check_tiny_int() {
+    let conn = &mut connection().await;
+    type_check::<_, sql_types::TinyInt>(conn, 1_i8).await;
+    type_check::<_, sql_types::TinyInt>(conn, -1_i8).await;
+    type_check::<_, sql_types::TinyInt>(conn, i8::MIN).await;
+    type_check::<_, sql_types::TinyInt>(conn, i8::MAX).await;
+}
+
+#[tokio::test]
+async fn check_unsigned() {
+    let conn = &mut connection().await;
+    type_check::<_, sql_types::Unsigned<sql_types::SmallInt>>(conn, u16::MIN).await;
+    type_check::<_, sql_types::Unsigned<sql_types::SmallInt>>(conn, u16::MAX).await;
+    type_check::<_, sql_types::Unsigned<sql_types::Integer>>(conn, u32::MIN).await;
+    type_check::<_, sql_types::Unsigned<sql_types::Integer>>(conn, u32::MAX).await;
+    type_check::<_, sql_types::Unsigned<sql_types::BigInt>>(conn, u64::MIN).await;
+    type_check::<_, sql_types::Unsigned<sql_types::BigInt>>(conn, i64::MAX as u64).await;
+}
+
+#[tokio::test]
+async fn check_integer_out_of_range() {
+    let conn = &mut connection().await;
+
+    let res = diesel::select(diesel::dsl::sql::<sql_types::TinyInt>("128"))
+        .get_result::<i8>(conn)
+        .await;
+    assert!(res.is_err());
+
+    let res = diesel::select(diesel::dsl::sql::<sql_types::Unsigned<sql_types::Integer>>(
+        "-1",
+    ))
+    .get_result::<u32>(conn)
+    .await;
+    assert!(res.is_err());
+
+    let res = diesel::select(diesel::dsl::sql::<sql_types::Unsigned<sql_types::BigInt>>(
+        "-1",
+    ))
+    .get_result::<u64>(conn)
+    .await;
+    assert!(res.is_err());
+
+    let res = diesel::select(u64::MAX.into_sql::<sql_types::Unsigned<sql_types::BigInt>>())
+        .get_result::<u64>(conn)
+        .await;
+    let err = res.unwrap_err().to_string();
+    assert!(err.contains("larger than"), "{err}");
+
+    let res = diesel::select(diesel::dsl::sql::<sql_types::TinyInt>("x'01'"))
+        .get_result::<i8>(conn)
+        .await;
+    let err = res.unwrap_err().to_string();
+    assert!(
+        err.contains("Expected an integer for TinyInt, but got BLOB"),
+        "{err}"
+    );
+}
+
 #[tokio::test]
 async fn check_bool() {
     let conn = &mut connection().await;
@@ -143,3 +202,112 @@ async fn test_time() {
     )
     .await;
 }
+
+#[cfg(feature = "serde_json")]
+#[tokio::test]
+async fn test_json() {
+    let conn = &mut connection().await;
+    type_check::<_, crate::sql_types::Json>(
+        conn,
+        serde_json::json!({"foo": "bar", "baz": [1, 2.5]}),
+    )
+    .await;
+    type_check::<_, crate::sql_types::Json>(conn, serde_json::json!(null)).await;
+}
+
+#[cfg(feature = "serde_json")]
+#[tokio::test]
+async fn test_jsonb() {
+    let conn = &mut connection().await;
+    type_check::<_, crate::sql_types::Jsonb>(
+        conn,
+        serde_json::json!({"foo": "bar", "baz": [1, 2.5, true, null], "quote": "a\"b"}),
+    )
+    .await;
+    type_check::<_, crate::sql_types::Jsonb>(
+        conn,
+        serde_json::json!("a long string value that does not fit into the header"),
+    )
+    .await;
+}
+
+#[derive(Debug, Clone, PartialEq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = sql_types::Text)]
+struct Tags(Vec<String>);
+
+impl diesel::deserialize::FromSql<sql_types::Text, crate::backend::TursoBackend> for Tags {
+    fn from_sql(value: crate::TursoValue) -> diesel::deserialize::Result<Self> {
+        let text = value.try_text()?;
+        Ok(Tags(text.split(',').map(str::to_owned).collect()))
+    }
+}
+
+impl diesel::serialize::ToSql<sql_types::Text, crate::backend::TursoBackend> for Tags {
+    fn to_sql<'b>(
+        &'b self,
+        out: &mut diesel::serialize::Output<'b, '_, crate::backend::TursoBackend>,
+    ) -> diesel::serialize::Result {
+        out.set_value(self.0.join(","));
+        Ok(diesel::serialize::IsNull::No)
+    }
+}
+
+#[derive(Debug, PartialEq, FromSqlRow)]
+struct StorageClass(crate::TursoStorageClass);
+
+impl<ST> diesel::deserialize::FromSql<ST, crate::backend::TursoBackend> for StorageClass {
+    fn from_sql(value: crate::TursoValue) -> diesel::deserialize::Result<Self> {
+        Ok(StorageClass(value.storage_class()))
+    }
+
+    fn from_nullable_sql(value: Option<crate::TursoValue>) -> diesel::deserialize::Result<Self> {
+        match value {
+            Some(value) => Self::from_sql(value),
+            None => Ok(StorageClass(crate::TursoStorageClass::Null)),
+        }
+    }
+}
+
+#[tokio::test]
+async fn check_custom_type() {
+    let conn = &mut connection().await;
+    type_check::<_, sql_types::Text>(conn, Tags(vec!["a".into(), "b".into()])).await;
+
+    let wrong_type = diesel::select(diesel::dsl::sql::<sql_types::Text>("1"))
+        .get_result::<Tags>(conn)
+        .await;
+    assert!(wrong_type.is_err());
+}
+
+#[tokio::test]
+async fn check_storage_class() {
+    use crate::TursoStorageClass;
+    use diesel::dsl::sql;
+
+    let conn = &mut connection().await;
+    let classes = diesel::select((
+        sql::<sql_types::Integer>("1"),
+        sql::<sql_types::Double>("1.5"),
+        sql::<sql_types::Text>("'a'"),
+        sql::<sql_types::Binary>("x'00'"),
+        sql::<sql_types::Nullable<sql_types::Text>>("NULL"),
+    ))
+    .get_result::<(
+        StorageClass,
+        StorageClass,
+        StorageClass,
+        StorageClass,
+        StorageClass,
+    )>(conn)
+    .await;
+    assert_eq!(
+        Ok((
+            StorageClass(TursoStorageClass::Integer),
+            StorageClass(TursoStorageClass::Real),
+            StorageClass(TursoStorageClass::Text),
+            StorageClass(TursoStorageClass::Blob),
+            StorageClass(TursoStorageClass::Null),
+        )),
+        classes
+    );
+}
@@ -0,0 +1,163 @@
+//! Fetch timeouts
+//!
+//! A fetch timeout limits the time a query may spend producing its rows. The
+//! engine computes a row inside the call which returns it, and doesn't offer
+//! a way to interrupt a running statement, so the deadline is checked by the
+//! connection between two rows. A query past its deadline is stopped and
+//! reset, and returns an error for which [`TursoErrorExt::is_interrupted`] is
+//! `true`.
+//!
+//! This is not a statement timeout:
+//!
+//! - A query which takes long to produce a single row, e.g. an aggregate over
+//!   a large table, runs to completion.
+//! - Statements which return no rows, e.g. `UPDATE` or `DELETE`, and
+//!   `batch_execute` aren't limited.
+
+use std::time::{Duration, Instant};
+
+use diesel::result::{DatabaseErrorInformation, DatabaseErrorKind, Error};
+use diesel::QueryResult;
+use diesel_async::scoped_futures::ScopedBoxFuture;
+
+use crate::AsyncTursoConnection;
+
+/// The details of errors of interrupted queries, engine errors have none
+const INTERRUPTED: &str = "interrupted";
+
+/// Turso specific information about errors
+pub trait TursoErrorExt {
+    /// Whether the query was interrupted before it returned all rows, e.g.
+    /// because it ran longer than its fetch timeout
+    ///
+    /// Such a query didn't fail, running it again with a longer timeout may
+    /// succeed.
+    fn is_interrupted(&self) -> bool;
+}
+
+impl TursoErrorExt for Error {
+    fn is_interrupted(&self) -> bool {
+        // `DatabaseErrorKind` has no variant for cancellation and can't be
+        // extended, the details mark the error instead
+        matches!(
+            self,
+            Error::DatabaseError(DatabaseErrorKind::Unknown, info)
+                if info.details() == Some(INTERRUPTED)
+        )
+    }
+}
+
+/// The error of a query stopped at its deadline
+#[derive(Debug)]
+struct Interrupted {
+    message: String,
+}
+
+impl DatabaseErrorInformation for Interrupted {
+    fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn details(&self) -> Option<&str> {
+        Some(INTERRUPTED)
+    }
+
+    fn hint(&self) -> Option<&str> {
+        None
+    }
+
+    fn table_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn column_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn constraint_name(&self) -> Option<&str> {
+        None
+    }
+
+    fn statement_position(&self) -> Option<i32> {
+        None
+    }
+}
+
+/// Restores the fetch timeout of a connection when dropped, also if the
+/// future of [`AsyncTursoConnection::with_fetch_timeout`] is cancelled
+struct TimeoutScope<'a> {
+    connection: &'a mut AsyncTursoConnection,
+    previous: Option<Duration>,
+}
+
+impl Drop for TimeoutScope<'_> {
+    fn drop(&mut self) {
+        self.connection.fetch_timeout = self.previous;
+    }
+}
+
+impl AsyncTursoConnection {
+    /// The longest time a query of this connection may spend producing its
+    /// rows, `None` (the default) for no limit
+    pub fn fetch_timeout(&self) -> Option<Duration> {
+        self.fetch_timeout
+    }
+
+    /// Limits the time every following query of this connection may spend
+    /// producing its rows
+    ///
+    /// See the [module documentation](self) for what is stopped, the
+    /// timeout is only checked between two rows.
+    pub fn set_fetch_timeout(&mut self, timeout: Option<Duration>) {
+        self.fetch_timeout = timeout;
+    }
+
+    /// Runs `f` with a fetch timeout of `timeout`, the timeout of the
+    /// connection is restored afterwards
+    ///
+    /// ```rust,ignore
+    /// let report = connection
+    ///     .with_fetch_timeout(Duration::from_secs(5), |conn| {
+    ///         async move { report_query.load::<ReportRow>(conn).await }.scope_boxed()
+    ///     })
+    ///     .await;
+    /// match report {
+    ///     Err(e) if e.is_interrupted() => { /* took too long */ }
+    ///     result => { /* ... */ }
+    /// }
+    /// ```
+    pub async fn with_fetch_timeout<'a, R, F>(
+        &'a mut self,
+        timeout: Duration,
+        f: F,
+    ) -> QueryResult<R>
+    where
+        F: for<'r> FnOnce(&'r mut Self) -> ScopedBoxFuture<'a, 'r, QueryResult<R>> + Send + 'a,
+        R: Send + 'a,
+    {
+        let previous = self.fetch_timeout.replace(timeout);
+        let mut scope = TimeoutScope {
+            connection: self,
+            previous,
+        };
+        let result = f(&mut *scope.connection).await;
+        result
+    }
+
+    /// When a query starting now has to stop
+    pub(crate) fn fetch_deadline(&self) -> Option<Instant> {
+        self.fetch_timeout.map(|timeout| Instant::now() + timeout)
+    }
+
+    /// The error of a query stopped at its deadline
+    pub(crate) fn fetch_timeout_error(&self) -> Error {
+        // Only queries with a deadline are stopped
+        let timeout = self.fetch_timeout.unwrap_or_default();
+        Error::DatabaseError(
+            DatabaseErrorKind::Unknown,
+            Box::new(Interrupted {
+                message: format!("interrupted: fetching the rows took longer than {timeout:?}"),
+            }),
+        )
+    }
+}
@@ -5,9 +5,13 @@ use diesel::deserialize::{self, FromSql};
 use diesel::serialize::{self, IsNull, Output, ToSql};
 use diesel::sql_types;
 
-use self::chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use self::chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone, Utc,
+};
 
 use crate::backend::TursoBackend;
+use crate::sql_types::{foreign_as_expression, TimestampJulian, TimestampUnix, Timestamptz};
+use crate::value::TursoValue;
 
 const DATE_FORMAT: &str = "%F";
 
@@ -44,9 +48,31 @@ const NAIVE_DATETIME_FORMATS: [&str; 18] = [
     "%FT%T%.f%:z",
 ];
 
+const DATETIME_FORMATS: [&str; 12] = [
+    // Most likely formats
+    "%FT%T%.f%:z",
+    "%F %T%.f%:z",
+    "%F %T%:z",
+    // All other formats in order of increasing specificity
+    "%F %RZ",
+    "%F %R%:z",
+    "%F %TZ",
+    "%F %T%.fZ",
+    "%FT%RZ",
+    "%FT%R%:z",
+    "%FT%TZ",
+    "%FT%T%:z",
+    "%FT%T%.fZ",
+];
+
+foreign_as_expression!(<Tz: TimeZone> DateTime<Tz> => Timestamptz);
+foreign_as_expression!(NaiveDateTime => TimestampUnix);
+foreign_as_expression!(NaiveDateTime => TimestampJulian);
+
+const EPOCH_IN_JULIAN_DAYS: f64 = 2_440_587.5;
+const SECONDS_IN_DAY: f64 = 86400.0;
+
 fn parse_julian(julian_days: f64) -> Option<NaiveDateTime> {
-    const EPOCH_IN_JULIAN_DAYS: f64 = 2_440_587.5;
-    const SECONDS_IN_DAY: f64 = 86400.0;
     let timestamp = (julian_days - EPOCH_IN_JULIAN_DAYS) * SECONDS_IN_DAY;
     #[allow(clippy::cast_possible_truncation)] // we want to truncate
     let seconds = timestamp.trunc() as i64;
@@ -57,12 +83,62 @@ fn parse_julian(julian_days: f64) -> Option<NaiveDateTime> {
     NaiveDateTime::from_timestamp_opt(seconds, nanos)
 }
 
+fn to_julian(dt: &NaiveDateTime) -> f64 {
+    #[allow(deprecated)] // otherwise we would need to bump our minimal chrono version
+    let seconds = dt.timestamp() as f64 + f64::from(dt.timestamp_subsec_nanos()) / 1E9;
+    seconds / SECONDS_IN_DAY + EPOCH_IN_JULIAN_DAYS
+}
+
+// Values written by `unixepoch()` are stored as INTEGER and values written by
+// `julianday()` as REAL, text is left to the caller
+fn parse_numeric(value: &TursoValue) -> Option<deserialize::Result<NaiveDateTime>> {
+    if let Some(seconds) = value.as_integer() {
+        #[allow(deprecated)] // otherwise we would need to bump our minimal chrono version
+        let dt = NaiveDateTime::from_timestamp_opt(seconds, 0)
+            .ok_or_else(|| format!("Invalid unix timestamp {seconds}").into());
+        return Some(dt);
+    }
+    if let Some(julian_days) = value.as_real() {
+        let dt = parse_julian(julian_days)
+            .ok_or_else(|| format!("Invalid julian day {julian_days}").into());
+        return Some(dt);
+    }
+    None
+}
+
+fn parse_naive_datetime(value: &TursoValue) -> deserialize::Result<NaiveDateTime> {
+    if let Some(dt) = parse_numeric(value) {
+        return dt;
+    }
+    let text = value
+        .as_text()
+        .ok_or_else(|| format!("Expected a datetime, but got {}", value.storage_class()))?;
+
+    for format in NAIVE_DATETIME_FORMATS {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Ok(dt);
+        }
+    }
+
+    if let Ok(julian_days) = text.parse::<f64>() {
+        if let Some(timestamp) = parse_julian(julian_days) {
+            return Ok(timestamp);
+        }
+    }
+
+    Err(format!("Invalid datetime {text}").into())
+}
+
 #[cfg(feature = "chrono")]
 impl FromSql<sql_types::Date, TursoBackend> for NaiveDate {
     fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
-        value
-            .parse_string(|s| Self::parse_from_str(s, DATE_FORMAT))
-            .map_err(Into::into)
+        if let Some(dt) = parse_numeric(&value) {
+            return dt.map(|dt| dt.date());
+        }
+        let text = value
+            .as_text()
+            .ok_or_else(|| format!("Expected a date, but got {}", value.storage_class()))?;
+        Self::parse_from_str(text, DATE_FORMAT).map_err(Into::into)
     }
 }
 
@@ -77,15 +153,20 @@ impl ToSql<sql_types::Date, TursoBackend> for NaiveDate {
 #[cfg(feature = "chrono")]
 impl FromSql<sql_types::Time, TursoBackend> for NaiveTime {
     fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
-        value.parse_string(|text| {
-            for format in TIME_FORMATS {
-                if let Ok(time) = Self::parse_from_str(text, format) {
-                    return Ok(time);
-                }
+        if let Some(dt) = parse_numeric(&value) {
+            return dt.map(|dt| dt.time());
+        }
+        let text = value
+            .as_text()
+            .ok_or_else(|| format!("Expected a time, but got {}", value.storage_class()))?;
+
+        for format in TIME_FORMATS {
+            if let Ok(time) = Self::parse_from_str(text, format) {
+                return Ok(time);
             }
+        }
 
-            Err(format!("Invalid time {text}").into())
-        })
+        Err(format!("Invalid time {text}").into())
     }
 }
 
@@ -100,28 +181,92 @@ impl ToSql<sql_types::Time, TursoBackend> for NaiveTime {
 #[cfg(feature = "chrono")]
 impl FromSql<sql_types::Timestamp, TursoBackend> for NaiveDateTime {
     fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
-        value.parse_string(|text| {
-            for format in NAIVE_DATETIME_FORMATS {
-                if let Ok(dt) = Self::parse_from_str(text, format) {
+        parse_naive_datetime(&value)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToSql<sql_types::Timestamp, TursoBackend> for NaiveDateTime {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(self.format(ENCODE_NAIVE_DATETIME_FORMAT).to_string());
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromSql<Timestamptz, TursoBackend> for DateTime<FixedOffset> {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        // First try to parse the timezone
+        if let Some(text) = value.as_text() {
+            for format in DATETIME_FORMATS {
+                if let Ok(dt) = DateTime::parse_from_str(text, format) {
                     return Ok(dt);
                 }
             }
+        }
 
-            if let Ok(julian_days) = text.parse::<f64>() {
-                if let Some(timestamp) = parse_julian(julian_days) {
-                    return Ok(timestamp);
-                }
-            }
+        // Fallback on assuming Utc
+        let naive_date_time = parse_naive_datetime(&value)?;
+        Ok(Utc.from_utc_datetime(&naive_date_time).into())
+    }
+}
 
-            Err(format!("Invalid datetime {text}").into())
-        })
+#[cfg(feature = "chrono")]
+impl FromSql<Timestamptz, TursoBackend> for DateTime<Utc> {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        let dt = <DateTime<FixedOffset> as FromSql<Timestamptz, TursoBackend>>::from_sql(value)?;
+        Ok(dt.with_timezone(&Utc))
     }
 }
 
 #[cfg(feature = "chrono")]
-impl ToSql<sql_types::Timestamp, TursoBackend> for NaiveDateTime {
+impl FromSql<Timestamptz, TursoBackend> for DateTime<Local> {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        let dt = <DateTime<FixedOffset> as FromSql<Timestamptz, TursoBackend>>::from_sql(value)?;
+        Ok(dt.with_timezone(&Local))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz> ToSql<Timestamptz, TursoBackend> for DateTime<Tz>
+where
+    Tz: TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
-        out.set_value(self.format(ENCODE_NAIVE_DATETIME_FORMAT).to_string());
+        // Keeps the offset, so that it round trips for `DateTime<FixedOffset>`
+        out.set_value(self.to_rfc3339_opts(SecondsFormat::AutoSi, false));
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromSql<TimestampUnix, TursoBackend> for NaiveDateTime {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        parse_naive_datetime(&value)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToSql<TimestampUnix, TursoBackend> for NaiveDateTime {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        #[allow(deprecated)] // otherwise we would need to bump our minimal chrono version
+        out.set_value(self.timestamp());
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl FromSql<TimestampJulian, TursoBackend> for NaiveDateTime {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        parse_naive_datetime(&value)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl ToSql<TimestampJulian, TursoBackend> for NaiveDateTime {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(to_julian(self));
         Ok(IsNull::No)
     }
 }
@@ -131,22 +276,20 @@ impl ToSql<sql_types::Timestamp, TursoBackend> for NaiveDateTime {
 mod tests {
     extern crate chrono;
 
-    use self::chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+    use self::chrono::{
+        DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+        Timelike, Utc,
+    };
 
     use diesel::dsl::{now, sql};
     //use diesel::prelude::*;
+    use crate::dsl::{date, datetime, julianday, strftime, time, unixepoch};
+    use crate::sql_types::{TimestampJulian, TimestampUnix, Timestamptz};
     use crate::tests::connection;
-    use diesel::sql_types::{Text, Time, Timestamp};
-    use diesel::{declare_sql_function, select, ExpressionMethods};
+    use diesel::sql_types::{BigInt, Date, Double, Text, Time, Timestamp};
+    use diesel::{select, ExpressionMethods, IntoSql, QueryDsl};
     use diesel_async::*;
 
-    #[declare_sql_function]
-    extern "SQL" {
-        fn datetime(x: Text) -> Timestamp;
-        fn time(x: Text) -> Time;
-        fn date(x: Text) -> Date;
-    }
-
     #[tokio::test]
     async fn unix_epoch_encodes_correctly() {
         let connection = &mut connection().await;
@@ -154,7 +297,7 @@ mod tests {
             .unwrap()
             .and_hms_opt(0, 0, 0)
             .unwrap();
-        let query = select(datetime("1970-01-01 00:00:00.000000").eq(time));
+        let query = select(datetime("1970-01-01 00:00:00.000000".into_sql::<Text>()).eq(time));
         assert_eq!(Ok(true), query.get_result(connection).await);
     }
 
@@ -234,11 +377,11 @@ mod tests {
         let connection = &mut connection().await;
 
         let midnight = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
-        let query = select(time("00:00:00.000000").eq(midnight));
+        let query = select(time("00:00:00.000000".into_sql::<Text>()).eq(midnight));
         assert!(query.get_result::<bool>(connection).await.unwrap());
 
         let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
-        let query = select(time("12:00:00.000000").eq(noon));
+        let query = select(time("12:00:00.000000".into_sql::<Text>()).eq(noon));
         assert!(query.get_result::<bool>(connection).await.unwrap());
 
         let roughly_half_past_eleven = NaiveTime::from_hms_micro_opt(23, 37, 4, 2200).unwrap();
@@ -297,19 +440,19 @@ mod tests {
     async fn dates_encode_correctly() {
         let connection = &mut connection().await;
         let january_first_2000 = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
-        let query = select(date("2000-01-01").eq(january_first_2000));
+        let query = select(date("2000-01-01".into_sql::<Text>()).eq(january_first_2000));
         assert!(query.get_result::<bool>(connection).await.unwrap());
 
         let distant_past = NaiveDate::from_ymd_opt(0, 4, 11).unwrap();
-        let query = select(date("0000-04-11").eq(distant_past));
+        let query = select(date("0000-04-11".into_sql::<Text>()).eq(distant_past));
         assert!(query.get_result::<bool>(connection).await.unwrap());
 
         let january_first_2018 = NaiveDate::from_ymd_opt(2018, 1, 1).unwrap();
-        let query = select(date("2018-01-01").eq(january_first_2018));
+        let query = select(date("2018-01-01".into_sql::<Text>()).eq(january_first_2018));
         assert!(query.get_result::<bool>(connection).await.unwrap());
 
         let distant_future = NaiveDate::from_ymd_opt(9999, 1, 8).unwrap();
-        let query = select(date("9999-01-08").eq(distant_future));
+        let query = select(date("9999-01-08".into_sql::<Text>()).eq(distant_future));
         assert!(query.get_result::<bool>(connection).await.unwrap());
     }
 
@@ -317,28 +460,28 @@ mod tests {
     async fn dates_decode_correctly() {
         let connection = &mut connection().await;
         let january_first_2000 = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
-        let query = select(date("2000-01-01"));
+        let query = select(date("2000-01-01".into_sql::<Text>()));
         assert_eq!(
             Ok(january_first_2000),
             query.get_result::<NaiveDate>(connection).await
         );
 
         let distant_past = NaiveDate::from_ymd_opt(0, 4, 11).unwrap();
-        let query = select(date("0000-04-11"));
+        let query = select(date("0000-04-11".into_sql::<Text>()));
         assert_eq!(
             Ok(distant_past),
             query.get_result::<NaiveDate>(connection).await
         );
 
         let january_first_2018 = NaiveDate::from_ymd_opt(2018, 1, 1).unwrap();
-        let query = select(date("2018-01-01"));
+        let query = select(date("2018-01-01".into_sql::<Text>()));
         assert_eq!(
             Ok(january_first_2018),
             query.get_result::<NaiveDate>(connection).await
         );
 
         let distant_future = NaiveDate::from_ymd_opt(9999, 1, 8).unwrap();
-        let query = select(date("9999-01-08"));
+        let query = select(date("9999-01-08".into_sql::<Text>()));
         assert_eq!(
             Ok(distant_future),
             query.get_result::<NaiveDate>(connection).await
@@ -352,7 +495,7 @@ mod tests {
             .unwrap()
             .and_hms_opt(1, 1, 1)
             .unwrap();
-        let query = select(datetime("2000-01-01 01:01:01.000000"));
+        let query = select(datetime("2000-01-01 01:01:01.000000".into_sql::<Text>()));
         assert_eq!(
             Ok(january_first_2000),
             query.get_result::<NaiveDateTime>(connection).await
@@ -362,14 +505,14 @@ mod tests {
             .unwrap()
             .and_hms_opt(2, 2, 2)
             .unwrap();
-        let query = select(datetime("0000-04-11 02:02:02.000000"));
+        let query = select(datetime("0000-04-11 02:02:02.000000".into_sql::<Text>()));
         assert_eq!(
             Ok(distant_past),
             query.get_result::<NaiveDateTime>(connection).await
         );
 
         let january_first_2018 = NaiveDate::from_ymd_opt(2018, 1, 1).unwrap();
-        let query = select(date("2018-01-01"));
+        let query = select(date("2018-01-01".into_sql::<Text>()));
         assert_eq!(
             Ok(january_first_2018),
             query.get_result::<NaiveDate>(connection).await
@@ -395,14 +538,17 @@ mod tests {
             .unwrap()
             .and_hms_opt(0, 0, 0)
             .unwrap();
-        let query = select(datetime("2000-01-01 00:00:00.000000").eq(january_first_2000));
+        let query = select(
+            datetime("2000-01-01 00:00:00.000000".into_sql::<Text>()).eq(january_first_2000),
+        );
         assert!(query.get_result::<bool>(connection).await.unwrap());
 
         let distant_past = NaiveDate::from_ymd_opt(0, 4, 11)
             .unwrap()
             .and_hms_opt(20, 00, 20)
             .unwrap();
-        let query = select(datetime("0000-04-11 20:00:20.000000").eq(distant_past));
+        let query =
+            select(datetime("0000-04-11 20:00:20.000000".into_sql::<Text>()).eq(distant_past));
         assert!(query.get_result::<bool>(connection).await.unwrap());
 
         let january_first_2018 = NaiveDate::from_ymd_opt(2018, 1, 1)
@@ -418,7 +564,327 @@ mod tests {
             .unwrap()
             .and_hms_opt(0, 0, 0)
             .unwrap();
-        let query = select(datetime("9999-01-08 00:00:00.000000").eq(distant_future));
+        let query =
+            select(datetime("9999-01-08 00:00:00.000000".into_sql::<Text>()).eq(distant_future));
         assert!(query.get_result::<bool>(connection).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn datetimes_with_offset_encode_as_rfc3339() {
+        let connection = &mut connection().await;
+        let time = Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).single().unwrap();
+        let query = select(sql::<Timestamptz>("'1970-01-01T00:00:00+00:00'").eq(time));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let time = Utc
+            .with_ymd_and_hms(1970, 1, 1, 0, 0, 0)
+            .single()
+            .unwrap()
+            .with_nanosecond(1_000_000)
+            .unwrap();
+        let query = select(sql::<Timestamptz>("'1970-01-01T00:00:00.001+00:00'").eq(time));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        // West one hour is negative offset
+        let time = FixedOffset::west_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(1970, 1, 1, 0, 0, 0)
+            .single()
+            .unwrap();
+        let query = select(sql::<Timestamptz>("'1970-01-01T00:00:00-01:00'").eq(time));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn datetimes_with_offset_round_trip() {
+        let connection = &mut connection().await;
+        let time = FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 5, 1, 12, 30, 0)
+            .single()
+            .unwrap()
+            .with_nanosecond(250_000_000)
+            .unwrap();
+        let result = select(sql::<Timestamptz>("").bind::<Timestamptz, _>(time))
+            .get_result::<DateTime<FixedOffset>>(connection)
+            .await
+            .unwrap();
+        assert_eq!(time, result);
+        assert_eq!(time.offset(), result.offset());
+
+        let utc = select(sql::<Timestamptz>("").bind::<Timestamptz, _>(time))
+            .get_result::<DateTime<Utc>>(connection)
+            .await;
+        assert_eq!(Ok(time.with_timezone(&Utc)), utc);
+
+        let local = select(sql::<Timestamptz>("").bind::<Timestamptz, _>(time))
+            .get_result::<DateTime<Local>>(connection)
+            .await;
+        assert_eq!(Ok(time.with_timezone(&Local)), local);
+    }
+
+    #[tokio::test]
+    async fn datetimes_with_offset_decode_in_all_possible_formats() {
+        let connection = &mut connection().await;
+        let time = Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).single().unwrap();
+        let valid_epoch_formats = vec![
+            "1970-01-01 00:00Z",
+            "1970-01-01 00:00:00Z",
+            "1970-01-01 00:00:00.000Z",
+            "1970-01-01 00:00:00.000000Z",
+            "1970-01-01T00:00Z",
+            "1970-01-01T00:00:00Z",
+            "1970-01-01T00:00:00.000Z",
+            "1970-01-01T00:00:00.000000Z",
+            "1970-01-01 00:00+00:00",
+            "1970-01-01 00:00:00+00:00",
+            "1970-01-01 00:00:00.000+00:00",
+            "1970-01-01 00:00:00.000000+00:00",
+            "1970-01-01T00:00+00:00",
+            "1970-01-01T00:00:00+00:00",
+            "1970-01-01T00:00:00.000+00:00",
+            "1970-01-01T00:00:00.000000+00:00",
+            "1970-01-01T01:00:00+01:00",
+            "1969-12-31T23:00:00-01:00",
+            "1970-01-01 00:00:00",
+            "2440587.5",
+        ];
+
+        for s in valid_epoch_formats {
+            let epoch_from_sql = select(sql::<Timestamptz>(&format!("'{s}'")))
+                .get_result::<DateTime<Utc>>(connection)
+                .await;
+            assert_eq!(Ok(time), epoch_from_sql, "format {s} failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn insert_timestamptz_into_table_as_text() {
+        diesel::table! {
+            use diesel::sql_types::*;
+            use crate::sql_types::Timestamptz;
+
+            #[allow(unused_parens)]
+            test_insert_timestamptz_into_table_as_text(id) {
+                id -> Integer,
+                timestamp_with_tz -> Timestamptz,
+            }
+        }
+        let conn = &mut connection().await;
+        diesel::sql_query(
+            "CREATE TABLE test_insert_timestamptz_into_table_as_text(id INTEGER PRIMARY KEY, timestamp_with_tz TEXT);",
+        )
+        .execute(conn)
+        .await
+        .unwrap();
+
+        let time = FixedOffset::east_opt(5 * 3600 + 30 * 60)
+            .unwrap()
+            .with_ymd_and_hms(2021, 9, 27, 17, 44, 23)
+            .single()
+            .unwrap();
+
+        diesel::insert_into(test_insert_timestamptz_into_table_as_text::table)
+            .values((
+                test_insert_timestamptz_into_table_as_text::id.eq(1),
+                test_insert_timestamptz_into_table_as_text::timestamp_with_tz.eq(time),
+            ))
+            .execute(conn)
+            .await
+            .unwrap();
+
+        let (text, result) = test_insert_timestamptz_into_table_as_text::table
+            .select((
+                sql::<Text>("timestamp_with_tz"),
+                test_insert_timestamptz_into_table_as_text::timestamp_with_tz,
+            ))
+            .get_result::<(String, DateTime<FixedOffset>)>(conn)
+            .await
+            .unwrap();
+        assert_eq!("2021-09-27T17:44:23+05:30", text);
+        assert_eq!(time, result);
+    }
+
+    #[tokio::test]
+    async fn integer_and_real_storage_decodes_correctly() {
+        let connection = &mut connection().await;
+        let unix = NaiveDate::from_ymd_opt(2021, 9, 27)
+            .unwrap()
+            .and_hms_opt(17, 44, 23)
+            .unwrap();
+        let julian = NaiveDate::from_ymd_opt(1970, 1, 2)
+            .unwrap()
+            .and_hms_opt(18, 0, 0)
+            .unwrap();
+
+        let query = select(sql::<Timestamp>("1632764663"));
+        assert_eq!(
+            Ok(unix),
+            query.get_result::<NaiveDateTime>(connection).await
+        );
+        let query = select(sql::<Timestamp>("2440589.25"));
+        assert_eq!(
+            Ok(julian),
+            query.get_result::<NaiveDateTime>(connection).await
+        );
+
+        let query = select(sql::<Date>("1632764663"));
+        assert_eq!(
+            Ok(unix.date()),
+            query.get_result::<NaiveDate>(connection).await
+        );
+        let query = select(sql::<Date>("2440589.25"));
+        assert_eq!(
+            Ok(julian.date()),
+            query.get_result::<NaiveDate>(connection).await
+        );
+
+        let query = select(sql::<Time>("1632764663"));
+        assert_eq!(
+            Ok(unix.time()),
+            query.get_result::<NaiveTime>(connection).await
+        );
+        let query = select(sql::<Time>("2440589.25"));
+        assert_eq!(
+            Ok(julian.time()),
+            query.get_result::<NaiveTime>(connection).await
+        );
+
+        let query = select(sql::<Timestamp>("X'00'"));
+        assert!(query.get_result::<NaiveDateTime>(connection).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn timestamps_encode_as_unix_and_julian() {
+        let connection = &mut connection().await;
+        let time = NaiveDate::from_ymd_opt(1970, 1, 2)
+            .unwrap()
+            .and_hms_opt(18, 0, 0)
+            .unwrap();
+
+        let query = select(sql::<BigInt>("").bind::<TimestampUnix, _>(time));
+        assert_eq!(Ok(151_200), query.get_result::<i64>(connection).await);
+        let query = select(sql::<Double>("").bind::<TimestampJulian, _>(time));
+        assert_eq!(Ok(2_440_589.25), query.get_result::<f64>(connection).await);
+
+        let query = select(sql::<TimestampUnix>("").bind::<TimestampUnix, _>(time));
+        assert_eq!(
+            Ok(time),
+            query.get_result::<NaiveDateTime>(connection).await
+        );
+        let query = select(sql::<TimestampJulian>("").bind::<TimestampJulian, _>(time));
+        assert_eq!(
+            Ok(time),
+            query.get_result::<NaiveDateTime>(connection).await
+        );
+    }
+
+    table! {
+        events {
+            id -> Integer,
+            happened_at -> Timestamp,
+            logged_at -> crate::sql_types::TimestampUnix,
+        }
+    }
+
+    #[tokio::test]
+    async fn date_and_time_functions_with_modifiers() {
+        let connection = &mut connection().await;
+        let moment = NaiveDate::from_ymd_opt(2024, 2, 20)
+            .unwrap()
+            .and_hms_opt(13, 45, 30)
+            .unwrap();
+
+        let query = select((
+            date(moment.into_sql::<Timestamp>()).modifier("start of month"),
+            time(moment.into_sql::<Timestamp>()).modifier("+15 minutes"),
+            datetime(moment.into_sql::<Timestamp>())
+                .modifier("+1 day")
+                .modifier("start of day"),
+            strftime("%Y-%m", moment.into_sql::<Timestamp>()).modifier("+10 days"),
+        ));
+        assert_eq!(
+            Ok((
+                NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+                NaiveTime::from_hms_opt(14, 0, 30).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 2, 21)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+                "2024-03".to_string(),
+            )),
+            query
+                .get_result::<(NaiveDate, NaiveTime, NaiveDateTime, String)>(connection)
+                .await
+        );
+
+        let query = select((
+            unixepoch("1970-01-02".into_sql::<Text>()),
+            julianday("1970-01-02 18:00:00".into_sql::<Text>()),
+            // `TimestampUnix` values are interpreted as unix time
+            datetime(sql::<TimestampUnix>("86400")),
+        ));
+        assert_eq!(
+            Ok((
+                86_400,
+                2_440_589.25,
+                NaiveDate::from_ymd_opt(1970, 1, 2)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+            )),
+            query
+                .get_result::<(i64, f64, NaiveDateTime)>(connection)
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn date_and_time_functions_in_filters() {
+        let connection = &mut connection().await;
+        connection
+            .batch_execute(
+                "CREATE TABLE events (
+                    id INTEGER PRIMARY KEY,
+                    happened_at TEXT NOT NULL,
+                    logged_at INTEGER NOT NULL
+                )",
+            )
+            .await
+            .unwrap();
+        connection
+            .batch_execute(
+                "INSERT INTO events VALUES
+                    (1, '2024-01-31 10:00:00', 1706695200),
+                    (2, '2024-02-15 10:00:00', 1707991200),
+                    (3, '2024-03-01 10:00:00', 1709287200)",
+            )
+            .await
+            .unwrap();
+
+        let february = events::table
+            .select(events::id)
+            .filter(
+                date(events::happened_at)
+                    .ge(date("2024-02-29".into_sql::<Text>()).modifier("start of month")),
+            )
+            .filter(
+                events::happened_at.lt(datetime("2024-02-29".into_sql::<Text>())
+                    .modifier("start of month")
+                    .modifier("+1 month")),
+            )
+            .load::<i32>(connection)
+            .await;
+        assert_eq!(Ok(vec![2]), february);
+
+        let months = events::table
+            .select(strftime("%m", events::logged_at))
+            .order_by(events::id)
+            .load::<String>(connection)
+            .await;
+        assert_eq!(
+            Ok(vec!["01".to_string(), "02".to_string(), "03".to_string()]),
+            months
+        );
+    }
 }
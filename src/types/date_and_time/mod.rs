@@ -8,6 +8,8 @@ use crate::{backend::TursoBackend, value::TursoValue};
 
 #[cfg(feature = "chrono")]
 mod chrono;
+#[cfg(feature = "time")]
+mod time;
 
 impl FromSql<sql_types::Date, TursoBackend> for String {
     fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
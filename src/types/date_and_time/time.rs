@@ -0,0 +1,804 @@
+//! This module makes it possible to map `time` date and time values to text
+//! fields. It is enabled with the `time` feature.
+
+extern crate time;
+
+use self::time::{
+    error::ComponentRange, macros::format_description, Date as NaiveDate, OffsetDateTime,
+    PrimitiveDateTime, Time as NaiveTime, UtcOffset,
+};
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+use self::time::format_description::FormatItem;
+
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::{Date, Time, Timestamp};
+
+use crate::backend::TursoBackend;
+use crate::sql_types::{foreign_as_expression, TimestampJulian, TimestampUnix, Timestamptz};
+use crate::value::TursoValue;
+
+/// Warning to future editors:
+/// Changes in the following formats need to be kept in sync
+/// with the formats of the `chrono` module.
+/// We need a distinction between whole second and subsecond
+/// since there is no format option to forgo the dot.
+/// We always print as many subsecond as his given to us,
+/// this means the subsecond part can be between 1 and 9 digits.
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const DATE_FORMAT: &[FormatItem<'_>] = format_description!("[year]-[month]-[day]");
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const ENCODE_TIME_FORMAT_WHOLE_SECOND: &[FormatItem<'_>] =
+    format_description!("[hour]:[minute]:[second]");
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const ENCODE_TIME_FORMAT_SUBSECOND: &[FormatItem<'_>] =
+    format_description!("[hour]:[minute]:[second].[subsecond]");
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const TIME_FORMATS: [&[FormatItem<'_>]; 9] = [
+    // Most likely formats
+    format_description!("[hour]:[minute]:[second].[subsecond]"),
+    format_description!("[hour]:[minute]:[second]"),
+    // All other valid formats in order of increasing specificity
+    format_description!("[hour]:[minute]"),
+    format_description!("[hour]:[minute]Z"),
+    format_description!("[hour]:[minute][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[hour]:[minute]:[second]Z"),
+    format_description!("[hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[hour]:[minute]:[second].[subsecond]Z"),
+    format_description!(
+        "[hour]:[minute]:[second].[subsecond][offset_hour sign:mandatory]:[offset_minute]"
+    ),
+];
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const ENCODE_PRIMITIVE_DATETIME_FORMAT_WHOLE_SECOND: &[FormatItem<'_>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const ENCODE_PRIMITIVE_DATETIME_FORMAT_SUBSECOND: &[FormatItem<'_>] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond]");
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const ENCODE_DATETIME_FORMAT_WHOLE_SECOND: &[FormatItem<'_>] = format_description!(
+    "[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]"
+);
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const ENCODE_DATETIME_FORMAT_SUBSECOND: &[FormatItem<'_>] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond][offset_hour sign:mandatory]:[offset_minute]");
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const PRIMITIVE_DATETIME_FORMATS: [&[FormatItem<'_>]; 18] = [
+    // Most likely formats
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond]"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]"),
+    // All other formats in order of increasing specificity
+    format_description!("[year]-[month]-[day] [hour]:[minute]"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]Z"),
+    format_description!("[year]-[month]-[day] [hour]:[minute][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]Z"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond]Z"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]Z"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]Z"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond]"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond]Z"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond][offset_hour sign:mandatory]:[offset_minute]"),
+];
+
+// the non-deprecated variant does not exist in our minimal supported version
+#[allow(deprecated)]
+const DATETIME_FORMATS: [&[FormatItem<'_>]; 12] = [
+    // Most likely formats
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]"),
+    // All other formats in order of increasing specificity
+    format_description!("[year]-[month]-[day] [hour]:[minute]Z"),
+    format_description!("[year]-[month]-[day] [hour]:[minute][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second]Z"),
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond]Z"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]Z"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]Z"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond]Z"),
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond][offset_hour sign:mandatory]:[offset_minute]"),
+];
+
+foreign_as_expression!(OffsetDateTime => Timestamptz);
+foreign_as_expression!(PrimitiveDateTime => TimestampUnix);
+foreign_as_expression!(PrimitiveDateTime => TimestampJulian);
+
+fn naive_utc(dt: OffsetDateTime) -> PrimitiveDateTime {
+    let dt = dt.to_offset(UtcOffset::UTC);
+    PrimitiveDateTime::new(dt.date(), dt.time())
+}
+
+const EPOCH_IN_JULIAN_DAYS: f64 = 2_440_587.5;
+const SECONDS_IN_DAY: f64 = 86400.0;
+
+fn parse_julian(julian_days: f64) -> Result<PrimitiveDateTime, ComponentRange> {
+    let timestamp = (julian_days - EPOCH_IN_JULIAN_DAYS) * SECONDS_IN_DAY;
+    #[allow(clippy::cast_possible_truncation)] // we multiply by 1E9 to prevent that
+    OffsetDateTime::from_unix_timestamp_nanos((timestamp * 1E9) as i128).map(naive_utc)
+}
+
+fn to_julian(dt: &PrimitiveDateTime) -> f64 {
+    let seconds = dt.assume_utc().unix_timestamp_nanos() as f64 / 1E9;
+    seconds / SECONDS_IN_DAY + EPOCH_IN_JULIAN_DAYS
+}
+
+// Values written by `unixepoch()` are stored as INTEGER and values written by
+// `julianday()` as REAL, text is left to the caller
+fn parse_numeric(value: &TursoValue) -> Option<deserialize::Result<PrimitiveDateTime>> {
+    if let Some(seconds) = value.as_integer() {
+        let dt = OffsetDateTime::from_unix_timestamp(seconds)
+            .map(naive_utc)
+            .map_err(|_| format!("Invalid unix timestamp {seconds}").into());
+        return Some(dt);
+    }
+    if let Some(julian_days) = value.as_real() {
+        let dt = parse_julian(julian_days)
+            .map_err(|_| format!("Invalid julian day {julian_days}").into());
+        return Some(dt);
+    }
+    None
+}
+
+fn parse_primitive_datetime(value: &TursoValue) -> deserialize::Result<PrimitiveDateTime> {
+    if let Some(dt) = parse_numeric(value) {
+        return dt;
+    }
+    let text = value
+        .as_text()
+        .ok_or_else(|| format!("Expected a datetime, but got {}", value.storage_class()))?;
+
+    for format in PRIMITIVE_DATETIME_FORMATS {
+        if let Ok(dt) = PrimitiveDateTime::parse(text, format) {
+            return Ok(dt);
+        }
+    }
+
+    if let Ok(julian_days) = text.parse::<f64>() {
+        if let Ok(timestamp) = parse_julian(julian_days) {
+            return Ok(timestamp);
+        }
+    }
+
+    Err(format!("Invalid datetime {text}").into())
+}
+
+impl FromSql<Date, TursoBackend> for NaiveDate {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        if let Some(dt) = parse_numeric(&value) {
+            return dt.map(|dt| dt.date());
+        }
+        let text = value
+            .as_text()
+            .ok_or_else(|| format!("Expected a date, but got {}", value.storage_class()))?;
+        Self::parse(text, DATE_FORMAT).map_err(Into::into)
+    }
+}
+
+impl ToSql<Date, TursoBackend> for NaiveDate {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(self.format(DATE_FORMAT).map_err(|err| err.to_string())?);
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Time, TursoBackend> for NaiveTime {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        if let Some(dt) = parse_numeric(&value) {
+            return dt.map(|dt| dt.time());
+        }
+        let text = value
+            .as_text()
+            .ok_or_else(|| format!("Expected a time, but got {}", value.storage_class()))?;
+
+        for format in TIME_FORMATS {
+            if let Ok(time) = Self::parse(text, format) {
+                return Ok(time);
+            }
+        }
+
+        Err(format!("Invalid time {text}").into())
+    }
+}
+
+impl ToSql<Time, TursoBackend> for NaiveTime {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        let format = if self.microsecond() == 0 {
+            ENCODE_TIME_FORMAT_WHOLE_SECOND
+        } else {
+            ENCODE_TIME_FORMAT_SUBSECOND
+        };
+        out.set_value(self.format(format).map_err(|err| err.to_string())?);
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Timestamp, TursoBackend> for PrimitiveDateTime {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        parse_primitive_datetime(&value)
+    }
+}
+
+impl ToSql<Timestamp, TursoBackend> for PrimitiveDateTime {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        let format = if self.nanosecond() == 0 {
+            ENCODE_PRIMITIVE_DATETIME_FORMAT_WHOLE_SECOND
+        } else {
+            ENCODE_PRIMITIVE_DATETIME_FORMAT_SUBSECOND
+        };
+        out.set_value(self.format(format).map_err(|err| err.to_string())?);
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Timestamptz, TursoBackend> for OffsetDateTime {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        // First try to parse the timezone
+        if let Some(text) = value.as_text() {
+            for format in DATETIME_FORMATS {
+                if let Ok(dt) = OffsetDateTime::parse(text, format) {
+                    return Ok(dt);
+                }
+            }
+        }
+
+        // Fallback on assuming UTC
+        let primitive_date_time = parse_primitive_datetime(&value)?;
+        Ok(primitive_date_time.assume_utc())
+    }
+}
+
+impl ToSql<Timestamptz, TursoBackend> for OffsetDateTime {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        // The offset is kept, so that it round trips
+        let format = if self.nanosecond() == 0 {
+            ENCODE_DATETIME_FORMAT_WHOLE_SECOND
+        } else {
+            ENCODE_DATETIME_FORMAT_SUBSECOND
+        };
+        out.set_value(self.format(format).map_err(|err| err.to_string())?);
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<TimestampUnix, TursoBackend> for PrimitiveDateTime {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        parse_primitive_datetime(&value)
+    }
+}
+
+impl ToSql<TimestampUnix, TursoBackend> for PrimitiveDateTime {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(self.assume_utc().unix_timestamp());
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<TimestampJulian, TursoBackend> for PrimitiveDateTime {
+    fn from_sql(value: <TursoBackend as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        parse_primitive_datetime(&value)
+    }
+}
+
+impl ToSql<TimestampJulian, TursoBackend> for PrimitiveDateTime {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(to_julian(self));
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::time::{
+        macros::{date, datetime},
+        Date as NaiveDate, Duration, OffsetDateTime, PrimitiveDateTime, Time as NaiveTime,
+    };
+
+    use super::naive_utc;
+
+    use diesel::dsl::{now, sql};
+    use diesel::sql_types::{BigInt, Date, Double, Text, Time, Timestamp};
+    use diesel::{select, ExpressionMethods, IntoSql, QueryDsl};
+    use diesel_async::RunQueryDsl;
+
+    use crate::dsl::{date, datetime, time};
+    use crate::sql_types::{TimestampJulian, TimestampUnix, Timestamptz};
+    use crate::tests::connection;
+
+    #[tokio::test]
+    async fn unix_epoch_encodes_correctly() {
+        let connection = &mut connection().await;
+        let time = datetime!(1970-1-1 0:0:0);
+        let query = select(datetime("1970-01-01 00:00:00.000000".into_sql::<Text>()).eq(time));
+        assert_eq!(Ok(true), query.get_result(connection).await);
+    }
+
+    #[tokio::test]
+    async fn unix_epoch_decodes_correctly_in_all_possible_formats() {
+        let connection = &mut connection().await;
+        let time = datetime!(1970-1-1 0:0:0);
+        let valid_epoch_formats = vec![
+            "1970-01-01 00:00",
+            "1970-01-01 00:00:00",
+            "1970-01-01 00:00:00.000",
+            "1970-01-01 00:00:00.000000",
+            "1970-01-01T00:00",
+            "1970-01-01T00:00:00",
+            "1970-01-01T00:00:00.000",
+            "1970-01-01T00:00:00.000000",
+            "1970-01-01 00:00Z",
+            "1970-01-01 00:00:00Z",
+            "1970-01-01 00:00:00.000Z",
+            "1970-01-01 00:00:00.000000Z",
+            "1970-01-01T00:00Z",
+            "1970-01-01T00:00:00Z",
+            "1970-01-01T00:00:00.000Z",
+            "1970-01-01T00:00:00.000000Z",
+            "1970-01-01 00:00+00:00",
+            "1970-01-01 00:00:00+00:00",
+            "1970-01-01 00:00:00.000+00:00",
+            "1970-01-01 00:00:00.000000+00:00",
+            "1970-01-01T00:00+00:00",
+            "1970-01-01T00:00:00+00:00",
+            "1970-01-01T00:00:00.000+00:00",
+            "1970-01-01T00:00:00.000000+00:00",
+            "1970-01-01 00:00+01:00",
+            "1970-01-01 00:00:00+01:00",
+            "1970-01-01 00:00:00.000+01:00",
+            "1970-01-01 00:00:00.000000+01:00",
+            "1970-01-01T00:00+01:00",
+            "1970-01-01T00:00:00+01:00",
+            "1970-01-01T00:00:00.000+01:00",
+            "1970-01-01T00:00:00.000000+01:00",
+            "1970-01-01T00:00-01:00",
+            "1970-01-01T00:00:00-01:00",
+            "1970-01-01T00:00:00.000-01:00",
+            "1970-01-01T00:00:00.000000-01:00",
+            "1970-01-01T00:00-01:00",
+            "1970-01-01T00:00:00-01:00",
+            "1970-01-01T00:00:00.000-01:00",
+            "1970-01-01T00:00:00.000000-01:00",
+            "2440587.5",
+        ];
+
+        for s in valid_epoch_formats {
+            let epoch_from_sql = select(sql::<Timestamp>(&format!("'{s}'")))
+                .get_result(connection)
+                .await;
+            assert_eq!(Ok(time), epoch_from_sql, "format {s} failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn times_relative_to_now_encode_correctly() {
+        let connection = &mut connection().await;
+        let time = naive_utc(OffsetDateTime::now_utc()) + Duration::seconds(60);
+        let query = select(now.lt(time));
+        assert_eq!(Ok(true), query.get_result(connection).await);
+
+        let time = naive_utc(OffsetDateTime::now_utc()) - Duration::seconds(600);
+        let query = select(now.gt(time));
+        assert_eq!(Ok(true), query.get_result(connection).await);
+    }
+
+    #[tokio::test]
+    async fn times_of_day_encode_correctly() {
+        let connection = &mut connection().await;
+
+        let midnight = NaiveTime::from_hms(0, 0, 0).unwrap();
+        let query = select(time("00:00:00".into_sql::<Text>()).eq(midnight));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let noon = NaiveTime::from_hms(12, 0, 0).unwrap();
+        let query = select(time("12:00:00".into_sql::<Text>()).eq(noon));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let roughly_half_past_eleven = NaiveTime::from_hms_micro(23, 37, 4, 2200).unwrap();
+        let query = select(sql::<Time>("'23:37:04.0022'").eq(roughly_half_past_eleven));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn times_of_day_decode_correctly() {
+        let connection = &mut connection().await;
+        let midnight = NaiveTime::from_hms(0, 0, 0).unwrap();
+        let valid_midnight_formats = &[
+            "00:00",
+            "00:00:00",
+            "00:00:00.000",
+            "00:00:00.000000",
+            "00:00Z",
+            "00:00:00Z",
+            "00:00:00.000Z",
+            "00:00:00.000000Z",
+            "00:00+00:00",
+            "00:00:00+00:00",
+            "00:00:00.000+00:00",
+            "00:00:00.000000+00:00",
+            "00:00+01:00",
+            "00:00:00+01:00",
+            "00:00:00.000+01:00",
+            "00:00:00.000000+01:00",
+            "00:00-01:00",
+            "00:00:00-01:00",
+            "00:00:00.000-01:00",
+            "00:00:00.000000-01:00",
+        ];
+        for format in valid_midnight_formats {
+            let query = select(sql::<Time>(&format!("'{format}'")));
+            assert_eq!(
+                Ok(midnight),
+                query.get_result::<NaiveTime>(connection).await,
+                "format {format} failed"
+            );
+        }
+
+        let noon = NaiveTime::from_hms(12, 0, 0).unwrap();
+        let query = select(sql::<Time>("'12:00:00'"));
+        assert_eq!(Ok(noon), query.get_result::<NaiveTime>(connection).await);
+
+        let roughly_half_past_eleven = NaiveTime::from_hms_micro(23, 37, 4, 2200).unwrap();
+        let query = select(sql::<Time>("'23:37:04.002200'"));
+        assert_eq!(
+            Ok(roughly_half_past_eleven),
+            query.get_result::<NaiveTime>(connection).await
+        );
+    }
+
+    #[tokio::test]
+    async fn dates_encode_correctly() {
+        let connection = &mut connection().await;
+        let january_first_2000 = date!(2000 - 1 - 1);
+        let query = select(date("2000-01-01".into_sql::<Text>()).eq(january_first_2000));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let distant_past = date!(0 - 4 - 11);
+        let query = select(date("0000-04-11".into_sql::<Text>()).eq(distant_past));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let january_first_2018 = date!(2018 - 1 - 1);
+        let query = select(date("2018-01-01".into_sql::<Text>()).eq(january_first_2018));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let distant_future = date!(9999 - 1 - 8);
+        let query = select(date("9999-01-08".into_sql::<Text>()).eq(distant_future));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn dates_decode_correctly() {
+        let connection = &mut connection().await;
+        let january_first_2000 = date!(2000 - 1 - 1);
+        let query = select(date("2000-01-01".into_sql::<Text>()));
+        assert_eq!(
+            Ok(january_first_2000),
+            query.get_result::<NaiveDate>(connection).await
+        );
+
+        let distant_past = date!(0 - 4 - 11);
+        let query = select(date("0000-04-11".into_sql::<Text>()));
+        assert_eq!(
+            Ok(distant_past),
+            query.get_result::<NaiveDate>(connection).await
+        );
+
+        let january_first_2018 = date!(2018 - 1 - 1);
+        let query = select(date("2018-01-01".into_sql::<Text>()));
+        assert_eq!(
+            Ok(january_first_2018),
+            query.get_result::<NaiveDate>(connection).await
+        );
+
+        let distant_future = date!(9999 - 1 - 8);
+        let query = select(date("9999-01-08".into_sql::<Text>()));
+        assert_eq!(
+            Ok(distant_future),
+            query.get_result::<NaiveDate>(connection).await
+        );
+    }
+
+    #[tokio::test]
+    async fn datetimes_decode_correctly() {
+        let connection = &mut connection().await;
+        let january_first_2000 = datetime!(2000-1-1 1:1:1);
+        let query = select(datetime("2000-01-01 01:01:01.000000".into_sql::<Text>()));
+        assert_eq!(
+            Ok(january_first_2000),
+            query.get_result::<PrimitiveDateTime>(connection).await
+        );
+
+        let distant_past = datetime!(0-4-11 2:2:2);
+        let query = select(datetime("0000-04-11 02:02:02.000000".into_sql::<Text>()));
+        assert_eq!(
+            Ok(distant_past),
+            query.get_result::<PrimitiveDateTime>(connection).await
+        );
+
+        let january_first_2018 = date!(2018 - 1 - 1);
+        let query = select(date("2018-01-01".into_sql::<Text>()));
+        assert_eq!(
+            Ok(january_first_2018),
+            query.get_result::<NaiveDate>(connection).await
+        );
+
+        let distant_future = datetime!(9999 - 1 - 8 23:59:59.0001);
+        let query = select(sql::<Timestamp>("'9999-01-08 23:59:59.000100'"));
+        assert_eq!(
+            Ok(distant_future),
+            query.get_result::<PrimitiveDateTime>(connection).await
+        );
+    }
+
+    #[tokio::test]
+    async fn datetimes_encode_correctly() {
+        let connection = &mut connection().await;
+        let january_first_2000 = datetime!(2000-1-1 0:0:0);
+        let query =
+            select(datetime("2000-01-01 00:00:00".into_sql::<Text>()).eq(january_first_2000));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let distant_past = datetime!(0-4-11 20:00:20);
+        let query = select(datetime("0000-04-11 20:00:20".into_sql::<Text>()).eq(distant_past));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let january_first_2018 = datetime!(2018 - 1 - 1 12:00:00.0005);
+        let query = select(sql::<Timestamp>("'2018-01-01 12:00:00.0005'").eq(january_first_2018));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        let distant_future = datetime!(9999-1-8 0:0:0);
+        let query = select(datetime("9999-01-08 00:00:00".into_sql::<Text>()).eq(distant_future));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn insert_timestamptz_into_table_as_text() {
+        diesel::table! {
+            use diesel::sql_types::*;
+            use crate::sql_types::Timestamptz;
+
+            #[allow(unused_parens)]
+            test_insert_timestamptz_into_table_as_text(id) {
+                id -> Integer,
+                timestamp_with_tz -> Timestamptz,
+            }
+        }
+        let conn = &mut connection().await;
+        diesel::sql_query(
+            "CREATE TABLE test_insert_timestamptz_into_table_as_text(id INTEGER PRIMARY KEY, timestamp_with_tz TEXT);",
+        )
+        .execute(conn)
+        .await
+        .unwrap();
+
+        let time: OffsetDateTime = datetime!(1970-1-1 0:0:0.0 utc);
+
+        diesel::insert_into(test_insert_timestamptz_into_table_as_text::table)
+            .values(vec![(
+                test_insert_timestamptz_into_table_as_text::id.eq(1),
+                test_insert_timestamptz_into_table_as_text::timestamp_with_tz
+                    .eq(sql::<Timestamptz>("'1970-01-01 00:00:00.000000+00:00'")),
+            )])
+            .execute(conn)
+            .await
+            .unwrap();
+
+        let result = test_insert_timestamptz_into_table_as_text::table
+            .select(test_insert_timestamptz_into_table_as_text::timestamp_with_tz)
+            .get_result::<OffsetDateTime>(conn)
+            .await
+            .unwrap();
+        assert_eq!(result, time);
+    }
+
+    #[tokio::test]
+    async fn can_query_timestamptz_column_with_between() {
+        diesel::table! {
+            use diesel::sql_types::*;
+            use crate::sql_types::Timestamptz;
+
+            #[allow(unused_parens)]
+            test_query_timestamptz_column_with_between(id) {
+                id -> Integer,
+                timestamp_with_tz -> Timestamptz,
+            }
+        }
+        let conn = &mut connection().await;
+        diesel::sql_query(
+            "CREATE TABLE test_query_timestamptz_column_with_between(id INTEGER PRIMARY KEY, timestamp_with_tz TEXT);",
+        )
+        .execute(conn)
+        .await
+        .unwrap();
+
+        diesel::insert_into(test_query_timestamptz_column_with_between::table)
+            .values(vec![
+                (
+                    test_query_timestamptz_column_with_between::id.eq(1),
+                    test_query_timestamptz_column_with_between::timestamp_with_tz
+                        .eq(datetime!(1970-1-1 0:0:1.0 utc)),
+                ),
+                (
+                    test_query_timestamptz_column_with_between::id.eq(2),
+                    test_query_timestamptz_column_with_between::timestamp_with_tz
+                        .eq(datetime!(1970-1-1 0:0:2.0 utc)),
+                ),
+                (
+                    test_query_timestamptz_column_with_between::id.eq(3),
+                    test_query_timestamptz_column_with_between::timestamp_with_tz
+                        .eq(datetime!(1970-1-1 0:0:3.0 utc)),
+                ),
+                (
+                    test_query_timestamptz_column_with_between::id.eq(4),
+                    test_query_timestamptz_column_with_between::timestamp_with_tz
+                        .eq(datetime!(1970-1-1 0:0:4.0 utc)),
+                ),
+            ])
+            .execute(conn)
+            .await
+            .unwrap();
+
+        let result = test_query_timestamptz_column_with_between::table
+            .select(test_query_timestamptz_column_with_between::timestamp_with_tz)
+            .filter(
+                test_query_timestamptz_column_with_between::timestamp_with_tz
+                    .gt(datetime!(1970-1-1 0:0:0.0 utc)),
+            )
+            .filter(
+                test_query_timestamptz_column_with_between::timestamp_with_tz
+                    .lt(datetime!(1970-1-1 0:0:4.0 utc)),
+            )
+            .count()
+            .get_result::<_>(conn)
+            .await;
+        assert_eq!(result, Ok(3));
+    }
+
+    #[tokio::test]
+    async fn unix_epoch_encodes_correctly_with_timezone() {
+        let connection = &mut connection().await;
+        // West one hour is negative offset
+        let time = datetime!(1970-1-1 0:00:00.001 -1:00);
+        let query = select(sql::<Timestamptz>("'1970-01-01T00:00:00.001-01:00'").eq(time));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn offset_round_trips() {
+        let connection = &mut connection().await;
+        let time = datetime!(2024-5-1 12:30:00.25 +2:00);
+        let query = select(sql::<Timestamptz>("").bind::<Timestamptz, _>(time));
+        let result = query
+            .get_result::<OffsetDateTime>(connection)
+            .await
+            .unwrap();
+        assert_eq!(time, result);
+        assert_eq!(time.offset(), result.offset());
+    }
+
+    #[tokio::test]
+    async fn unix_epoch_encodes_correctly_with_utc_timezone() {
+        let connection = &mut connection().await;
+        let time: OffsetDateTime = datetime!(1970-1-1 0:0:0.001 utc);
+        let query = select(sql::<Timestamptz>("'1970-01-01T00:00:00.001+00:00'").eq(time));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+
+        // and without millisecond
+        let time: OffsetDateTime = datetime!(1970-1-1 0:0:0 utc);
+        let query = select(sql::<Timestamptz>("'1970-01-01T00:00:00+00:00'").eq(time));
+        assert!(query.get_result::<bool>(connection).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn unix_epoch_decodes_correctly_with_utc_timezone_in_all_possible_formats() {
+        let connection = &mut connection().await;
+        let time: OffsetDateTime = datetime!(1970-1-1 0:0:0 utc);
+        let valid_epoch_formats = vec![
+            "1970-01-01 00:00Z",
+            "1970-01-01 00:00:00Z",
+            "1970-01-01 00:00:00.000Z",
+            "1970-01-01 00:00:00.000000Z",
+            "1970-01-01T00:00Z",
+            "1970-01-01T00:00:00Z",
+            "1970-01-01T00:00:00.000Z",
+            "1970-01-01T00:00:00.000000Z",
+            "1970-01-01 00:00+00:00",
+            "1970-01-01 00:00:00+00:00",
+            "1970-01-01 00:00:00.000+00:00",
+            "1970-01-01 00:00:00.000000+00:00",
+            "1970-01-01T00:00+00:00",
+            "1970-01-01T00:00:00+00:00",
+            "1970-01-01T00:00:00.000+00:00",
+            "1970-01-01T00:00:00.000000+00:00",
+            "2440587.5",
+        ];
+
+        for s in valid_epoch_formats {
+            let epoch_from_sql = select(sql::<Timestamptz>(&format!("'{s}'")))
+                .get_result(connection)
+                .await;
+            assert_eq!(Ok(time), epoch_from_sql, "format {s} failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn integer_and_real_storage_decodes_correctly() {
+        let connection = &mut connection().await;
+        let unix = datetime!(2021-9-27 17:44:23);
+        let julian = datetime!(1970-1-2 18:00:00);
+
+        let query = select(sql::<Timestamp>("1632764663"));
+        assert_eq!(
+            Ok(unix),
+            query.get_result::<PrimitiveDateTime>(connection).await
+        );
+        let query = select(sql::<Timestamp>("2440589.25"));
+        assert_eq!(
+            Ok(julian),
+            query.get_result::<PrimitiveDateTime>(connection).await
+        );
+
+        let query = select(sql::<Date>("1632764663"));
+        assert_eq!(
+            Ok(unix.date()),
+            query.get_result::<NaiveDate>(connection).await
+        );
+        let query = select(sql::<Time>("2440589.25"));
+        assert_eq!(
+            Ok(julian.time()),
+            query.get_result::<NaiveTime>(connection).await
+        );
+
+        let query = select(sql::<Timestamptz>("1632764663"));
+        assert_eq!(
+            Ok(unix.assume_utc()),
+            query.get_result::<OffsetDateTime>(connection).await
+        );
+    }
+
+    #[tokio::test]
+    async fn timestamps_encode_as_unix_and_julian() {
+        let connection = &mut connection().await;
+        let time = datetime!(1970-1-2 18:00:00);
+
+        let query = select(sql::<BigInt>("").bind::<TimestampUnix, _>(time));
+        assert_eq!(Ok(151_200), query.get_result::<i64>(connection).await);
+        let query = select(sql::<Double>("").bind::<TimestampJulian, _>(time));
+        assert_eq!(Ok(2_440_589.25), query.get_result::<f64>(connection).await);
+
+        let query = select(sql::<TimestampUnix>("").bind::<TimestampUnix, _>(time));
+        assert_eq!(
+            Ok(time),
+            query.get_result::<PrimitiveDateTime>(connection).await
+        );
+        let query = select(sql::<TimestampJulian>("").bind::<TimestampJulian, _>(time));
+        assert_eq!(
+            Ok(time),
+            query.get_result::<PrimitiveDateTime>(connection).await
+        );
+    }
+}
@@ -0,0 +1,446 @@
+//! Support for JSON and JSONB values
+//!
+//! Diesel only implements `AsExpression` and `FromSqlRow` for
+//! `serde_json::Value` together with one of its own backends, so they are
+//! provided here towards the [`Json`] and [`Jsonb`] types of this crate.
+
+extern crate serde_json;
+
+use diesel::deserialize::{self, FromSql, Queryable};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+
+use crate::sql_types::{foreign_as_expression, Json, Jsonb};
+use crate::{backend::TursoBackend, value::TursoValue};
+
+foreign_as_expression!(serde_json::Value => Json);
+foreign_as_expression!(serde_json::Value => Jsonb);
+
+impl FromSql<Json, TursoBackend> for serde_json::Value {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        serde_json::from_str(value.try_text()?).map_err(|_| "Invalid Json".into())
+    }
+}
+
+impl ToSql<Json, TursoBackend> for serde_json::Value {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(serde_json::to_string(self)?);
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<Json, TursoBackend> for serde_json::Value {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+impl FromSql<Jsonb, TursoBackend> for serde_json::Value {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        // The `jsonb*` functions accept JSON text as well, so a `Jsonb`
+        // column may hold text
+        if let Some(text) = value.as_text() {
+            return serde_json::from_str(text).map_err(|_| "Invalid Json".into());
+        }
+        let bytes = value.try_blob()?;
+        if bytes.is_empty() {
+            return Err("Empty blob cannot be decoded as JSONB".into());
+        }
+        jsonb::read(bytes)
+    }
+}
+
+impl ToSql<Jsonb, TursoBackend> for serde_json::Value {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        let mut buffer = Vec::new();
+        jsonb::write(self, &mut buffer, 0)?;
+        out.set_value(buffer);
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<Jsonb, TursoBackend> for serde_json::Value {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+/// The binary JSON format used by the `jsonb*` family of SQL functions
+///
+/// See <https://sqlite.org/jsonb.html> for the format description.
+mod jsonb {
+    use super::*;
+
+    const JSONB_NULL: u8 = 0x00;
+    const JSONB_TRUE: u8 = 0x01;
+    const JSONB_FALSE: u8 = 0x02;
+    const JSONB_INT: u8 = 0x03;
+    const JSONB_INT5: u8 = 0x04;
+    const JSONB_FLOAT: u8 = 0x05;
+    const JSONB_FLOAT5: u8 = 0x06;
+    const JSONB_TEXT: u8 = 0x07;
+    const JSONB_TEXTJ: u8 = 0x08;
+    const JSONB_TEXT5: u8 = 0x09;
+    const JSONB_TEXTRAW: u8 = 0x0A;
+    const JSONB_ARRAY: u8 = 0x0B;
+    const JSONB_OBJECT: u8 = 0x0C;
+
+    // Same nesting limit as the JSON functions of the database engine,
+    // it also keeps the recursive (de)serialization from overflowing the stack
+    const MAX_DEPTH: usize = 1000;
+
+    pub(super) fn read(bytes: &[u8]) -> deserialize::Result<serde_json::Value> {
+        let (value, size) = read_value(bytes, 0)?;
+        if size == bytes.len() {
+            Ok(value)
+        } else {
+            Err("Payload contained more bytes than the encoded JSONB".into())
+        }
+    }
+
+    // Returns the element type, the header size and the payload size
+    fn read_header(bytes: &[u8]) -> deserialize::Result<(u8, usize, usize)> {
+        let first_byte = *bytes.first().ok_or("Unexpected end of JSONB data")?;
+        let element_type = first_byte & 0x0F;
+        let header_size = match first_byte >> 4 {
+            0x0..=0xB => 1,
+            0xC => 2,
+            0xD => 3,
+            0xE => 5,
+            _ => 9,
+        };
+        let size_bytes = bytes
+            .get(1..header_size)
+            .ok_or("Invalid JSONB data: insufficient bytes for payload size")?;
+        let payload_size = if header_size == 1 {
+            usize::from(first_byte >> 4)
+        } else {
+            let size = size_bytes
+                .iter()
+                .fold(0_u64, |size, byte| (size << 8) | u64::from(*byte));
+            usize::try_from(size)?
+        };
+        if bytes.len() - header_size < payload_size {
+            return Err(format!(
+                "Invalid JSONB data: expected {payload_size} bytes of payload, got {}",
+                bytes.len() - header_size
+            )
+            .into());
+        }
+        Ok((element_type, header_size, payload_size))
+    }
+
+    // Returns the decoded value and the number of bytes it occupied
+    fn read_value(bytes: &[u8], depth: usize) -> deserialize::Result<(serde_json::Value, usize)> {
+        if depth > MAX_DEPTH {
+            return Err("JSONB value is nested too deeply".into());
+        }
+        let (element_type, header_size, payload_size) = read_header(bytes)?;
+        let payload = &bytes[header_size..header_size + payload_size];
+        let value = match element_type {
+            JSONB_NULL => serde_json::Value::Null,
+            JSONB_TRUE => serde_json::Value::Bool(true),
+            JSONB_FALSE => serde_json::Value::Bool(false),
+            JSONB_INT | JSONB_FLOAT => serde_json::from_str(std::str::from_utf8(payload)?)
+                .map_err(|_| "Invalid number in JSONB")?,
+            JSONB_INT5 => read_int5(std::str::from_utf8(payload)?)?,
+            JSONB_FLOAT5 => read_float5(std::str::from_utf8(payload)?)?,
+            JSONB_TEXT | JSONB_TEXTRAW => {
+                serde_json::Value::String(std::str::from_utf8(payload)?.to_owned())
+            }
+            JSONB_TEXTJ => serde_json::from_str(&format!("\"{}\"", std::str::from_utf8(payload)?))
+                .map_err(|_| "Invalid escape sequence in JSONB text")?,
+            JSONB_TEXT5 => return Err("JSON5 text is not supported".into()),
+            JSONB_ARRAY => {
+                let mut values = Vec::new();
+                let mut offset = 0;
+                while offset < payload.len() {
+                    let (value, size) = read_value(&payload[offset..], depth + 1)?;
+                    values.push(value);
+                    offset += size;
+                }
+                serde_json::Value::Array(values)
+            }
+            JSONB_OBJECT => {
+                let mut object = serde_json::Map::new();
+                let mut offset = 0;
+                while offset < payload.len() {
+                    let (key, size) = read_value(&payload[offset..], depth + 1)?;
+                    let serde_json::Value::String(key) = key else {
+                        return Err("Expected a string as object key".into());
+                    };
+                    offset += size;
+                    if offset == payload.len() {
+                        return Err("No value found for object key".into());
+                    }
+                    let (value, size) = read_value(&payload[offset..], depth + 1)?;
+                    object.insert(key, value);
+                    offset += size;
+                }
+                serde_json::Value::Object(object)
+            }
+            _ => return Err(format!("Unsupported or reserved JSONB type: {element_type}").into()),
+        };
+        Ok((value, header_size + payload_size))
+    }
+
+    // JSON5 integers may use a leading `+` and hexadecimal notation
+    fn read_int5(text: &str) -> deserialize::Result<serde_json::Value> {
+        let (negative, digits) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let value = match digits
+            .strip_prefix("0x")
+            .or_else(|| digits.strip_prefix("0X"))
+        {
+            Some(hex) => i64::from_str_radix(hex, 16)?,
+            None => digits.parse::<i64>()?,
+        };
+        let value = if negative { -value } else { value };
+        Ok(value.into())
+    }
+
+    // JSON5 floats may omit digits around the decimal point and use `Infinity`/`NaN`,
+    // which cannot be represented by `serde_json`
+    fn read_float5(text: &str) -> deserialize::Result<serde_json::Value> {
+        let value = text.parse::<f64>()?;
+        serde_json::Number::from_f64(value)
+            .map(serde_json::Value::Number)
+            .ok_or_else(|| format!("Cannot represent {text} as JSON number").into())
+    }
+
+    fn write_header(buffer: &mut Vec<u8>, element_type: u8, payload_size: usize) {
+        if payload_size <= 0x0B {
+            // The size fits into the upper nibble of the first byte
+            buffer.push(((payload_size as u8) << 4) | element_type);
+        } else if let Ok(size) = u8::try_from(payload_size) {
+            buffer.extend_from_slice(&[(0x0C << 4) | element_type, size]);
+        } else if let Ok(size) = u16::try_from(payload_size) {
+            buffer.push((0x0D << 4) | element_type);
+            buffer.extend_from_slice(&size.to_be_bytes());
+        } else if let Ok(size) = u32::try_from(payload_size) {
+            buffer.push((0x0E << 4) | element_type);
+            buffer.extend_from_slice(&size.to_be_bytes());
+        } else {
+            buffer.push((0x0F << 4) | element_type);
+            buffer.extend_from_slice(&(payload_size as u64).to_be_bytes());
+        }
+    }
+
+    fn write_payload(buffer: &mut Vec<u8>, element_type: u8, payload: &[u8]) {
+        write_header(buffer, element_type, payload.len());
+        buffer.extend_from_slice(payload);
+    }
+
+    pub(super) fn write(
+        value: &serde_json::Value,
+        buffer: &mut Vec<u8>,
+        depth: usize,
+    ) -> serialize::Result {
+        if depth > MAX_DEPTH {
+            return Err("JSON value is nested too deeply".into());
+        }
+        match value {
+            serde_json::Value::Null => write_header(buffer, JSONB_NULL, 0),
+            serde_json::Value::Bool(true) => write_header(buffer, JSONB_TRUE, 0),
+            serde_json::Value::Bool(false) => write_header(buffer, JSONB_FALSE, 0),
+            serde_json::Value::Number(number) => {
+                let element_type = if number.is_f64() {
+                    JSONB_FLOAT
+                } else {
+                    JSONB_INT
+                };
+                write_payload(buffer, element_type, number.to_string().as_bytes());
+            }
+            serde_json::Value::String(text) => write_string(buffer, text),
+            serde_json::Value::Array(values) => {
+                let mut payload = Vec::new();
+                for value in values {
+                    write(value, &mut payload, depth + 1)?;
+                }
+                write_payload(buffer, JSONB_ARRAY, &payload);
+            }
+            serde_json::Value::Object(object) => {
+                let mut payload = Vec::new();
+                for (key, value) in object {
+                    write_string(&mut payload, key);
+                    write(value, &mut payload, depth + 1)?;
+                }
+                write_payload(buffer, JSONB_OBJECT, &payload);
+            }
+        }
+        Ok(IsNull::No)
+    }
+
+    fn write_string(buffer: &mut Vec<u8>, text: &str) {
+        // TEXTRAW holds text that would need escaping when rendered as JSON
+        let needs_escaping = text
+            .chars()
+            .any(|c| c == '"' || c == '\\' || c.is_control());
+        let element_type = if needs_escaping {
+            JSONB_TEXTRAW
+        } else {
+            JSONB_TEXT
+        };
+        write_payload(buffer, element_type, text.as_bytes());
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use diesel::dsl::sql;
+    use diesel::sql_types::{Integer, Text};
+    use diesel::{select, ExpressionMethods, IntoSql, QueryDsl};
+    use diesel_async::RunQueryDsl;
+    use serde_json::json;
+
+    use crate::dsl::TursoJsonExpressionMethods;
+    use crate::sql_types::{Json, Jsonb};
+    use crate::tests::{connection, users};
+
+    #[tokio::test]
+    async fn jsonb_created_by_the_database_decodes_correctly() {
+        let connection = &mut connection().await;
+        let value = select(sql::<Jsonb>(
+            r#"jsonb('{"a": [1, 2.5, "x\"y", null, true, false], "b": {"c": "d"}}')"#,
+        ))
+        .get_result::<serde_json::Value>(connection)
+        .await;
+        assert_eq!(
+            Ok(json!({"a": [1, 2.5, "x\"y", null, true, false], "b": {"c": "d"}})),
+            value
+        );
+    }
+
+    #[tokio::test]
+    async fn values_of_other_storage_classes_decode_or_fail() {
+        let connection = &mut connection().await;
+        let value = select(sql::<Jsonb>(r#"'{"a": 1}'"#))
+            .get_result::<serde_json::Value>(connection)
+            .await;
+        assert_eq!(Ok(json!({"a": 1})), value);
+
+        let value = select(sql::<Json>("1"))
+            .get_result::<serde_json::Value>(connection)
+            .await;
+        assert!(value.is_err());
+        let value = select(sql::<Jsonb>("1.5"))
+            .get_result::<serde_json::Value>(connection)
+            .await;
+        assert!(value.is_err());
+    }
+
+    #[tokio::test]
+    async fn jsonb_encodes_to_a_value_understood_by_the_database() {
+        let connection = &mut connection().await;
+        let value = json!({"a": [1, 2.5, "x\"y", null, true, false], "b": {"c": "d"}});
+        let text = select(
+            sql::<Text>("json(")
+                .bind::<Jsonb, _>(value.clone())
+                .sql(")"),
+        )
+        .get_result::<String>(connection)
+        .await
+        .unwrap();
+        assert_eq!(
+            value,
+            serde_json::from_str::<serde_json::Value>(&text).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn retrieve_json_paths() {
+        let connection = &mut connection().await;
+        let value = json!({"name": "Ferris", "address": {"city": "Berlin"}});
+
+        let city = select(
+            value
+                .clone()
+                .into_sql::<Json>()
+                .retrieve_as_text("$.address.city"),
+        )
+        .get_result::<Option<String>>(connection)
+        .await;
+        assert_eq!(Ok(Some("Berlin".to_owned())), city);
+
+        let address = select(
+            value
+                .clone()
+                .into_sql::<Json>()
+                .retrieve_as_object("$.address"),
+        )
+        .get_result::<Option<serde_json::Value>>(connection)
+        .await;
+        assert_eq!(Ok(Some(json!({"city": "Berlin"}))), address);
+
+        let name = select(
+            value
+                .clone()
+                .into_sql::<Json>()
+                .json_extract::<Text, _>("$.name"),
+        )
+        .get_result::<Option<String>>(connection)
+        .await;
+        assert_eq!(Ok(Some("Ferris".to_owned())), name);
+
+        let missing = select(
+            value
+                .into_sql::<Json>()
+                .json_extract::<Text, _>("$.missing"),
+        )
+        .get_result::<Option<String>>(connection)
+        .await;
+        assert_eq!(Ok(None), missing);
+    }
+
+    #[tokio::test]
+    async fn json_set_inserts_and_replaces_values() {
+        let connection = &mut connection().await;
+        let value = select(
+            json!({"name": "Ferris"})
+                .into_sql::<Json>()
+                .json_set("$.tags", json!(["crab"])),
+        )
+        .get_result::<serde_json::Value>(connection)
+        .await;
+        assert_eq!(Ok(json!({"name": "Ferris", "tags": ["crab"]})), value);
+    }
+
+    #[tokio::test]
+    async fn json_each_can_be_used_with_eq_any() {
+        let connection = &mut connection().await;
+        for name in ["Alice", "Bob", "Charlie"] {
+            diesel::insert_into(users::table)
+                .values(users::name.eq(name))
+                .execute(connection)
+                .await
+                .unwrap();
+        }
+        let ids = users::table
+            .select(users::id)
+            .order(users::id)
+            .load::<i32>(connection)
+            .await
+            .unwrap();
+
+        let selected = users::table
+            .select(users::id)
+            .filter(
+                users::id.eq_any(
+                    json!([ids[0], ids[2]])
+                        .into_sql::<Json>()
+                        .json_each::<Integer>(),
+                ),
+            )
+            .order(users::id)
+            .load::<i32>(connection)
+            .await;
+        assert_eq!(Ok(vec![ids[0], ids[2]]), selected);
+    }
+}
@@ -10,6 +10,13 @@ use crate::{
 };
 
 mod date_and_time;
+#[cfg(feature = "serde_json")]
+mod json;
+#[cfg(any(feature = "bigdecimal", feature = "rust_decimal"))]
+mod numeric;
+#[cfg(feature = "uuid")]
+mod uuid;
+mod vector;
 
 // VarChar is just an alias for Text in diesel, so we only need Text implementations
 
@@ -109,6 +116,108 @@ impl ToSql<sql_types::BigInt, TursoBackend> for i64 {
 
 // ------
 
+// TinyInt and unsigned integers, range checked as they share the 64 bit
+// storage with every other integer
+
+fn read_in_range<T>(value: &TursoValue, sql_type: &str) -> deserialize::Result<T>
+where
+    T: TryFrom<i64>,
+{
+    let int = value.as_integer().ok_or_else(|| {
+        format!(
+            "Expected an integer for {sql_type}, but got {}",
+            value.storage_class()
+        )
+    })?;
+    T::try_from(int).map_err(|_| format!("Integer {int} is out of range for {sql_type}").into())
+}
+
+impl HasSqlType<sql_types::TinyInt> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Integer
+    }
+}
+
+impl FromSql<sql_types::TinyInt, TursoBackend> for i8 {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        read_in_range(&value, "TinyInt")
+    }
+}
+
+impl ToSql<sql_types::TinyInt, TursoBackend> for i8 {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(i64::from(*self));
+        Ok(IsNull::No)
+    }
+}
+
+impl HasSqlType<sql_types::Unsigned<sql_types::SmallInt>> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Integer
+    }
+}
+
+impl FromSql<sql_types::Unsigned<sql_types::SmallInt>, TursoBackend> for u16 {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        read_in_range(&value, "Unsigned<SmallInt>")
+    }
+}
+
+impl ToSql<sql_types::Unsigned<sql_types::SmallInt>, TursoBackend> for u16 {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(i64::from(*self));
+        Ok(IsNull::No)
+    }
+}
+
+impl HasSqlType<sql_types::Unsigned<sql_types::Integer>> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Integer
+    }
+}
+
+impl FromSql<sql_types::Unsigned<sql_types::Integer>, TursoBackend> for u32 {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        read_in_range(&value, "Unsigned<Integer>")
+    }
+}
+
+impl ToSql<sql_types::Unsigned<sql_types::Integer>, TursoBackend> for u32 {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(i64::from(*self));
+        Ok(IsNull::No)
+    }
+}
+
+impl HasSqlType<sql_types::Unsigned<sql_types::BigInt>> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Integer
+    }
+}
+
+impl FromSql<sql_types::Unsigned<sql_types::BigInt>, TursoBackend> for u64 {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        read_in_range(&value, "Unsigned<BigInt>")
+    }
+}
+
+impl ToSql<sql_types::Unsigned<sql_types::BigInt>, TursoBackend> for u64 {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        // Integers are stored as signed 64 bit, larger values would wrap around
+        let int = i64::try_from(*self).map_err(|_| {
+            format!(
+                "Unsigned<BigInt> value {self} is larger than {}, \
+                 the largest integer that can be stored",
+                i64::MAX
+            )
+        })?;
+        out.set_value(int);
+        Ok(IsNull::No)
+    }
+}
+
+// ------
+
 // Float
 
 impl HasSqlType<sql_types::Float> for TursoBackend {
@@ -200,6 +309,34 @@ impl HasSqlType<sql_types::Binary> for TursoBackend {
     }
 }
 
+// Numeric (stored as text to keep every digit, see `crate::sql_types::NumericReal`)
+
+impl HasSqlType<sql_types::Numeric> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Text
+    }
+}
+
+impl HasSqlType<crate::sql_types::NumericReal> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Double
+    }
+}
+
+// Vectors (stored as blobs in the format of the vector functions)
+
+impl HasSqlType<crate::sql_types::Vector32> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Binary
+    }
+}
+
+impl HasSqlType<crate::sql_types::Vector64> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Binary
+    }
+}
+
 // ------ Time related (simplified to only text)
 
 impl HasSqlType<sql_types::Date> for TursoBackend {
@@ -219,3 +356,37 @@ impl HasSqlType<sql_types::Timestamp> for TursoBackend {
         TursoType::Text
     }
 }
+
+impl HasSqlType<crate::sql_types::Timestamptz> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Text
+    }
+}
+
+impl HasSqlType<crate::sql_types::TimestampUnix> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Integer
+    }
+}
+
+impl HasSqlType<crate::sql_types::TimestampJulian> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Double
+    }
+}
+
+// ------ Json (stored as text, jsonb as the binary format of the json functions)
+
+#[cfg(feature = "serde_json")]
+impl HasSqlType<crate::sql_types::Json> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Text
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl HasSqlType<crate::sql_types::Jsonb> for TursoBackend {
+    fn metadata(_lookup: &mut ()) -> TursoType {
+        TursoType::Binary
+    }
+}
@@ -0,0 +1,252 @@
+//! Support for `bigdecimal::BigDecimal`
+//!
+//! Diesel already provides `AsExpression<Numeric>` and `FromSqlRow` for
+//! `BigDecimal` with its `numeric` feature.
+
+extern crate bigdecimal;
+
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Numeric;
+
+use super::{decode_real, encode_real};
+use crate::sql_types::{foreign_as_expression, NumericReal};
+use crate::{backend::TursoBackend, value::TursoValue};
+
+foreign_as_expression!(BigDecimal => NumericReal);
+
+fn parse(text: &str) -> deserialize::Result<BigDecimal> {
+    BigDecimal::from_str(text.trim())
+        .map_err(|e| format!("Invalid decimal number `{text}`: {e}").into())
+}
+
+fn decode(value: TursoValue) -> deserialize::Result<BigDecimal> {
+    if let Some(text) = value.as_text() {
+        parse(text)
+    } else if let Some(int) = value.as_integer() {
+        Ok(BigDecimal::from(int))
+    } else if let Some(real) = value.as_real() {
+        parse(&decode_real(real)?)
+    } else {
+        Err(format!(
+            "Expected a decimal number, but got {}",
+            value.storage_class()
+        )
+        .into())
+    }
+}
+
+impl FromSql<Numeric, TursoBackend> for BigDecimal {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        decode(value)
+    }
+}
+
+impl ToSql<Numeric, TursoBackend> for BigDecimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(self.to_plain_string());
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<NumericReal, TursoBackend> for BigDecimal {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        decode(value)
+    }
+}
+
+impl ToSql<NumericReal, TursoBackend> for BigDecimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(encode_real(&self.to_plain_string())?);
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use bigdecimal::BigDecimal;
+    use diesel::dsl::sql;
+    use diesel::sql_types::{Double, Numeric, Text};
+    use diesel::{select, ExpressionMethods, IntoSql, QueryDsl};
+    use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+
+    use crate::sql_types::NumericReal;
+
+    table! {
+        accounts {
+            id -> Integer,
+            balance -> Numeric,
+        }
+    }
+
+    table! {
+        ledger {
+            id -> Integer,
+            balance -> Numeric,
+        }
+    }
+
+    #[tokio::test]
+    async fn numeric_is_stored_as_text() {
+        let connection = &mut crate::tests::connection().await;
+
+        let value = BigDecimal::from_str("12345678901234567890.000012345").unwrap();
+        let (stored, kind) = select((
+            value.clone().into_sql::<Numeric>(),
+            sql::<Text>("typeof(")
+                .bind::<Numeric, _>(value.clone())
+                .sql(")"),
+        ))
+        .get_result::<(BigDecimal, String)>(connection)
+        .await
+        .unwrap();
+        assert_eq!(stored, value);
+        assert_eq!(kind, "text");
+
+        let text =
+            select(sql::<Text>("").bind::<Numeric, _>(BigDecimal::from_str("1.50").unwrap()))
+                .get_result::<String>(connection)
+                .await
+                .unwrap();
+        assert_eq!(text, "1.50");
+    }
+
+    #[tokio::test]
+    async fn numeric_table_round_trip() {
+        let connection = &mut crate::tests::connection().await;
+        connection
+            .batch_execute(
+                "CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance NUMERIC NOT NULL)",
+            )
+            .await
+            .unwrap();
+
+        let balance = BigDecimal::from_str("-1000000000000000000.01").unwrap();
+        diesel::insert_into(accounts::table)
+            .values((accounts::id.eq(1), accounts::balance.eq(&balance)))
+            .execute(connection)
+            .await
+            .unwrap();
+
+        let loaded = accounts::table
+            .select(accounts::balance)
+            .filter(accounts::id.eq(1))
+            .get_result::<BigDecimal>(connection)
+            .await
+            .unwrap();
+        assert_eq!(loaded, balance);
+    }
+
+    #[tokio::test]
+    async fn text_column_keeps_scale() {
+        let connection = &mut crate::tests::connection().await;
+        connection
+            .batch_execute(
+                "CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance NUMERIC NOT NULL);
+                 CREATE TABLE ledger (id INTEGER PRIMARY KEY, balance TEXT NOT NULL)",
+            )
+            .await
+            .unwrap();
+
+        let balance = BigDecimal::from_str("2.50").unwrap();
+        diesel::insert_into(accounts::table)
+            .values((accounts::id.eq(1), accounts::balance.eq(&balance)))
+            .execute(connection)
+            .await
+            .unwrap();
+        diesel::insert_into(ledger::table)
+            .values((ledger::id.eq(1), ledger::balance.eq(&balance)))
+            .execute(connection)
+            .await
+            .unwrap();
+
+        // NUMERIC affinity converts the short decimal to REAL
+        let (numeric, kind) = accounts::table
+            .select((accounts::balance, sql::<Text>("typeof(balance)")))
+            .get_result::<(BigDecimal, String)>(connection)
+            .await
+            .unwrap();
+        assert_eq!(numeric.to_string(), "2.5");
+        assert_eq!(kind, "real");
+
+        let (text, kind) = ledger::table
+            .select((ledger::balance, sql::<Text>("typeof(balance)")))
+            .get_result::<(BigDecimal, String)>(connection)
+            .await
+            .unwrap();
+        assert_eq!(text.to_string(), "2.50");
+        assert_eq!(kind, "text");
+    }
+
+    #[tokio::test]
+    async fn numeric_decodes_integer_and_real() {
+        let connection = &mut crate::tests::connection().await;
+
+        let int = select(sql::<Numeric>("CAST(42 AS INTEGER)"))
+            .get_result::<BigDecimal>(connection)
+            .await
+            .unwrap();
+        assert_eq!(int, BigDecimal::from(42));
+
+        let real = select(sql::<Numeric>("").bind::<Double, _>(0.1))
+            .get_result::<BigDecimal>(connection)
+            .await
+            .unwrap();
+        assert_eq!(real, BigDecimal::from_str("0.1").unwrap());
+    }
+
+    #[tokio::test]
+    async fn numeric_detects_lossy_real() {
+        let connection = &mut crate::tests::connection().await;
+
+        let lossy = select(sql::<Numeric>("").bind::<Double, _>(0.1 + 0.2))
+            .get_result::<BigDecimal>(connection)
+            .await;
+        assert!(lossy.is_err());
+
+        let nan = select(sql::<Numeric>("").bind::<Double, _>(f64::INFINITY))
+            .get_result::<BigDecimal>(connection)
+            .await;
+        assert!(nan.is_err());
+
+        let blob = select(sql::<Numeric>("x'01'"))
+            .get_result::<BigDecimal>(connection)
+            .await
+            .unwrap_err();
+        assert!(
+            blob.to_string()
+                .contains("Expected a decimal number, but got BLOB"),
+            "{blob}"
+        );
+    }
+
+    #[tokio::test]
+    async fn numeric_real_mode() {
+        let connection = &mut crate::tests::connection().await;
+
+        let value = BigDecimal::from_str("19.99").unwrap();
+        let (stored, kind) = select((
+            value.clone().into_sql::<NumericReal>(),
+            sql::<Text>("typeof(")
+                .bind::<NumericReal, _>(value.clone())
+                .sql(")"),
+        ))
+        .get_result::<(BigDecimal, String)>(connection)
+        .await
+        .unwrap();
+        assert_eq!(stored, value);
+        assert_eq!(kind, "real");
+
+        let too_precise = BigDecimal::from_str("1234567890.1234567890").unwrap();
+        let result = select(too_precise.into_sql::<NumericReal>())
+            .get_result::<BigDecimal>(connection)
+            .await;
+        assert!(result.is_err());
+    }
+}
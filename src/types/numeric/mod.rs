@@ -0,0 +1,71 @@
+//! Support for decimal numbers on `Numeric` columns
+//!
+//! Decimals are bound as TEXT, which keeps every digit, or as REAL with the
+//! [`NumericReal`](crate::sql_types::NumericReal) SQL type. Decoding accepts
+//! all three storage classes, but refuses REAL values that carry more digits
+//! than a REAL can represent exactly.
+//!
+//! The affinity of the column decides how the TEXT is stored. A `NUMERIC`
+//! column converts decimals which fit into an INTEGER or REAL, so `2.50` reads
+//! back as `2.5` and only longer decimals stay TEXT. Declare the column as
+//! `TEXT` to keep the scale and every digit of all decimals.
+
+use diesel::deserialize;
+use diesel::serialize;
+
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal;
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal;
+
+/// Every decimal with up to this many significant digits survives the round
+/// trip through a REAL
+const REAL_DIGITS: usize = f64::DIGITS as usize;
+
+/// Counts the significant digits of a decimal in plain or exponent notation
+fn significant_digits(text: &str) -> usize {
+    let mantissa = text
+        .split(['e', 'E'])
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(['-', '+']);
+    let digits = mantissa.replace('.', "");
+    digits.trim_start_matches('0').trim_end_matches('0').len()
+}
+
+/// Returns the shortest text which reads back as the given REAL
+///
+/// Fails if the REAL has more significant digits than it can hold exactly,
+/// in which case the stored value is likely not the one which was written.
+fn decode_real(value: f64) -> deserialize::Result<String> {
+    if !value.is_finite() {
+        return Err(format!("{value} is not a valid decimal number").into());
+    }
+    // `Display` of `f64` never uses an exponent
+    let text = value.to_string();
+    if significant_digits(&text) > REAL_DIGITS {
+        return Err(format!(
+            "REAL value {text} has more than {REAL_DIGITS} significant digits \
+             and cannot be read as decimal without loss of precision"
+        )
+        .into());
+    }
+    Ok(text)
+}
+
+/// Converts a decimal into a REAL, failing if the decimal is not read back
+/// unchanged
+fn encode_real(text: &str) -> serialize::Result<f64> {
+    let value = text
+        .parse::<f64>()
+        .map_err(|_| format!("{text} is not representable as REAL"))?;
+    let exact = value.is_finite() && significant_digits(text) <= REAL_DIGITS;
+    if !exact {
+        return Err(format!(
+            "{text} has more than {REAL_DIGITS} significant digits \
+             and cannot be stored as REAL without loss of precision"
+        )
+        .into());
+    }
+    Ok(value)
+}
@@ -0,0 +1,204 @@
+//! Support for `rust_decimal::Decimal`
+//!
+//! Diesel has no support for `rust_decimal`, so `Queryable` is provided here.
+//! `AsExpression<Numeric>` can't be implemented for `Decimal` outside of
+//! diesel, binding a `Decimal` as `Numeric` requires a local type deriving
+//! `AsExpression` that delegates to these implementations. Binding as
+//! [`NumericReal`] works directly.
+
+extern crate rust_decimal;
+
+use diesel::deserialize::{self, FromSql, Queryable};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Numeric;
+use rust_decimal::Decimal;
+
+use super::{decode_real, encode_real};
+use crate::sql_types::{foreign_as_expression, NumericReal};
+use crate::{backend::TursoBackend, value::TursoValue};
+
+foreign_as_expression!(Decimal => NumericReal);
+
+fn parse(text: &str) -> deserialize::Result<Decimal> {
+    // `from_str_exact` fails instead of rounding digits which don't fit
+    Decimal::from_str_exact(text.trim())
+        .map_err(|e| format!("Invalid decimal number `{text}`: {e}").into())
+}
+
+fn decode(value: TursoValue) -> deserialize::Result<Decimal> {
+    if let Some(text) = value.as_text() {
+        parse(text)
+    } else if let Some(int) = value.as_integer() {
+        Ok(Decimal::from(int))
+    } else if let Some(real) = value.as_real() {
+        parse(&decode_real(real)?)
+    } else {
+        Err(format!(
+            "Expected a decimal number, but got {}",
+            value.storage_class()
+        )
+        .into())
+    }
+}
+
+impl FromSql<Numeric, TursoBackend> for Decimal {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        decode(value)
+    }
+}
+
+impl ToSql<Numeric, TursoBackend> for Decimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(self.to_string());
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<Numeric, TursoBackend> for Decimal {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+impl FromSql<NumericReal, TursoBackend> for Decimal {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        decode(value)
+    }
+}
+
+impl ToSql<NumericReal, TursoBackend> for Decimal {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(encode_real(&self.to_string())?);
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<NumericReal, TursoBackend> for Decimal {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use std::str::FromStr;
+
+    use diesel::dsl::sql;
+    use diesel::expression::AsExpression;
+    use diesel::serialize::{self, Output, ToSql};
+    use diesel::sql_types::{Double, Numeric, Text};
+    use diesel::{select, ExpressionMethods, IntoSql, QueryDsl};
+    use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+    use rust_decimal::Decimal;
+
+    use crate::backend::TursoBackend;
+    use crate::sql_types::NumericReal;
+
+    #[derive(Debug, AsExpression)]
+    #[diesel(sql_type = Numeric)]
+    struct BoundDecimal(Decimal);
+
+    impl ToSql<Numeric, TursoBackend> for BoundDecimal {
+        fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+            <Decimal as ToSql<Numeric, TursoBackend>>::to_sql(&self.0, out)
+        }
+    }
+
+    table! {
+        prices {
+            id -> Integer,
+            amount -> Numeric,
+        }
+    }
+
+    #[tokio::test]
+    async fn decimal_round_trip() {
+        let connection = &mut crate::tests::connection().await;
+        connection
+            .batch_execute("CREATE TABLE prices (id INTEGER PRIMARY KEY, amount NUMERIC NOT NULL)")
+            .await
+            .unwrap();
+
+        let amount = Decimal::from_str("79228162514264337593543950.335").unwrap();
+        diesel::insert_into(prices::table)
+            .values((prices::id.eq(1), prices::amount.eq(BoundDecimal(amount))))
+            .execute(connection)
+            .await
+            .unwrap();
+
+        let (loaded, kind) = prices::table
+            .select((prices::amount, sql::<Text>("typeof(amount)")))
+            .get_result::<(Decimal, String)>(connection)
+            .await
+            .unwrap();
+        assert_eq!(loaded, amount);
+        assert_eq!(kind, "text");
+    }
+
+    #[tokio::test]
+    async fn decimal_decodes_all_storage_classes() {
+        let connection = &mut crate::tests::connection().await;
+
+        let text = select(sql::<Numeric>("'2.50'"))
+            .get_result::<Decimal>(connection)
+            .await
+            .unwrap();
+        assert_eq!(text.to_string(), "2.50");
+
+        let int = select(sql::<Numeric>("-7"))
+            .get_result::<Decimal>(connection)
+            .await
+            .unwrap();
+        assert_eq!(int, Decimal::from(-7));
+
+        let real = select(sql::<Numeric>("").bind::<Double, _>(2.5))
+            .get_result::<Decimal>(connection)
+            .await
+            .unwrap();
+        assert_eq!(real, Decimal::from_str("2.5").unwrap());
+    }
+
+    #[tokio::test]
+    async fn decimal_detects_precision_loss() {
+        let connection = &mut crate::tests::connection().await;
+
+        let lossy = select(sql::<Numeric>("").bind::<Double, _>(0.1 + 0.2))
+            .get_result::<Decimal>(connection)
+            .await;
+        assert!(lossy.is_err());
+
+        let too_long = select(sql::<Numeric>("'0.12345678901234567890123456789012'"))
+            .get_result::<Decimal>(connection)
+            .await;
+        assert!(too_long.is_err());
+    }
+
+    #[tokio::test]
+    async fn decimal_real_mode() {
+        let connection = &mut crate::tests::connection().await;
+
+        let value = Decimal::from_str("-0.125").unwrap();
+        let (stored, kind) = select((
+            value.into_sql::<NumericReal>(),
+            sql::<Text>("typeof(")
+                .bind::<NumericReal, _>(value)
+                .sql(")"),
+        ))
+        .get_result::<(Decimal, String)>(connection)
+        .await
+        .unwrap();
+        assert_eq!(stored, value);
+        assert_eq!(kind, "real");
+
+        let too_precise = Decimal::from_str("1.0000000000000000001").unwrap();
+        let result = select(too_precise.into_sql::<NumericReal>())
+            .get_result::<Decimal>(connection)
+            .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Support for `uuid::Uuid`, either as 16 byte blob or as hyphenated text
+//!
+//! Diesel only provides `FromSqlRow` for `uuid::Uuid` together with its
+//! postgres backend, so `Queryable` is provided here. The orphan rules don't
+//! allow `AsExpression` for `Uuid` towards `Binary` or `Text`, binding a
+//! `Uuid` requires a local type deriving `AsExpression` that delegates to
+//! these implementations.
+
+extern crate uuid;
+
+use diesel::deserialize::{self, FromSql, Queryable};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types;
+
+use crate::{backend::TursoBackend, value::TursoValue};
+
+impl FromSql<sql_types::Binary, TursoBackend> for uuid::Uuid {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        let bytes = value.try_blob()?;
+        uuid::Uuid::from_slice(bytes).map_err(|_| {
+            format!(
+                "Invalid UUID: expected 16 bytes, but got {} bytes",
+                bytes.len()
+            )
+            .into()
+        })
+    }
+}
+
+impl ToSql<sql_types::Binary, TursoBackend> for uuid::Uuid {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(self.as_bytes().as_slice());
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<sql_types::Binary, TursoBackend> for uuid::Uuid {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+impl FromSql<sql_types::Text, TursoBackend> for uuid::Uuid {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        let text = value.try_text()?;
+        uuid::Uuid::parse_str(text).map_err(|e| format!("Invalid UUID `{text}`: {e}").into())
+    }
+}
+
+impl ToSql<sql_types::Text, TursoBackend> for uuid::Uuid {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        out.set_value(self.hyphenated().to_string());
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<sql_types::Text, TursoBackend> for uuid::Uuid {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use diesel::dsl::sql;
+    use diesel::expression::AsExpression;
+    use diesel::serialize::{self, Output, ToSql};
+    use diesel::sql_types::{Binary, Text};
+    use diesel::{select, ExpressionMethods};
+    use diesel_async::RunQueryDsl;
+
+    use super::uuid::Uuid;
+    use crate::backend::TursoBackend;
+    use crate::tests::connection;
+
+    const UUID: &str = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+
+    // Diesel has no `AsExpression<Binary>`/`AsExpression<Text>` for `Uuid`
+    #[derive(Debug, AsExpression)]
+    #[diesel(sql_type = Binary)]
+    #[diesel(sql_type = Text)]
+    struct BoundUuid(Uuid);
+
+    impl ToSql<Binary, TursoBackend> for BoundUuid {
+        fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+            ToSql::<Binary, TursoBackend>::to_sql(&self.0, out)
+        }
+    }
+
+    impl ToSql<Text, TursoBackend> for BoundUuid {
+        fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+            ToSql::<Text, TursoBackend>::to_sql(&self.0, out)
+        }
+    }
+
+    #[tokio::test]
+    async fn uuid_encodes_as_blob_and_text() {
+        let connection = &mut connection().await;
+        let uuid = Uuid::parse_str(UUID).unwrap();
+
+        let query =
+            select(sql::<Binary>("X'67e5504410b1426f9247bb680e5fe0c8'").eq(BoundUuid(uuid)));
+        assert_eq!(Ok(true), query.get_result::<bool>(connection).await);
+
+        let query = select(sql::<Text>(&format!("'{UUID}'")).eq(BoundUuid(uuid)));
+        assert_eq!(Ok(true), query.get_result::<bool>(connection).await);
+    }
+
+    #[tokio::test]
+    async fn uuid_decodes_from_blob_and_text() {
+        let connection = &mut connection().await;
+        let uuid = Uuid::parse_str(UUID).unwrap();
+
+        let from_blob = select(sql::<Binary>("X'67e5504410b1426f9247bb680e5fe0c8'"))
+            .get_result::<Uuid>(connection)
+            .await;
+        assert_eq!(Ok(uuid), from_blob);
+
+        let from_text = select(sql::<Text>(&format!("'{UUID}'")))
+            .get_result::<Uuid>(connection)
+            .await;
+        assert_eq!(Ok(uuid), from_text);
+    }
+
+    #[tokio::test]
+    async fn malformed_uuids_fail_to_decode() {
+        let connection = &mut connection().await;
+
+        let short_blob = select(sql::<Binary>("X'0102'"))
+            .get_result::<Uuid>(connection)
+            .await;
+        assert!(short_blob.is_err());
+
+        let text_as_blob = select(sql::<Binary>("'not a blob'"))
+            .get_result::<Uuid>(connection)
+            .await;
+        assert!(text_as_blob.is_err());
+
+        let invalid_text = select(sql::<Text>("'not-a-uuid'"))
+            .get_result::<Uuid>(connection)
+            .await;
+        assert!(invalid_text.is_err());
+    }
+}
@@ -0,0 +1,321 @@
+//! Support for the vector types of the database engine
+//!
+//! `vector32()` produces a blob of little endian `f32` values, `vector64()`
+//! a blob of little endian `f64` values followed by a single type byte.
+//! `Vec<f32>` and `Vec<f64>` have no diesel support, so `Queryable` is
+//! provided here.
+
+use diesel::deserialize::{self, FromSql, Queryable};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+
+use crate::sql_types::{foreign_as_expression, Vector32, Vector64};
+use crate::{backend::TursoBackend, value::TursoValue};
+
+/// Type byte appended to blobs of `f32` vectors with an odd length
+const VECTOR32_TYPE: u8 = 1;
+/// Type byte appended to blobs of `f64` vectors
+const VECTOR64_TYPE: u8 = 2;
+
+foreign_as_expression!(Vec<f32> => Vector32);
+foreign_as_expression!(Vec<f64> => Vector64);
+
+/// The element data of a vector blob
+enum VectorBlob<'a> {
+    F32(&'a [u8]),
+    F64(&'a [u8]),
+}
+
+impl<'a> VectorBlob<'a> {
+    fn parse(blob: &'a [u8]) -> deserialize::Result<Self> {
+        // Blobs of an even length are always `f32` vectors without type byte
+        let (data, vector_type) = match blob.split_last() {
+            Some((&vector_type, data)) if blob.len() % 2 == 1 => (data, vector_type),
+            _ => (blob, VECTOR32_TYPE),
+        };
+        match vector_type {
+            VECTOR32_TYPE if data.len() % 4 == 0 => Ok(Self::F32(data)),
+            VECTOR64_TYPE if data.len() % 8 == 0 => Ok(Self::F64(data)),
+            _ => Err(format!(
+                "Invalid vector blob of {} bytes with type {vector_type}",
+                blob.len()
+            )
+            .into()),
+        }
+    }
+}
+
+fn decode_f32(data: &[u8]) -> Vec<f32> {
+    data.chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+fn decode_f64(data: &[u8]) -> Vec<f64> {
+    data.chunks_exact(8)
+        .map(|chunk| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(chunk);
+            f64::from_le_bytes(bytes)
+        })
+        .collect()
+}
+
+/// Parses the `[1.0, 2.0]` text form returned by `vector_extract()`
+fn parse_text<T: std::str::FromStr>(text: &str) -> deserialize::Result<Vec<T>> {
+    let elements = text
+        .trim()
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .ok_or_else(|| format!("Invalid vector `{text}`, expected `[...]`"))?;
+    if elements.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    elements
+        .split(',')
+        .map(|e| {
+            e.trim()
+                .parse()
+                .map_err(|_| format!("Invalid vector element `{}` in `{text}`", e.trim()).into())
+        })
+        .collect()
+}
+
+impl FromSql<Vector32, TursoBackend> for Vec<f32> {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        if let Some(text) = value.as_text() {
+            return parse_text(text);
+        }
+        let blob = value
+            .as_blob()
+            .ok_or_else(|| format!("Expected a vector blob, but got {}", value.storage_class()))?;
+        match VectorBlob::parse(blob)? {
+            VectorBlob::F32(data) => Ok(decode_f32(data)),
+            VectorBlob::F64(_) => Err("Expected a `vector32` blob, but got a `vector64` blob, \
+                 load it as `Vector64` instead"
+                .into()),
+        }
+    }
+}
+
+impl ToSql<Vector32, TursoBackend> for Vec<f32> {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        let blob = self
+            .iter()
+            .flat_map(|element| element.to_le_bytes())
+            .collect::<Vec<u8>>();
+        out.set_value(blob);
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<Vector32, TursoBackend> for Vec<f32> {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+impl FromSql<Vector64, TursoBackend> for Vec<f64> {
+    fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+        if let Some(text) = value.as_text() {
+            return parse_text(text);
+        }
+        let blob = value
+            .as_blob()
+            .ok_or_else(|| format!("Expected a vector blob, but got {}", value.storage_class()))?;
+        match VectorBlob::parse(blob)? {
+            VectorBlob::F32(data) => Ok(decode_f32(data).into_iter().map(f64::from).collect()),
+            VectorBlob::F64(data) => Ok(decode_f64(data)),
+        }
+    }
+}
+
+impl ToSql<Vector64, TursoBackend> for Vec<f64> {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+        let mut blob = self
+            .iter()
+            .flat_map(|element| element.to_le_bytes())
+            .collect::<Vec<u8>>();
+        blob.push(VECTOR64_TYPE);
+        out.set_value(blob);
+        Ok(IsNull::No)
+    }
+}
+
+impl Queryable<Vector64, TursoBackend> for Vec<f64> {
+    type Row = Self;
+
+    fn build(row: Self::Row) -> deserialize::Result<Self> {
+        Ok(row)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use diesel::dsl::sql;
+    use diesel::sql_types::Binary;
+    use diesel::{select, ExpressionMethods, IntoSql, QueryDsl};
+    use diesel_async::{RunQueryDsl, SimpleAsyncConnection};
+
+    use crate::dsl::TursoVectorExpressionMethods;
+    use crate::sql_types::{Vector32, Vector64};
+
+    table! {
+        documents {
+            id -> Integer,
+            embedding -> crate::sql_types::Vector32,
+            summary -> Nullable<crate::sql_types::Vector64>,
+        }
+    }
+
+    async fn documents(connection: &mut crate::AsyncTursoConnection) {
+        connection
+            .batch_execute(
+                "CREATE TABLE documents (
+                    id INTEGER PRIMARY KEY,
+                    embedding BLOB NOT NULL,
+                    summary BLOB
+                )",
+            )
+            .await
+            .unwrap();
+        diesel::insert_into(documents::table)
+            .values(vec![
+                (
+                    documents::id.eq(1),
+                    documents::embedding.eq(vec![1.0_f32, 0.0, 0.0]),
+                    documents::summary.eq(Some(vec![1.0_f64, 0.0])),
+                ),
+                (
+                    documents::id.eq(2),
+                    documents::embedding.eq(vec![0.0_f32, 1.0, 0.0]),
+                    documents::summary.eq(None::<Vec<f64>>),
+                ),
+                (
+                    documents::id.eq(3),
+                    documents::embedding.eq(vec![0.9_f32, 0.1, 0.0]),
+                    documents::summary.eq(Some(vec![0.0_f64, 1.0])),
+                ),
+            ])
+            .execute(connection)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn vector32_matches_vector_function() {
+        let connection = &mut crate::tests::connection().await;
+
+        let (ours, theirs) = select((
+            sql::<Binary>("").bind::<Vector32, _>(vec![1.5_f32, -2.0, 0.25]),
+            sql::<Binary>("vector32('[1.5, -2.0, 0.25]')"),
+        ))
+        .get_result::<(Vec<u8>, Vec<u8>)>(connection)
+        .await
+        .unwrap();
+        assert_eq!(ours, theirs);
+
+        let vector = select(sql::<Vector32>("vector32('[1.5, -2.0, 0.25]')"))
+            .get_result::<Vec<f32>>(connection)
+            .await
+            .unwrap();
+        assert_eq!(vector, vec![1.5, -2.0, 0.25]);
+    }
+
+    #[tokio::test]
+    async fn vector64_matches_vector_function() {
+        let connection = &mut crate::tests::connection().await;
+
+        let (ours, theirs) = select((
+            sql::<Binary>("").bind::<Vector64, _>(vec![0.1_f64, 1e300]),
+            sql::<Binary>("vector64('[0.1, 1e300]')"),
+        ))
+        .get_result::<(Vec<u8>, Vec<u8>)>(connection)
+        .await
+        .unwrap();
+        assert_eq!(ours, theirs);
+
+        let vector = select(vec![0.1_f64, 1e300].into_sql::<Vector64>())
+            .get_result::<Vec<f64>>(connection)
+            .await
+            .unwrap();
+        assert_eq!(vector, vec![0.1, 1e300]);
+
+        let widened = select(sql::<Vector64>("vector32('[0.5, 2]')"))
+            .get_result::<Vec<f64>>(connection)
+            .await
+            .unwrap();
+        assert_eq!(widened, vec![0.5, 2.0]);
+
+        let narrowed = select(sql::<Vector32>("vector64('[0.5, 2]')"))
+            .get_result::<Vec<f32>>(connection)
+            .await;
+        assert!(narrowed.is_err());
+    }
+
+    #[tokio::test]
+    async fn vector_extract_text() {
+        let connection = &mut crate::tests::connection().await;
+
+        let (text, vector) = select((
+            vec![1.0_f32, 2.5].into_sql::<Vector32>().vector_extract(),
+            sql::<Vector32>("'[1, 2.5]'"),
+        ))
+        .get_result::<(String, Vec<f32>)>(connection)
+        .await
+        .unwrap();
+        assert_eq!(text.replace(' ', ""), "[1,2.5]");
+        assert_eq!(vector, vec![1.0, 2.5]);
+    }
+
+    #[tokio::test]
+    async fn order_by_cosine_distance() {
+        let connection = &mut crate::tests::connection().await;
+        documents(connection).await;
+
+        let nearest = documents::table
+            .select(documents::id)
+            .order_by(documents::embedding.cosine_distance(vec![1.0_f32, 0.0, 0.0]))
+            .limit(2)
+            .load::<i32>(connection)
+            .await
+            .unwrap();
+        assert_eq!(nearest, vec![1, 3]);
+
+        let distances = documents::table
+            .select(documents::embedding.l2_distance(vec![0.0_f32, 1.0, 0.0]))
+            .order_by(documents::id)
+            .load::<f64>(connection)
+            .await
+            .unwrap();
+        assert!(distances[1].abs() < 1e-6);
+        assert!((distances[0] - 2.0_f64.sqrt()).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn nullable_vector_distance() {
+        let connection = &mut crate::tests::connection().await;
+        documents(connection).await;
+
+        let distances = documents::table
+            .select(documents::summary.cosine_distance(vec![1.0_f64, 0.0]))
+            .order_by(documents::id)
+            .load::<Option<f64>>(connection)
+            .await
+            .unwrap();
+        assert_eq!(distances.len(), 3);
+        assert!(distances[0].unwrap().abs() < 1e-9);
+        assert_eq!(distances[1], None);
+        assert!((distances[2].unwrap() - 1.0).abs() < 1e-9);
+
+        let extracted = documents::table
+            .select(documents::summary.vector_extract())
+            .filter(documents::id.eq(2))
+            .get_result::<Option<String>>(connection)
+            .await
+            .unwrap();
+        assert_eq!(extracted, None);
+    }
+}
@@ -0,0 +1,64 @@
+//! `SaveChangesDsl` support for `AsyncTursoConnection`
+
+use diesel::associations::HasTable;
+use diesel::prelude::*;
+use diesel::query_builder::IntoUpdateTarget;
+use diesel::query_dsl::methods::FindDsl;
+use diesel_async::methods::{ExecuteDsl, LoadQuery};
+use diesel_async::{RunQueryDsl, UpdateAndFetchResults};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+
+use crate::AsyncTursoConnection;
+
+// Uses `UPDATE ... RETURNING`, and like diesel's SQLite backend an update
+// followed by a lookup of the primary key if the database doesn't support it
+impl<'b, Changes, Output> UpdateAndFetchResults<Changes, Output> for AsyncTursoConnection
+where
+    Output: Send + 'static,
+    Changes: Copy + Identifiable + Send + 'b,
+    Changes: AsChangeset<Target = <Changes as HasTable>::Table> + IntoUpdateTarget,
+    Changes::Table: FindDsl<Changes::Id> + Send,
+    Changes::WhereClause: Send,
+    Changes::Changeset: Send,
+    Changes::Id: Send,
+    diesel::dsl::Update<Changes, Changes>:
+        ExecuteDsl<AsyncTursoConnection> + LoadQuery<'b, AsyncTursoConnection, Output> + Send + 'b,
+    diesel::dsl::Find<Changes::Table, Changes::Id>:
+        LoadQuery<'b, AsyncTursoConnection, Output> + Send + 'b,
+    <Changes::Table as diesel::QuerySource>::FromClause: Send,
+{
+    fn update_and_fetch<'conn, 'changes>(
+        &'conn mut self,
+        changeset: Changes,
+    ) -> BoxFuture<'changes, QueryResult<Output>>
+    where
+        Changes: 'changes,
+        Changes::Changeset: 'changes,
+        'conn: 'changes,
+        Self: 'changes,
+    {
+        async move {
+            if self.supports_returning {
+                let result = diesel::update(changeset)
+                    .set(changeset)
+                    .get_result(&mut *self)
+                    .await;
+                // The statement failed before changing anything if `RETURNING`
+                // turned out to be unsupported
+                if result.is_ok() || self.supports_returning {
+                    return result;
+                }
+            }
+            diesel::update(changeset)
+                .set(changeset)
+                .execute(&mut *self)
+                .await?;
+            Changes::table()
+                .find(changeset.id())
+                .get_result(&mut *self)
+                .await
+        }
+        .boxed()
+    }
+}
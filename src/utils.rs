@@ -33,3 +33,8 @@ impl DatabaseErrorInformation for TursoError {
         None
     }
 }
+
+/// Quotes `name` for use as an identifier in SQL
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
@@ -1,10 +1,75 @@
+//! Raw values read from and written to the database
+//!
+//! [`TursoValue`] is the extension point for types this crate doesn't know
+//! about. `FromSql<_, TursoBackend>` impls receive a `TursoValue`, inspect
+//! its [storage class](TursoValue::storage_class) and read it with one of the
+//! borrowing `as_*` accessors or the fallible `try_*` getters. `ToSql` impls
+//! pass anything convertible into a `TursoValue` to
+//! [`Output::set_value`](diesel::serialize::Output::set_value).
+//!
+//! ```rust
+//! # use diesel::deserialize::{self, FromSql};
+//! # use diesel::serialize::{self, IsNull, Output, ToSql};
+//! # use diesel::sql_types::Text;
+//! # use diesel_turso::backend::TursoBackend;
+//! # use diesel_turso::TursoValue;
+//! #[derive(Debug)]
+//! struct Tags(Vec<String>);
+//!
+//! impl FromSql<Text, TursoBackend> for Tags {
+//!     fn from_sql(value: TursoValue) -> deserialize::Result<Self> {
+//!         let text = value.try_text()?;
+//!         Ok(Tags(text.split(',').map(str::to_owned).collect()))
+//!     }
+//! }
+//!
+//! impl ToSql<Text, TursoBackend> for Tags {
+//!     fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, TursoBackend>) -> serialize::Result {
+//!         out.set_value(self.0.join(","));
+//!         Ok(IsNull::No)
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+
+use diesel::deserialize;
 use turso::Value;
 
-#[derive(Debug)]
+/// A single value of a row or a bind parameter
+///
+/// See the [module documentation](self) for how to use it in `FromSql` and
+/// `ToSql` impls.
+#[derive(Debug, Clone)]
 pub struct TursoValue {
     value: Value,
 }
 
+/// The storage class of a [`TursoValue`]
+///
+/// Every value stored in the database has one of these classes, independent
+/// of the declared type of its column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TursoStorageClass {
+    Null,
+    Integer,
+    Real,
+    Text,
+    Blob,
+}
+
+impl fmt::Display for TursoStorageClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Null => "NULL",
+            Self::Integer => "INTEGER",
+            Self::Real => "REAL",
+            Self::Text => "TEXT",
+            Self::Blob => "BLOB",
+        })
+    }
+}
+
 impl From<bool> for TursoValue {
     fn from(value: bool) -> Self {
         Self::from_turso_value(Value::Integer(if value { 1 } else { 0 }))
@@ -59,6 +124,12 @@ impl From<()> for TursoValue {
     }
 }
 
+impl From<&str> for TursoValue {
+    fn from(value: &str) -> Self {
+        Self::from_turso_value(Value::Text(value.to_owned()))
+    }
+}
+
 impl From<&[u8]> for TursoValue {
     fn from(value: &[u8]) -> Self {
         Self::from_turso_value(Value::Blob(value.to_vec()))
@@ -66,10 +137,12 @@ impl From<&[u8]> for TursoValue {
 }
 
 impl TursoValue {
+    /// Wraps a value of the underlying driver
     pub fn from_turso_value(value: Value) -> Self {
         Self { value }
     }
 
+    /// Returns a copy of the value of the underlying driver
     pub fn to_turso_value(&self) -> Value {
         self.value.clone()
     }
@@ -104,14 +177,98 @@ impl TursoValue {
         }
     }
 
-    pub(crate) fn parse_string<R>(&self, f: impl FnOnce(&str) -> R) -> R {
+    /// Returns the bytes if the value is a blob
+    pub fn as_blob(&self) -> Option<&[u8]> {
         match &self.value {
-            Value::Text(s) => f(s),
-            _ => panic!("Value is not a string, but {:?}", self.value),
+            Value::Blob(b) => Some(b),
+            _ => None,
         }
     }
 
+    /// Returns the integer if the value is an integer
+    pub fn as_integer(&self) -> Option<i64> {
+        match &self.value {
+            Value::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the float if the value is a real
+    pub fn as_real(&self) -> Option<f64> {
+        match &self.value {
+            Value::Real(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Returns the text if the value is a string
+    pub fn as_text(&self) -> Option<&str> {
+        match &self.value {
+            Value::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the value is `NULL`
     pub fn is_null(&self) -> bool {
         matches!(self.value, Value::Null)
     }
+
+    /// Returns the storage class of the value
+    pub fn storage_class(&self) -> TursoStorageClass {
+        match self.value {
+            Value::Null => TursoStorageClass::Null,
+            Value::Integer(_) => TursoStorageClass::Integer,
+            Value::Real(_) => TursoStorageClass::Real,
+            Value::Text(_) => TursoStorageClass::Text,
+            Value::Blob(_) => TursoStorageClass::Blob,
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> deserialize::Result<T> {
+        Err(format!("Expected {expected}, but got {}", self.storage_class()).into())
+    }
+
+    /// Returns the text, or an error if the value isn't `TEXT`
+    pub fn try_text(&self) -> deserialize::Result<&str> {
+        match self.as_text() {
+            Some(text) => Ok(text),
+            None => self.unexpected("a TEXT value"),
+        }
+    }
+
+    /// Returns the bytes, or an error if the value isn't a `BLOB`
+    pub fn try_blob(&self) -> deserialize::Result<&[u8]> {
+        match self.as_blob() {
+            Some(blob) => Ok(blob),
+            None => self.unexpected("a BLOB value"),
+        }
+    }
+
+    /// Returns the integer, or an error if the value isn't an `INTEGER`
+    pub fn try_integer(&self) -> deserialize::Result<i64> {
+        match self.as_integer() {
+            Some(integer) => Ok(integer),
+            None => self.unexpected("an INTEGER value"),
+        }
+    }
+
+    /// Returns the number as float, or an error if the value is neither
+    /// `REAL` nor `INTEGER`
+    pub fn try_real(&self) -> deserialize::Result<f64> {
+        match self.value {
+            Value::Real(f) => Ok(f),
+            Value::Integer(i) => Ok(i as f64),
+            _ => self.unexpected("a REAL or INTEGER value"),
+        }
+    }
+
+    /// Returns `false` for `0` and `true` for any other integer, or an error
+    /// if the value isn't an `INTEGER`
+    pub fn try_bool(&self) -> deserialize::Result<bool> {
+        match self.as_integer() {
+            Some(integer) => Ok(integer != 0),
+            None => self.unexpected("an INTEGER value"),
+        }
+    }
 }